@@ -0,0 +1,208 @@
+//! Typed I²C addresses.
+//!
+//! All MCP342x devices use a 7-bit I²C address in the range `0x68`–`0x6F`.
+//! The upper four bits are fixed to `1101`, the lower three bits are either
+//! programmed at the factory (MCP3421/2/5/6, selected by the ordering code) or
+//! configured with the `Adr0` and `Adr1` pins (MCP3423/4/7/8).
+//!
+//! ```
+//! use mcp3425::address::{Address, AdrPin};
+//!
+//! let address = Address::from_pins(AdrPin::Low, AdrPin::Float);
+//! assert_eq!(address.value(), 0x69);
+//! ```
+//!
+//! For the factory programmed variants (MCP3421/2/5/6), there is a constant
+//! for each ordering code. The variants with address pins (MCP3423/4/7/8)
+//! have constants for the common pin strappings:
+//!
+//! ```
+//! use mcp3425::address;
+//!
+//! // MCP3425A5T-E/CH
+//! assert_eq!(address::MCP3425_A5.value(), 0x6D);
+//! // MCP3428 with Adr0 and Adr1 tied to VDD
+//! assert_eq!(address::MCP3428_ALL_HIGH.value(), 0x6E);
+//! ```
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// Lowest address of the MCP342x family: `0b1101000`
+const BASE: u8 = 0x68;
+
+macro_rules! factory_addresses {
+    ($model:literal; $($name:ident = $bits:literal),+) => {
+        $(
+            #[doc = concat!("Address of the `", $model, "A", $bits, "` ordering code.")]
+            pub const $name: Address = Address(BASE | $bits);
+        )+
+    };
+}
+
+factory_addresses!("MCP3421";
+    MCP3421_A0 = 0, MCP3421_A1 = 1, MCP3421_A2 = 2, MCP3421_A3 = 3,
+    MCP3421_A4 = 4, MCP3421_A5 = 5, MCP3421_A6 = 6, MCP3421_A7 = 7
+);
+factory_addresses!("MCP3422";
+    MCP3422_A0 = 0, MCP3422_A1 = 1, MCP3422_A2 = 2, MCP3422_A3 = 3,
+    MCP3422_A4 = 4, MCP3422_A5 = 5, MCP3422_A6 = 6, MCP3422_A7 = 7
+);
+factory_addresses!("MCP3425";
+    MCP3425_A0 = 0, MCP3425_A1 = 1, MCP3425_A2 = 2, MCP3425_A3 = 3,
+    MCP3425_A4 = 4, MCP3425_A5 = 5, MCP3425_A6 = 6, MCP3425_A7 = 7
+);
+factory_addresses!("MCP3426";
+    MCP3426_A0 = 0, MCP3426_A1 = 1, MCP3426_A2 = 2, MCP3426_A3 = 3,
+    MCP3426_A4 = 4, MCP3426_A5 = 5, MCP3426_A6 = 6, MCP3426_A7 = 7
+);
+
+macro_rules! strapped_addresses {
+    ($model:literal; $all_low:ident, $all_high:ident, $all_float:ident) => {
+        #[doc = concat!("Address of an ", $model, " with `Adr0` and `Adr1` tied to VSS.")]
+        pub const $all_low: Address = Address(BASE);
+        #[doc = concat!("Address of an ", $model, " with `Adr0` and `Adr1` tied to VDD.")]
+        pub const $all_high: Address = Address(BASE | 0b110);
+        #[doc = concat!("Address of an ", $model, " with `Adr0` and `Adr1` left floating.")]
+        pub const $all_float: Address = Address(BASE);
+    };
+}
+
+strapped_addresses!("MCP3423"; MCP3423_ALL_LOW, MCP3423_ALL_HIGH, MCP3423_ALL_FLOAT);
+strapped_addresses!("MCP3424"; MCP3424_ALL_LOW, MCP3424_ALL_HIGH, MCP3424_ALL_FLOAT);
+strapped_addresses!("MCP3427"; MCP3427_ALL_LOW, MCP3427_ALL_HIGH, MCP3427_ALL_FLOAT);
+strapped_addresses!("MCP3428"; MCP3428_ALL_LOW, MCP3428_ALL_HIGH, MCP3428_ALL_FLOAT);
+
+/// Logic level of an address selection pin (`Adr0` or `Adr1`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AdrPin {
+    /// Pin tied to VSS.
+    Low,
+    /// Pin tied to VDD.
+    High,
+    /// Pin left floating.
+    Float,
+}
+
+/// A valid 7-bit I²C address of an MCP342x device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Address(u8);
+
+/// Error returned when converting a value outside `0x68`–`0x6F` to an
+/// [`Address`](struct.Address.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidAddress(pub u8);
+
+impl fmt::Display for InvalidAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid MCP342x address: {:#04x}", self.0)
+    }
+}
+
+impl Address {
+    /// Return the address selected by the `Adr0` and `Adr1` pins of an
+    /// MCP3423/4/7/8.
+    ///
+    /// See datasheet table 5-3.
+    pub fn from_pins(adr0: AdrPin, adr1: AdrPin) -> Self {
+        let bits = match (adr0, adr1) {
+            (AdrPin::Low, AdrPin::Low) => 0b000,
+            (AdrPin::Low, AdrPin::Float) => 0b001,
+            (AdrPin::Low, AdrPin::High) => 0b010,
+            (AdrPin::High, AdrPin::Low) => 0b100,
+            (AdrPin::High, AdrPin::Float) => 0b101,
+            (AdrPin::High, AdrPin::High) => 0b110,
+            (AdrPin::Float, AdrPin::Low) => 0b011,
+            (AdrPin::Float, AdrPin::High) => 0b111,
+            (AdrPin::Float, AdrPin::Float) => 0b000,
+        };
+        Address(BASE | bits)
+    }
+
+    /// Return the 7-bit address.
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// Return all eight addresses of the MCP342x family, in ascending order.
+    pub fn all() -> impl Iterator<Item = Address> {
+        (0..8).map(|bits| Address(BASE | bits))
+    }
+}
+
+impl TryFrom<u8> for Address {
+    type Error = InvalidAddress;
+
+    /// Validate a 7-bit address.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value & !0b111 == BASE {
+            Ok(Address(value))
+        } else {
+            Err(InvalidAddress(value))
+        }
+    }
+}
+
+impl From<Address> for u8 {
+    fn from(address: Address) -> u8 {
+        address.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(AdrPin::Low, AdrPin::Low, 0x68)]
+    #[case(AdrPin::Low, AdrPin::Float, 0x69)]
+    #[case(AdrPin::Low, AdrPin::High, 0x6A)]
+    #[case(AdrPin::Float, AdrPin::Low, 0x6B)]
+    #[case(AdrPin::High, AdrPin::Low, 0x6C)]
+    #[case(AdrPin::High, AdrPin::Float, 0x6D)]
+    #[case(AdrPin::High, AdrPin::High, 0x6E)]
+    #[case(AdrPin::Float, AdrPin::High, 0x6F)]
+    #[case(AdrPin::Float, AdrPin::Float, 0x68)]
+    fn test_from_pins(#[case] adr0: AdrPin, #[case] adr1: AdrPin, #[case] expected: u8) {
+        assert_eq!(Address::from_pins(adr0, adr1).value(), expected);
+    }
+
+    #[rstest]
+    #[case(0x68, true)]
+    #[case(0x6F, true)]
+    #[case(0x67, false)]
+    #[case(0x70, false)]
+    #[case(0xD0, false)] // 8-bit write address of 0x68
+    fn test_try_from(#[case] value: u8, #[case] valid: bool) {
+        assert_eq!(Address::try_from(value).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(MCP3425_A0.value(), 0x68);
+        assert_eq!(MCP3421_A7.value(), 0x6F);
+        assert_eq!(MCP3422_A2.value(), 0x6A);
+        assert_eq!(MCP3426_A1.value(), 0x69);
+        assert_eq!(
+            MCP3428_ALL_LOW,
+            Address::from_pins(AdrPin::Low, AdrPin::Low)
+        );
+        assert_eq!(
+            MCP3428_ALL_HIGH,
+            Address::from_pins(AdrPin::High, AdrPin::High)
+        );
+        assert_eq!(
+            MCP3428_ALL_FLOAT,
+            Address::from_pins(AdrPin::Float, AdrPin::Float)
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        let mut all = Address::all();
+        assert_eq!(all.next().map(u8::from), Some(0x68));
+        assert_eq!(all.last().map(u8::from), Some(0x6F));
+    }
+}
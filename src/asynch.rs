@@ -0,0 +1,1200 @@
+//! Async driver based on the
+//! [`embedded-hal-async`](https://docs.rs/embedded-hal-async) traits.
+//!
+//! The API mirrors the blocking [`MCP3425`](../struct.MCP3425.html) driver,
+//! but all methods that talk to the device return futures. Waiting for a
+//! conversion to finish is done with the [`Wait`](trait.Wait.html) trait,
+//! which is implemented for every async `DelayNs`, so the executor can run
+//! other tasks in the meantime.
+//!
+//! ## Cancellation
+//!
+//! All futures returned by the driver can be dropped at any `.await` point.
+//! If a one-shot [`measure`](struct.MCP3425.html#method.measure) future is
+//! dropped while the conversion is running, the driver remembers that a
+//! conversion may still be pending. The next measurement first waits for that
+//! conversion to finish and discards its result, so a stale or half-finished
+//! conversion is never returned.
+//!
+//! Only available if the `async` Cargo feature is enabled.
+
+use core::marker::PhantomData;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::{Error as _, ErrorKind, I2c};
+
+use crate::calibration::{ChannelCalibrations, Tempco, Temperature, TemperatureFn};
+use crate::model::{AnyModel, Model};
+pub use crate::sampler::Clock;
+use crate::{
+    calculate_voltage, decode_reading, Config, ConfigRegister, ContinuousMode, ConversionMode,
+    DeviceStatus, DriverError, Error, MeasureError, OneShotMode, Presence, ReadError, Resolution,
+    RetryPolicy, Voltage, GENERAL_CALL_ADDRESS, GENERAL_CALL_CONVERSION, MAX_PACING_POLLS,
+    READY_POLL_INTERVAL_US,
+};
+
+/// Start a conversion on all MCP342x devices on the bus at the same time.
+///
+/// Async version of
+/// [`general_call_conversion`](../fn.general_call_conversion.html).
+pub async fn general_call_conversion<I2C: I2c>(i2c: &mut I2C) -> Result<(), I2C::Error> {
+    i2c.write(GENERAL_CALL_ADDRESS, &[GENERAL_CALL_CONVERSION])
+        .await
+}
+
+/// A generic way of waiting for a conversion to finish.
+///
+/// The driver only needs to wait for a given number of microseconds. This
+/// trait is implemented for every `embedded_hal_async::delay::DelayNs`, but it
+/// can also be implemented for the timers of async runtimes that don't
+/// provide a `DelayNs` implementation.
+///
+/// With the `embassy` Cargo feature, the
+/// [`EmbassyTimer`](struct.EmbassyTimer.html) implementation based on
+/// `embassy_time::Timer` is available.
+pub trait Wait {
+    /// Wait for the specified number of microseconds.
+    #[allow(async_fn_in_trait)]
+    async fn wait_us(&mut self, us: u32);
+}
+
+impl<T: DelayNs> Wait for T {
+    async fn wait_us(&mut self, us: u32) {
+        self.delay_us(us).await
+    }
+}
+
+/// Wait for conversions with `embassy_time::Timer`.
+///
+/// This allows using the async driver in an
+/// [Embassy](https://embassy.dev/) application without having to pass in a
+/// `DelayNs` implementation. For example, on an RP2040:
+///
+/// ```ignore
+/// use embassy_executor::Spawner;
+/// use embassy_rp::{bind_interrupts, i2c, peripherals::I2C0};
+/// use mcp3425::{asynch::{EmbassyTimer, MCP3425}, Config};
+///
+/// bind_interrupts!(struct Irqs {
+///     I2C0_IRQ => i2c::InterruptHandler<I2C0>;
+/// });
+///
+/// #[embassy_executor::main]
+/// async fn main(_spawner: Spawner) {
+///     let p = embassy_rp::init(Default::default());
+///     let i2c = i2c::I2c::new_async(p.I2C0, p.PIN_5, p.PIN_4, Irqs, i2c::Config::default());
+///     let mut adc = MCP3425::oneshot(i2c, 0x68, EmbassyTimer);
+///     loop {
+///         let voltage = adc.measure(&Config::default()).await;
+///         defmt::info!("Measured: {}", defmt::Debug2Format(&voltage));
+///     }
+/// }
+/// ```
+///
+/// Only available if the `embassy` Cargo feature is enabled.
+#[cfg(feature = "embassy")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct EmbassyTimer;
+
+#[cfg(feature = "embassy")]
+impl Wait for EmbassyTimer {
+    async fn wait_us(&mut self, us: u32) {
+        embassy_time::Timer::after_micros(u64::from(us)).await
+    }
+}
+
+/// Maximum number of times the ready flag is read in
+/// [`set_config`](struct.MCP3425.html#method.set_config).
+const MAX_READY_POLLS: u8 = 8;
+
+/// Async driver for the MCP3425 ADC
+///
+/// Like the blocking driver, it is generic over the device
+/// [`model`](../model/index.html), which is used to reject channels the
+/// device does not have.
+#[derive(Debug, Default)]
+pub struct MCP3425<I2C, D, M, DEV = AnyModel> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    /// The I²C device address.
+    address: u8,
+    /// The concrete Delay implementation.
+    delay: D,
+    /// The ADC conversion mode.
+    mode: M,
+    /// The configuration being used by the last measurement.
+    config: Option<Config>,
+    /// Resolution of a one-shot conversion that was started, but whose result
+    /// has not been read yet (e.g. because the future was cancelled).
+    pending: Option<Resolution>,
+    /// Retry behavior for transient errors.
+    retry: RetryPolicy,
+    /// Calibrations for configurations without their own calibration.
+    calibrations: ChannelCalibrations,
+    /// Temperature coefficients of the offset and gain error.
+    tempco: Option<Tempco>,
+    /// The temperature for the tempco compensation.
+    temperature: Option<Temperature>,
+    /// The device model.
+    model: PhantomData<DEV>,
+}
+
+impl<I2C, D, M> MCP3425<I2C, D, M>
+where
+    I2C: I2c,
+    D: Wait,
+    M: ConversionMode,
+{
+    /// Initialize the MCP3425 driver.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn new(i2c: I2C, address: u8, delay: D, mode: M) -> Self {
+        MCP3425 {
+            i2c,
+            address,
+            delay,
+            mode,
+            config: None,
+            pending: None,
+            retry: RetryPolicy::default(),
+            calibrations: ChannelCalibrations::new(),
+            tempco: None,
+            temperature: None,
+            model: PhantomData,
+        }
+    }
+}
+
+impl<I2C, D, M, DEV> MCP3425<I2C, D, M, DEV>
+where
+    I2C: I2c,
+    D: Wait,
+    M: ConversionMode,
+    DEV: Model,
+{
+    /// Specify the device model.
+    ///
+    /// See [`MCP3425::with_model`](../struct.MCP3425.html#method.with_model).
+    pub fn with_model<NEW: Model>(self, _model: NEW) -> MCP3425<I2C, D, M, NEW> {
+        MCP3425 {
+            i2c: self.i2c,
+            address: self.address,
+            delay: self.delay,
+            mode: self.mode,
+            config: self.config,
+            pending: self.pending,
+            retry: self.retry,
+            calibrations: self.calibrations,
+            tempco: self.tempco,
+            temperature: self.temperature,
+            model: PhantomData,
+        }
+    }
+
+    /// Change the conversion mode, keeping all other settings.
+    fn into_mode<N: ConversionMode>(self, mode: N) -> MCP3425<I2C, D, N, DEV> {
+        MCP3425 {
+            i2c: self.i2c,
+            address: self.address,
+            delay: self.delay,
+            mode,
+            config: None,
+            pending: None,
+            retry: self.retry,
+            calibrations: self.calibrations,
+            tempco: self.tempco,
+            temperature: self.temperature,
+            model: PhantomData,
+        }
+    }
+
+    /// Make sure that the device model has the configured channel.
+    ///
+    /// Otherwise, the device would ignore the channel selection bits and
+    /// measure a different input.
+    fn check_channel(&self, config: &Config) -> Result<(), MeasureError<I2C::Error>> {
+        if config.channel.index() < DEV::CHANNELS {
+            Ok(())
+        } else {
+            Err(MeasureError::ChannelNotSupported)
+        }
+    }
+
+    /// Read the output code and the configuration register from the device.
+    async fn read_code_and_config<Err: DriverError<Bus = I2C::Error>>(
+        &mut self,
+        resolution: &Resolution,
+    ) -> Result<(i32, ConfigRegister), Err> {
+        let mut buf = [0; 4];
+        let buf = &mut buf[..resolution.read_len()];
+        self.i2c.read(self.address, buf).await.map_err(Err::i2c)?;
+        Ok(decode_reading(buf))
+    }
+
+    /// Read from the device until the RDY bit is cleared.
+    ///
+    /// `waited_us` is the time already waited since the conversion was
+    /// started. Return `None` if the result is not ready once the maximum
+    /// conversion time of the resolution has passed.
+    async fn poll_until_ready<Err: DriverError<Bus = I2C::Error>>(
+        &mut self,
+        resolution: &Resolution,
+        mut waited_us: u32,
+    ) -> Result<Option<i32>, Err> {
+        let timeout_us = resolution.max_conversion_time().as_micros() as u32;
+        loop {
+            let (measurement, config_reg) = self.read_code_and_config(resolution).await?;
+            if config_reg.is_ready() {
+                return Ok(Some(measurement));
+            }
+            if waited_us >= timeout_us {
+                return Ok(None);
+            }
+            // Not yet ready, wait some more time
+            self.delay.wait_us(READY_POLL_INTERVAL_US).await;
+            waited_us = waited_us.saturating_add(READY_POLL_INTERVAL_US);
+        }
+    }
+
+    /// Read and decode the configuration register of the device, without
+    /// changing its configuration or starting a conversion.
+    ///
+    /// See [`MCP3425::read_status`](../struct.MCP3425.html#method.read_status).
+    pub async fn read_status(&mut self) -> Result<DeviceStatus, Error<I2C::Error>> {
+        let mut buf = [0; 4];
+        self.i2c
+            .read(self.address, &mut buf)
+            .await
+            .map_err(Error::I2c)?;
+        DeviceStatus::try_from(buf[3]).map_err(Error::InvalidConfig)
+    }
+
+    /// Check whether the device is present, without changing its
+    /// configuration or starting a conversion.
+    ///
+    /// See [`MCP3425::ping`](../struct.MCP3425.html#method.ping).
+    pub async fn ping(&mut self) -> Result<Presence, Error<I2C::Error>> {
+        let mut buf = [0; 4];
+        match self.i2c.read(self.address, &mut buf).await {
+            Ok(()) => Ok(Presence::from_config(buf[3])),
+            Err(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => Ok(Presence::Absent),
+            Err(e) => Err(Error::I2c(e)),
+        }
+    }
+
+    /// Set the retry behavior for transient errors in
+    /// [`measure`](struct.MCP3425.html#method.measure) and
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement).
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    /// Set the calibration of every channel, which is applied to all
+    /// configurations without their own calibration.
+    ///
+    /// See [`MCP3425::set_channel_calibrations`](../struct.MCP3425.html#method.set_channel_calibrations).
+    pub fn set_channel_calibrations(&mut self, calibrations: ChannelCalibrations) {
+        self.calibrations = calibrations;
+    }
+
+    /// Return the calibration of every channel.
+    pub fn channel_calibrations(&self) -> &ChannelCalibrations {
+        &self.calibrations
+    }
+
+    /// Return `config` with the calibration of its channel, unless it
+    /// already has a calibration.
+    fn calibrated(&self, config: &Config) -> Config {
+        self.calibrations.apply(config)
+    }
+
+    /// Set the temperature coefficients of the offset and gain error, or
+    /// `None` to disable the temperature compensation.
+    ///
+    /// See [`MCP3425::set_tempco`](../struct.MCP3425.html#method.set_tempco).
+    pub fn set_tempco(&mut self, tempco: Option<Tempco>) {
+        self.tempco = tempco;
+    }
+
+    /// Set the current temperature in millidegrees Celsius for the tempco
+    /// compensation.
+    ///
+    /// This replaces a source set with
+    /// [`set_temperature_source`](struct.MCP3425.html#method.set_temperature_source).
+    pub fn set_temperature(&mut self, temperature_mc: i32) {
+        self.temperature = Some(Temperature::Fixed(temperature_mc));
+    }
+
+    /// Set a function that is called for the current temperature whenever a
+    /// voltage is calculated with tempco compensation.
+    ///
+    /// This replaces a temperature set with
+    /// [`set_temperature`](struct.MCP3425.html#method.set_temperature).
+    pub fn set_temperature_source(&mut self, read_mc: TemperatureFn) {
+        self.temperature = Some(Temperature::Source(read_mc));
+    }
+
+    /// Convert `measurement` to a voltage with the calibration of `config`,
+    /// compensated for the current temperature.
+    fn voltage<Err: DriverError>(&self, measurement: i32, config: &Config) -> Result<Voltage, Err> {
+        let config = Tempco::apply(self.tempco, self.temperature, config);
+        calculate_voltage(measurement, &config)
+    }
+
+    /// Return a mutable reference to the I2C device, e.g. for bus recovery
+    /// sequences.
+    ///
+    /// The configuration cached by the driver is not updated, so don't use
+    /// this to change the configuration of the device.
+    pub fn bus_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
+    }
+
+    /// Return the I²C device address.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Change the I²C device address, e.g. after the address of an MCP342x
+    /// device was changed by latching its address pins.
+    pub fn set_address(&mut self, address: u8) {
+        self.address = address;
+    }
+
+    /// Destroy the driver instance and return the I2C device.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Destroy the driver instance and return the I2C device and the delay.
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+impl<I2C, D> MCP3425<I2C, D, OneShotMode>
+where
+    I2C: I2c,
+    D: Wait,
+{
+    /// Initialize the MCP3425 driver in One-Shot mode.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn oneshot(i2c: I2C, address: u8, delay: D) -> Self {
+        MCP3425::new(i2c, address, delay, OneShotMode)
+    }
+}
+
+impl<I2C, D, DEV> MCP3425<I2C, D, OneShotMode, DEV>
+where
+    I2C: I2c,
+    D: Wait,
+    DEV: Model,
+{
+    /// Change the conversion mode to continuous.
+    ///
+    /// This conversion is side-effect free, so it will not write any
+    /// configuration to the device until
+    /// [`set_config`](struct.MCP3425.html#method.set_config) is called.
+    pub fn into_continuous(self) -> MCP3425<I2C, D, ContinuousMode, DEV> {
+        self.into_mode(ContinuousMode)
+    }
+
+    /// Do a one-shot voltage measurement.
+    ///
+    /// Return the result in millivolts.
+    ///
+    /// After the typical conversion time, the result is polled until the
+    /// [maximum conversion time](../enum.Resolution.html#method.max_conversion_time)
+    /// has passed, like in the blocking driver.
+    ///
+    /// Transient errors are retried according to the
+    /// [`RetryPolicy`](../struct.RetryPolicy.html).
+    pub async fn measure(&mut self, config: &Config) -> Result<Voltage, MeasureError<I2C::Error>> {
+        let mut attempt = 1;
+        let mut first_error = None;
+        loop {
+            match self.measure_once(config).await {
+                Err(e) if e.is_transient() => {
+                    if attempt >= self.retry.max_attempts {
+                        return Err(first_error.unwrap_or(e));
+                    }
+                    first_error.get_or_insert(e);
+                    attempt += 1;
+                    self.delay
+                        .wait_us(self.retry.backoff_ms.saturating_mul(1000))
+                        .await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Do a single one-shot voltage measurement attempt.
+    async fn measure_once(&mut self, config: &Config) -> Result<Voltage, MeasureError<I2C::Error>> {
+        self.check_channel(config)?;
+        let command = ConfigRegister::NOT_READY.bits() | self.mode.bits() | config.bits();
+
+        // Let a conversion started by a cancelled measurement run out
+        self.finish_pending_conversion().await?;
+
+        // Send command. Mark the conversion as pending before the write, in
+        // case the future is dropped while the command is being sent.
+        self.pending = Some(config.resolution);
+        self.i2c
+            .write(self.address, &[command])
+            .await
+            .map_err(MeasureError::I2c)?;
+
+        // Wait for the conversion to finish, then poll until the maximum
+        // conversion time has passed
+        let conversion_time_us = config.resolution.conversion_time_us();
+        self.delay.wait_us(conversion_time_us).await;
+        let measurement = self
+            .poll_until_ready(&config.resolution, conversion_time_us)
+            .await?
+            .ok_or(MeasureError::NotReady)?;
+        self.pending = None;
+
+        // Calculate voltage from raw value
+        self.voltage(measurement, &self.calibrated(config))
+    }
+
+    /// Return whether a conversion started by a cancelled
+    /// [`measure`](struct.MCP3425.html#method.measure) future may still be
+    /// running.
+    pub fn has_pending_conversion(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Wait until a conversion that was started by a cancelled measurement
+    /// has finished, and discard its result.
+    ///
+    /// If the conversion does not finish within the maximum conversion time,
+    /// [`MeasureError::NotReady`](../enum.MeasureError.html#variant.NotReady)
+    /// is returned and it is waited for again by the next measurement.
+    async fn finish_pending_conversion(&mut self) -> Result<(), MeasureError<I2C::Error>> {
+        if let Some(resolution) = self.pending {
+            let (_, config_reg) = self.read_code_and_config(&resolution).await?;
+            if !config_reg.is_ready() {
+                let conversion_time_us = resolution.conversion_time_us();
+                self.delay.wait_us(conversion_time_us).await;
+                self.poll_until_ready(&resolution, conversion_time_us)
+                    .await?
+                    .ok_or(MeasureError::NotReady)?;
+            }
+            self.pending = None;
+        }
+        Ok(())
+    }
+}
+
+impl<I2C, D> MCP3425<I2C, D, ContinuousMode>
+where
+    I2C: I2c,
+    D: Wait,
+{
+    /// Initialize the MCP3425 driver in Continuous Measurement mode.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn continuous(i2c: I2C, address: u8, delay: D) -> Self {
+        MCP3425::new(i2c, address, delay, ContinuousMode)
+    }
+}
+
+impl<I2C, D, DEV> MCP3425<I2C, D, ContinuousMode, DEV>
+where
+    I2C: I2c,
+    D: Wait,
+    DEV: Model,
+{
+    /// Change the conversion mode to one-shot.
+    ///
+    /// This conversion is side-effect free, so it will not write any
+    /// configuration to the device until a first one-shot measurement is
+    /// triggered.
+    pub fn into_oneshot(self) -> MCP3425<I2C, D, OneShotMode, DEV> {
+        self.into_mode(OneShotMode)
+    }
+
+    /// Write the specified configuration to the device and wait until the
+    /// first measurement is ready.
+    ///
+    /// Instead of busy-polling the device, the driver waits for the
+    /// conversion time of the configured resolution, and then re-checks the
+    /// ready flag up to 8 times in intervals of a quarter conversion time. If
+    /// the measurement is still not ready after that, the device is considered
+    /// wedged and [`Error::Timeout`](../enum.Error.html#variant.Timeout) is
+    /// returned.
+    ///
+    /// Return the number of times the ready flag was read, for diagnostics.
+    ///
+    /// Note: Since the wait-until-ready logic needs to read the data register,
+    /// when reading the measurement immediately after setting the
+    /// configuration, that measurement will be returned as `NotReady`.
+    pub async fn set_config(&mut self, config: &Config) -> Result<u8, Error<I2C::Error>> {
+        self.check_channel(config)?;
+
+        // Set configuration
+        let command = self.mode.bits() | config.bits();
+        self.i2c
+            .write(self.address, &[command])
+            .await
+            .map_err(Error::I2c)?;
+        self.config = Some(self.calibrated(config));
+
+        // Wait for first measurement
+        let conversion_time_us = config.resolution.conversion_time_us();
+        self.delay.wait_us(conversion_time_us).await;
+
+        // Re-check until ready
+        for polls in 1..=MAX_READY_POLLS {
+            let (_, config_reg) = self
+                .read_code_and_config::<Error<_>>(&config.resolution)
+                .await?;
+            if config_reg.is_ready() {
+                return Ok(polls);
+            }
+            if polls < MAX_READY_POLLS {
+                // Not yet ready, wait some more time
+                self.delay.wait_us(conversion_time_us / 4).await;
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    /// Read a measurement from the device.
+    ///
+    /// Note that the [`set_config`](struct.MCP3425.html#method.set_config)
+    /// method MUST have been called before, otherwise
+    /// [`ReadError::NotInitialized`](../enum.ReadError.html#variant.NotInitialized)
+    /// will be returned.
+    ///
+    /// If you poll faster than the sample rate,
+    /// [`ReadError::NotReady`](../enum.ReadError.html#variant.NotReady) will be
+    /// returned, unless a [`RetryPolicy`](../struct.RetryPolicy.html) is set.
+    pub async fn read_measurement(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        let mut attempt = 1;
+        let mut first_error = None;
+        loop {
+            match self.read_measurement_once().await {
+                Err(e) if e.is_transient() => {
+                    if attempt >= self.retry.max_attempts {
+                        return Err(first_error.unwrap_or(e));
+                    }
+                    first_error.get_or_insert(e);
+                    attempt += 1;
+                    self.delay
+                        .wait_us(self.retry.backoff_ms.saturating_mul(1000))
+                        .await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Do a single attempt at reading a measurement from the device.
+    async fn read_measurement_once(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        // Make sure that the configuration has been written to the device
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+
+        // Read measurement and config register
+        let (measurement, config_reg) = self.read_code_and_config(&config.resolution).await?;
+
+        // Check "Not Ready" flag before looking at the value, so that a stale
+        // saturated result is reported as not ready. See datasheet section
+        // 5.1.1 for more details.
+        if !config_reg.is_ready() {
+            return Err(ReadError::NotReady);
+        }
+
+        // Calculate voltage from raw value
+        self.voltage(measurement, &config)
+    }
+
+    /// Write the specified configuration to the device and fill `voltages`
+    /// with consecutive measurements.
+    ///
+    /// Every value is read as soon as the next conversion has finished, so
+    /// all values are fresh samples, spaced by the sample rate of the
+    /// configured resolution. If a result does not become ready within three
+    /// conversion times (one conversion time, then polls for two more),
+    /// [`Error::NotReady`](../enum.Error.html#variant.NotReady) is returned.
+    pub async fn measure_into(
+        &mut self,
+        config: &Config,
+        voltages: &mut [Voltage],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_config(config).await?;
+        let conversion_time_us = config.resolution.conversion_time_us();
+        for voltage in voltages.iter_mut() {
+            // Wait for the next conversion, then poll until it is ready, for
+            // up to two more conversion times
+            self.delay.wait_us(conversion_time_us).await;
+            let mut polls = 0;
+            *voltage = loop {
+                match self.read_measurement_once().await {
+                    Err(ReadError::NotReady) if polls < MAX_PACING_POLLS => {
+                        polls += 1;
+                        self.delay.wait_us(conversion_time_us / 16).await;
+                    }
+                    result => break result?,
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+/// A group of devices on the same bus that are sampled simultaneously.
+///
+/// The configuration is written to every device first. Then a single I²C
+/// general call conversion command is sent, which starts the conversion on
+/// all devices at the same time. After waiting for the slowest conversion,
+/// the results are collected from every device.
+///
+/// Note that the general call is received by *all* MCP342x devices on the bus,
+/// including devices that are not part of the group.
+///
+/// ```no_run
+/// # async fn run<I2C, D>(adcs: [mcp3425::asynch::MCP3425<I2C, D, mcp3425::OneShotMode>; 3])
+/// # where I2C: embedded_hal_async::i2c::I2c, D: mcp3425::asynch::Wait
+/// # {
+/// use mcp3425::{asynch::SyncGroup, Config};
+///
+/// let mut group = SyncGroup::new(adcs);
+/// let config = Config::default();
+/// let results = group.measure(&[config; 3]).await.unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SyncGroup<I2C, D, const N: usize, DEV = AnyModel> {
+    /// The devices in this group.
+    devices: [MCP3425<I2C, D, OneShotMode, DEV>; N],
+}
+
+impl<I2C, D, const N: usize, DEV> SyncGroup<I2C, D, N, DEV>
+where
+    I2C: I2c,
+    D: Wait,
+    DEV: Model,
+{
+    /// Create a new group from one-shot drivers sharing the same bus.
+    ///
+    /// The bus handle and delay of the first device are used to send the
+    /// general call and to wait for the conversions. Devices whose result is
+    /// not ready by then are polled with their own delay.
+    pub fn new(devices: [MCP3425<I2C, D, OneShotMode, DEV>; N]) -> Self {
+        SyncGroup { devices }
+    }
+
+    /// Start a conversion on all devices simultaneously and return the
+    /// results in the order of the devices.
+    ///
+    /// Bus errors abort the whole group measurement. Errors that only concern
+    /// a single device (saturation, or a result that is not ready yet) are
+    /// returned in that device's slot.
+    #[allow(clippy::type_complexity)]
+    pub async fn measure(
+        &mut self,
+        configs: &[Config; N],
+    ) -> Result<[Result<Voltage, MeasureError<I2C::Error>>; N], MeasureError<I2C::Error>> {
+        // Write configuration without starting a conversion
+        for (device, config) in self.devices.iter_mut().zip(configs) {
+            device.check_channel(config)?;
+            device.finish_pending_conversion().await?;
+            let command = device.mode.bits() | config.bits();
+            device
+                .i2c
+                .write(device.address, &[command])
+                .await
+                .map_err(MeasureError::I2c)?;
+        }
+
+        // Start all conversions at once
+        let wait_us = configs
+            .iter()
+            .map(|config| config.resolution.conversion_time_us())
+            .max()
+            .unwrap_or(0);
+        for (device, config) in self.devices.iter_mut().zip(configs) {
+            device.pending = Some(config.resolution);
+        }
+        if let Some(first) = self.devices.first_mut() {
+            general_call_conversion(&mut first.i2c)
+                .await
+                .map_err(MeasureError::I2c)?;
+
+            // Wait for the typical time of the slowest conversion
+            first.delay.wait_us(wait_us).await;
+        }
+
+        // Collect results, polling every device until its maximum conversion
+        // time has passed
+        let mut raw = [None; N];
+        for ((device, slot), config) in self.devices.iter_mut().zip(raw.iter_mut()).zip(configs) {
+            *slot = device.poll_until_ready(&config.resolution, wait_us).await?;
+            if slot.is_some() {
+                device.pending = None;
+            }
+        }
+        let mut index = 0;
+        Ok(raw.map(|measurement| {
+            let device = &self.devices[index];
+            let config = &configs[index];
+            index += 1;
+            match measurement {
+                Some(measurement) => device.voltage(measurement, &device.calibrated(config)),
+                None => Err(MeasureError::NotReady),
+            }
+        }))
+    }
+
+    /// Destroy the group and return the devices.
+    pub fn release(self) -> [MCP3425<I2C, D, OneShotMode, DEV>; N] {
+        self.devices
+    }
+}
+
+/// Perform one-shot measurements at a fixed cadence.
+///
+/// The sampler keeps an absolute schedule: The time spent in I²C
+/// transactions and waiting for the conversion is not added on top of the
+/// sample interval, so the sample times do not drift over long periods.
+///
+/// If a measurement takes longer than the sample interval, the schedule is
+/// re-anchored at the current time instead of catching up with a burst of
+/// back-to-back samples.
+///
+/// ```no_run
+/// # async fn run<I2C, D, C>(adc: mcp3425::asynch::MCP3425<I2C, D, mcp3425::OneShotMode>, clock: C)
+/// # where I2C: embedded_hal_async::i2c::I2c, D: mcp3425::asynch::Wait, C: mcp3425::asynch::Clock
+/// # {
+/// use mcp3425::{asynch::PeriodicSampler, Config};
+///
+/// // Sample once per second
+/// let mut sampler = PeriodicSampler::new(adc, Config::default(), 1_000_000, clock);
+/// loop {
+///     let voltage = sampler.sample().await;
+///     // ...
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PeriodicSampler<I2C, D, C, DEV = AnyModel> {
+    /// The one-shot driver performing the measurements.
+    adc: MCP3425<I2C, D, OneShotMode, DEV>,
+    /// The configuration used for every measurement.
+    config: Config,
+    /// The sample interval in microseconds.
+    interval_us: u32,
+    /// The time source used for drift compensation.
+    clock: C,
+    /// The time at which the next measurement should be started.
+    next_us: Option<u64>,
+}
+
+impl<I2C, D, C, DEV> PeriodicSampler<I2C, D, C, DEV>
+where
+    I2C: I2c,
+    D: Wait,
+    C: Clock,
+    DEV: Model,
+{
+    /// Create a new sampler taking a measurement every `interval_us`
+    /// microseconds.
+    ///
+    /// The delay implementation of the driver is used to wait between
+    /// samples. The first sample is taken immediately.
+    pub fn new(
+        adc: MCP3425<I2C, D, OneShotMode, DEV>,
+        config: Config,
+        interval_us: u32,
+        clock: C,
+    ) -> Self {
+        PeriodicSampler {
+            adc,
+            config,
+            interval_us,
+            clock,
+            next_us: None,
+        }
+    }
+
+    /// Wait until the next sample is due, then do a one-shot measurement.
+    pub async fn sample(&mut self) -> Result<Voltage, MeasureError<I2C::Error>> {
+        let now = self.clock.now_us();
+        let due = *self.next_us.get_or_insert(now);
+        if due > now {
+            let wait_us = (due - now).min(u64::from(self.interval_us)) as u32;
+            self.adc.delay.wait_us(wait_us).await;
+        }
+
+        let result = self.adc.measure(&self.config).await;
+
+        // Schedule the next sample relative to the previous deadline, not
+        // relative to the end of this measurement.
+        let next = due + u64::from(self.interval_us);
+        let now = self.clock.now_us();
+        self.next_us = Some(if next < now { now } else { next });
+
+        result
+    }
+
+    /// Destroy the sampler and return the driver and the clock.
+    pub fn release(self) -> (MCP3425<I2C, D, OneShotMode, DEV>, C) {
+        (self.adc, self.clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::{CheckedDelay, NoopDelay, Transaction as DelayTransaction},
+        i2c::{Mock as I2cMock, Transaction},
+    };
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use futures::executor::block_on;
+    use futures::FutureExt;
+
+    use super::*;
+
+    /// A delay that never finishes while `hang` is set, used to cancel
+    /// futures in the middle of a conversion.
+    struct HangingDelay {
+        hang: Rc<Cell<bool>>,
+    }
+
+    impl DelayNs for HangingDelay {
+        async fn delay_ns(&mut self, _ns: u32) {
+            if self.hang.get() {
+                core::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// A clock that advances by a fixed step every time it is read.
+    struct SteppingClock {
+        now: u64,
+        step: u64,
+    }
+
+    impl Clock for SteppingClock {
+        fn now_us(&mut self) -> u64 {
+            let now = self.now;
+            self.now += self.step;
+            now
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_read_voltage_oneshot() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000111, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = block_on(adc.measure(&Config::default())).expect("Measuring failed");
+        assert_eq!(voltage.as_millivolts(), 7);
+        adc.destroy().done();
+    }
+
+    #[test]
+    fn test_continuous_not_initialized() {
+        let dev = I2cMock::new(&[]);
+        let mut adc = MCP3425::continuous(dev, 0x42, NoopDelay);
+        let err = block_on(adc.read_measurement()).unwrap_err();
+        assert!(matches!(err, ReadError::NotInitialized), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// A measurement that is cancelled while waiting for the conversion leaves
+    /// the conversion marked as pending. The next measurement waits for it to
+    /// finish before starting a new one.
+    #[test]
+    fn test_cancelled_measurement() {
+        let addr = 0x42;
+        let expectations = [
+            // Cancelled measurement
+            Transaction::write(addr, vec![0b10000000]),
+            // Recovery: The cancelled conversion is still running
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b00000000]),
+            // New measurement
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000111, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let hang = Rc::new(Cell::new(true));
+        let delay = HangingDelay { hang: hang.clone() };
+        let mut adc = MCP3425::oneshot(dev, addr, delay);
+
+        // Drop the future while it waits for the conversion
+        assert!(adc.measure(&Config::default()).now_or_never().is_none());
+        assert!(adc.has_pending_conversion());
+
+        hang.set(false);
+        block_on(adc.measure(&Config::default())).expect("Measuring failed");
+        assert!(!adc.has_pending_conversion());
+        adc.destroy().done();
+    }
+
+    /// A slow conversion is polled until the maximum conversion time.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_polls_until_ready() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000111, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::async_delay_us(4_000),
+            DelayTransaction::async_delay_us(250),
+            DelayTransaction::async_delay_us(250),
+        ]);
+        let mut adc = MCP3425::oneshot(dev, addr, delay);
+        let voltage = block_on(adc.measure(&Config::default())).unwrap();
+        assert_eq!(voltage.as_millivolts(), 7);
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+
+    /// A conversion that does not finish within the maximum conversion time
+    /// is reported as not ready and stays pending.
+    #[test]
+    fn test_measure_polls_exhausted() {
+        let addr = 0x42;
+        let mut expectations = vec![Transaction::write(addr, vec![0b10000000])];
+        // 4000 µs, then 7 polls of 250 µs reach the 5682 µs maximum
+        expectations.extend((0..8).map(|_| Transaction::read(addr, vec![0, 0, 0b10000000])));
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let err = block_on(adc.measure(&Config::default())).unwrap_err();
+        assert!(matches!(err, MeasureError::NotReady), "{:?}", err);
+        assert!(adc.has_pending_conversion());
+        adc.destroy().done();
+    }
+
+    /// The ready flag is re-checked in intervals of a quarter conversion time.
+    #[test]
+    fn test_set_config_polls() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b10010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::async_delay_us(4_000),
+            DelayTransaction::async_delay_us(1_000),
+        ]);
+        let mut adc = MCP3425::continuous(dev, addr, delay);
+        let polls = block_on(adc.set_config(&Config::default())).unwrap();
+        assert_eq!(polls, 2);
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+
+    /// The number of re-checks is bounded.
+    #[test]
+    fn test_set_config_polls_exhausted() {
+        let addr = 0x42;
+        let mut expectations = vec![Transaction::write(addr, vec![0b00010000])];
+        expectations.extend((0..8).map(|_| Transaction::read(addr, vec![0, 0, 0b10010000])));
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let err = block_on(adc.set_config(&Config::default())).unwrap_err();
+        assert!(matches!(err, Error::Timeout), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Channel calibrations are applied like in the blocking driver.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_channel_calibrations() {
+        use crate::calibration::Calibration;
+        use crate::tests::oneshot_expectations;
+        use crate::Channel;
+
+        let addr = 0x42;
+        let dev = I2cMock::new(&oneshot_expectations(addr, 0, &[100, 1_000]));
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let offset = Calibration::new(-5_000, 0);
+        adc.set_channel_calibrations(ChannelCalibrations::new().with(Channel::Channel1, offset));
+        let voltage = block_on(adc.measure(&Config::default())).unwrap();
+        assert_eq!(voltage.as_millivolts(), 95);
+
+        let config = Config::default().with_calibration(Calibration::default());
+        let voltage = block_on(adc.measure(&config)).unwrap();
+        assert_eq!(voltage.as_millivolts(), 1_000);
+        adc.destroy().done();
+    }
+
+    /// The tempco compensation is applied like in the blocking driver.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_tempco_compensation() {
+        use crate::tests::oneshot_expectations;
+
+        let addr = 0x42;
+        let dev = I2cMock::new(&oneshot_expectations(addr, 0, &[1_000, 1_000]));
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_tempco(Some(Tempco::new(0, 1_000)));
+        // No compensation without a temperature
+        let voltage = block_on(adc.measure(&Config::default())).unwrap();
+        assert_eq!(voltage.as_millivolts(), 1_000);
+        adc.set_temperature(45_000);
+        let voltage = block_on(adc.measure(&Config::default())).unwrap();
+        assert_eq!(voltage.as_millivolts(), 980);
+        adc.destroy().done();
+    }
+
+    /// Selecting a channel the model does not have fails without accessing
+    /// the bus.
+    #[test]
+    #[cfg(feature = "dual_channel")]
+    fn test_channel_not_supported() {
+        use crate::{model::Mcp3425, Channel};
+
+        let dev = I2cMock::new(&[]);
+        let config = Config::default().with_channel(Channel::Channel2);
+
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay).with_model(Mcp3425);
+        let err = block_on(adc.measure(&config)).unwrap_err();
+        assert!(
+            matches!(err, MeasureError::ChannelNotSupported),
+            "{:?}",
+            err
+        );
+
+        let mut adc = adc.into_continuous();
+        let err = block_on(adc.set_config(&config)).unwrap_err();
+        assert!(matches!(err, Error::ChannelNotSupported), "{:?}", err);
+
+        adc.destroy().done();
+    }
+
+    /// All devices are configured first, then started with one general call.
+    #[test]
+    fn test_sync_group() {
+        let expectations = [
+            // Configuration without start bit
+            Transaction::write(0x68, vec![0b00000000]),
+            Transaction::write(0x69, vec![0b00001000]),
+            // General call conversion
+            Transaction::write(0x00, vec![0b00001000]),
+            // Results
+            Transaction::read(0x68, vec![0b00000000, 0b00000111, 0b00000000]),
+            Transaction::read(0x69, vec![0b01111111, 0b11111111, 0b00001000]),
+        ];
+        let mut dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[DelayTransaction::async_delay_ms(57)]);
+        let adc1 = MCP3425::oneshot(dev.clone(), 0x68, delay);
+        let adc2 = MCP3425::oneshot(dev.clone(), 0x69, CheckedDelay::new(&[]));
+        let mut group = SyncGroup::new([adc1, adc2]);
+
+        let configs = [
+            Config::default(),
+            Config::default().with_resolution(Resolution::Bits16Sps15),
+        ];
+        let [first, second] = block_on(group.measure(&configs)).unwrap();
+        assert!(first.is_ok(), "{:?}", first);
+        assert!(
+            matches!(second, Err(MeasureError::VoltageTooHigh)),
+            "{:?}",
+            second
+        );
+
+        for adc in group.release() {
+            let MCP3425 { mut delay, .. } = adc;
+            delay.done();
+        }
+        dev.done();
+    }
+
+    /// The time spent measuring is subtracted from the wait until the next
+    /// sample.
+    #[test]
+    fn test_periodic_sampler_compensates_measurement_time() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            // First sample: Conversion wait only
+            DelayTransaction::async_delay_ms(4),
+            // Second sample: 2 ms have passed since the first sample started
+            DelayTransaction::async_delay_us(8_000),
+            DelayTransaction::async_delay_ms(4),
+        ]);
+        let adc = MCP3425::oneshot(dev, addr, delay);
+        let clock = SteppingClock {
+            now: 0,
+            step: 1_000,
+        };
+        let mut sampler = PeriodicSampler::new(adc, Config::default(), 10_000, clock);
+        block_on(sampler.sample()).unwrap();
+        block_on(sampler.sample()).unwrap();
+        let (adc, _) = sampler.release();
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+
+    /// An overrun re-anchors the schedule instead of sampling in a burst.
+    #[test]
+    fn test_periodic_sampler_overrun() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::async_delay_ms(4),
+            // No wait between the samples
+            DelayTransaction::async_delay_ms(4),
+        ]);
+        let adc = MCP3425::oneshot(dev, addr, delay);
+        let clock = SteppingClock {
+            now: 0,
+            step: 20_000,
+        };
+        let mut sampler = PeriodicSampler::new(adc, Config::default(), 10_000, clock);
+        block_on(sampler.sample()).unwrap();
+        block_on(sampler.sample()).unwrap();
+        let (adc, _) = sampler.release();
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+}
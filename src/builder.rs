@@ -0,0 +1,206 @@
+//! Validated construction of a [`Config`](../struct.Config.html).
+//!
+//! The `with_*` methods of `Config` never fail. A
+//! [`ConfigBuilder`](struct.ConfigBuilder.html) instead checks the
+//! configuration against the device model, and against an optional
+//! application-specific validation hook, when calling
+//! [`build`](struct.ConfigBuilder.html#method.build):
+//!
+//! ```
+//! use mcp3425::builder::ConfigError;
+//! use mcp3425::model::Mcp3425;
+//! use mcp3425::{Config, Gain, Resolution};
+//!
+//! let config = Config::builder()
+//!     .for_model(Mcp3425)
+//!     .resolution(Resolution::Bits16Sps15)
+//!     .gain(Gain::Gain2)
+//!     .build()
+//!     .unwrap();
+//!
+//! // Reject high gains in this application
+//! let result = Config::builder()
+//!     .gain(Gain::Gain8)
+//!     .validate(|config| match config.gain {
+//!         Gain::Gain8 => Err("gain too high for this sensor"),
+//!         _ => Ok(()),
+//!     })
+//!     .build();
+//! assert_eq!(result, Err(ConfigError::Rejected("gain too high for this sensor")));
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::model::{AnyModel, Model};
+use crate::{Channel, Config, Gain, Resolution, SaturationPolicy};
+
+/// A validation hook for [`ConfigBuilder::validate`](struct.ConfigBuilder.html#method.validate).
+pub type Validator = fn(&Config) -> Result<(), &'static str>;
+
+/// Error returned by [`ConfigBuilder::build`](struct.ConfigBuilder.html#method.build).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The model does not have the selected channel.
+    ChannelNotSupported,
+    /// The model does not support the selected resolution.
+    ResolutionNotSupported,
+    /// The validation hook rejected the configuration.
+    Rejected(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::ChannelNotSupported => f.write_str("channel not supported by model"),
+            ConfigError::ResolutionNotSupported => f.write_str("resolution not supported by model"),
+            ConfigError::Rejected(reason) => write!(f, "configuration rejected: {}", reason),
+        }
+    }
+}
+
+/// Builder for a validated [`Config`](../struct.Config.html).
+///
+/// Create it with [`Config::builder`](../struct.Config.html#method.builder).
+/// Without calling [`for_model`](#method.for_model), only the validation hook
+/// is checked.
+#[derive(Debug, Copy, Clone)]
+pub struct ConfigBuilder<DEV = AnyModel> {
+    /// The configuration being built.
+    config: Config,
+    /// Optional application-specific validation.
+    validator: Option<Validator>,
+    /// The device model to validate against.
+    model: PhantomData<DEV>,
+}
+
+impl ConfigBuilder {
+    /// Create a builder starting from the default configuration.
+    pub fn new() -> Self {
+        ConfigBuilder::from_config(Config::default())
+    }
+
+    /// Create a builder starting from an existing configuration, e.g. one of
+    /// the presets like [`Config::fast`](../struct.Config.html#method.fast).
+    pub fn from_config(config: Config) -> Self {
+        ConfigBuilder {
+            config,
+            validator: None,
+            model: PhantomData,
+        }
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        ConfigBuilder::new()
+    }
+}
+
+impl<DEV: Model> ConfigBuilder<DEV> {
+    /// Validate the configuration against the device model `NEW`.
+    pub fn for_model<NEW: Model>(self, _model: NEW) -> ConfigBuilder<NEW> {
+        ConfigBuilder {
+            config: self.config,
+            validator: self.validator,
+            model: PhantomData,
+        }
+    }
+
+    /// Set the resolution.
+    pub fn resolution(mut self, resolution: Resolution) -> Self {
+        self.config.resolution = resolution;
+        self
+    }
+
+    /// Set the gain.
+    pub fn gain(mut self, gain: Gain) -> Self {
+        self.config.gain = gain;
+        self
+    }
+
+    /// Set the input channel.
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.config.channel = channel;
+        self
+    }
+
+    /// Set the saturation policy.
+    pub fn saturation_policy(mut self, saturation: SaturationPolicy) -> Self {
+        self.config.saturation = saturation;
+        self
+    }
+
+    /// Set a hook that is called by [`build`](#method.build) after the model
+    /// checks. Replaces a previously set hook.
+    pub fn validate(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Check the configuration and return it.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let config = self.config;
+        if config.channel.index() >= DEV::CHANNELS {
+            return Err(ConfigError::ChannelNotSupported);
+        }
+        if config.resolution.res_bits() > DEV::MAX_RESOLUTION_BITS {
+            return Err(ConfigError::ResolutionNotSupported);
+        }
+        if let Some(validator) = self.validator {
+            validator(&config).map_err(ConfigError::Rejected)?;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Mcp3428;
+
+    #[test]
+    fn test_build() {
+        let config = ConfigBuilder::from_config(Config::fast())
+            .gain(Gain::Gain4)
+            .for_model(Mcp3428)
+            .build()
+            .unwrap();
+        assert_eq!(config, Config::fast().with_gain(Gain::Gain4));
+    }
+
+    #[test]
+    #[cfg(feature = "dual_channel")]
+    fn test_channel_not_supported() {
+        use crate::model::Mcp3425;
+
+        let result = Config::builder()
+            .channel(Channel::Channel2)
+            .for_model(Mcp3425)
+            .build();
+        assert_eq!(result, Err(ConfigError::ChannelNotSupported));
+    }
+
+    #[test]
+    #[cfg(feature = "eighteen_bit")]
+    fn test_resolution_not_supported() {
+        use crate::model::Mcp3421;
+
+        let builder = Config::builder().resolution(Resolution::Bits18Sps3_75);
+        assert!(builder.for_model(Mcp3421).build().is_ok());
+        assert_eq!(
+            builder.for_model(Mcp3428).build(),
+            Err(ConfigError::ResolutionNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_validator() {
+        let builder = Config::builder().validate(|config| match config.resolution {
+            Resolution::Bits12Sps240 => Err("too coarse"),
+            _ => Ok(()),
+        });
+        assert_eq!(builder.build(), Err(ConfigError::Rejected("too coarse")));
+        assert!(builder.resolution(Resolution::Bits14Sps60).build().is_ok());
+    }
+}
@@ -0,0 +1,660 @@
+//! Correction of offset and gain errors.
+//!
+//! The MCP342x has a small offset error and a gain error of up to ±0.35%
+//! (see the electrical characteristics in the datasheet), which differs
+//! between the PGA settings. A [`Calibration`](struct.Calibration.html) is
+//! computed from two measurements of known reference voltages, e.g. against
+//! a precision source in production test, and attached to the
+//! [`Config`](../struct.Config.html) it was measured with. Every voltage
+//! calculated with that configuration is then corrected:
+//!
+//! ```
+//! use mcp3425::{calibration::Calibration, Config, Gain};
+//!
+//! // The device reads 0.5mV at 0V and 1996mV at 2000mV
+//! let calibration = Calibration::from_points(500, 0, 1_996_000, 2_000_000).unwrap();
+//! let config = Config::default().with_calibration(calibration);
+//! ```
+//!
+//! The offset alone can be measured with shorted inputs, see
+//! [`calibrate_offset`](../struct.MCP3425.html#method.calibrate_offset).
+//! Known corrections, e.g. from a calibration certificate, can be set
+//! directly with [`Calibration::new`](struct.Calibration.html#method.new).
+//! The correction is applied to the input voltage in microvolts, before it
+//! is rounded to the resolution of the voltage type, so it does not degrade
+//! 16 or 18 bit readings.
+//!
+//! To store a calibration in EEPROM or flash, e.g. at production test, and
+//! reload it at boot, convert it to a fixed-size byte array with
+//! [`to_bytes`](struct.Calibration.html#method.to_bytes). With the `serde`
+//! Cargo feature, it also implements `Serialize` and `Deserialize`.
+//!
+//! Multi-channel devices often need a calibration per channel, since the
+//! front-ends differ. Attach a
+//! [`ChannelCalibrations`](struct.ChannelCalibrations.html) table to the
+//! driver with
+//! [`set_channel_calibrations`](../struct.MCP3425.html#method.set_channel_calibrations)
+//! to apply them based on the channel of the configuration.
+//!
+//! The offset and gain errors drift with the temperature. With the
+//! [`Tempco`](struct.Tempco.html) of the setup and the current temperature,
+//! the driver compensates the drift, see
+//! [`set_tempco`](../struct.MCP3425.html#method.set_tempco).
+//!
+//! Without a per-unit calibration, the
+//! [`ErrorLimits`](struct.ErrorLimits.html) from the datasheet bound the
+//! error of a reading. They can't be used as a correction, since the sign of
+//! the errors differs between devices: The conservative default is the
+//! identity calibration, i.e. no calibration at all. It can be pre-loaded
+//! together with the maximum limits as a
+//! [`BoundedCalibration`](struct.BoundedCalibration.html), which is replaced
+//! once a per-unit calibration is available.
+
+use core::fmt;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{Channel, Config, Gain};
+
+/// Offset and gain error limits from the electrical characteristics of the
+/// datasheet, at 25°C and 16 bits.
+///
+/// The datasheet specifies the gain error (0.05% typical, 0.35% maximum)
+/// and the offset error (15µV typical, 40µV maximum) at gain 1. For the
+/// other gains, the PGA gain error match of 0.1% is added to the gain error.
+///
+/// ```
+/// use mcp3425::{calibration::ErrorLimits, Gain};
+///
+/// let limits = ErrorLimits::maximum(Gain::Gain1);
+/// // Worst case error of a 1V reading: 0.35% of 1V plus 40µV
+/// assert_eq!(limits.error_bound_uv(1_000_000), 3_540);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorLimits {
+    /// Gain error in parts per million.
+    pub gain_ppm: u32,
+    /// Input-referred offset error in microvolts.
+    pub offset_uv: u32,
+}
+
+/// PGA gain error match between gain settings in parts per million.
+const PGA_MATCH_PPM: u32 = 1_000;
+
+impl ErrorLimits {
+    /// Return the typical errors at the specified gain.
+    pub const fn typical(gain: Gain) -> Self {
+        ErrorLimits::at_gain(gain, 500, 15)
+    }
+
+    /// Return the maximum errors at the specified gain.
+    pub const fn maximum(gain: Gain) -> Self {
+        ErrorLimits::at_gain(gain, 3_500, 40)
+    }
+
+    /// Add the PGA gain error match to the gain error at gains above 1.
+    const fn at_gain(gain: Gain, gain_ppm: u32, offset_uv: u32) -> Self {
+        let gain_ppm = match gain {
+            Gain::Gain1 => gain_ppm,
+            _ => gain_ppm + PGA_MATCH_PPM,
+        };
+        ErrorLimits {
+            gain_ppm,
+            offset_uv,
+        }
+    }
+
+    /// Return the bound of the error of an uncalibrated reading of
+    /// `microvolts`, in microvolts.
+    pub fn error_bound_uv(&self, microvolts: i32) -> u32 {
+        let gain_error =
+            u64::from(microvolts.unsigned_abs()) * u64::from(self.gain_ppm) / 1_000_000;
+        // The input range is ±2.048V, so the gain error of any reading fits
+        (gain_error as u32).saturating_add(self.offset_uv)
+    }
+}
+
+/// A calibration together with the bounds of the error that remains after
+/// applying it.
+///
+/// ```
+/// use mcp3425::{calibration::BoundedCalibration, Gain};
+///
+/// // No per-unit calibration yet
+/// let calibration = BoundedCalibration::datasheet(Gain::Gain1);
+/// assert_eq!(calibration.apply_uv(1_000_000), (1_000_000, 3_540));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BoundedCalibration {
+    /// The correction applied to readings.
+    pub calibration: Calibration,
+    /// The error limits of corrected readings.
+    pub limits: ErrorLimits,
+}
+
+impl BoundedCalibration {
+    /// Return the conservative default for a device without a per-unit
+    /// calibration: The identity calibration, centred on zero error, bounded
+    /// by the maximum errors from the datasheet at the specified gain.
+    pub const fn datasheet(gain: Gain) -> Self {
+        BoundedCalibration {
+            calibration: Calibration::new(0, 0),
+            limits: ErrorLimits::maximum(gain),
+        }
+    }
+
+    /// Correct the reading `microvolts`, and return the corrected value
+    /// together with the bound of its error, both in microvolts.
+    pub fn apply_uv(&self, microvolts: i32) -> (i32, u32) {
+        let corrected = self.calibration.apply_uv(microvolts);
+        (corrected, self.limits.error_bound_uv(corrected))
+    }
+}
+
+/// Version of the byte format of [`Calibration::to_bytes`](struct.Calibration.html#method.to_bytes).
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Error returned by
+/// [`Calibration::from_bytes`](struct.Calibration.html#method.from_bytes).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The format version is not supported, e.g. because the storage was
+    /// never written.
+    UnsupportedVersion(u8),
+    /// The checksum does not match, the data is corrupted.
+    Checksum,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported calibration format version: {}", version)
+            }
+            DecodeError::Checksum => f.write_str("calibration checksum mismatch"),
+        }
+    }
+}
+
+/// Offset and gain correction, applied as
+/// `corrected = measured * (1 + gain_ppm / 10⁶) + offset_uv`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calibration {
+    /// Offset added after the gain correction, in microvolts.
+    offset_uv: i32,
+    /// Gain correction in parts per million.
+    gain_ppm: i32,
+}
+
+impl Calibration {
+    /// Create a calibration from an offset in microvolts and a gain
+    /// correction in parts per million.
+    ///
+    /// ```
+    /// use mcp3425::calibration::Calibration;
+    ///
+    /// // The device reads 0.1% low and 0.25mV high
+    /// let calibration = Calibration::new(-250, 1_000);
+    /// assert_eq!(calibration.apply_uv(1_000_250), 1_001_000);
+    /// ```
+    pub const fn new(offset_uv: i32, gain_ppm: i32) -> Self {
+        Calibration {
+            offset_uv,
+            gain_ppm,
+        }
+    }
+
+    /// Return the offset in microvolts, which is added after the gain
+    /// correction.
+    pub const fn offset_uv(&self) -> i32 {
+        self.offset_uv
+    }
+
+    /// Return the gain correction in parts per million.
+    pub const fn gain_ppm(&self) -> i32 {
+        self.gain_ppm
+    }
+
+    /// Compute the calibration from two measurements of reference voltages,
+    /// all in microvolts.
+    ///
+    /// `measured1_uv` is the uncalibrated reading of the reference voltage
+    /// `actual1_uv`, and likewise for the second point. The points should be
+    /// far apart, e.g. close to zero and close to full scale.
+    ///
+    /// Return `None` if both measurements are equal, or if the correction is
+    /// out of range.
+    pub fn from_points(
+        measured1_uv: i32,
+        actual1_uv: i32,
+        measured2_uv: i32,
+        actual2_uv: i32,
+    ) -> Option<Self> {
+        let measured_span = i64::from(measured2_uv) - i64::from(measured1_uv);
+        let actual_span = i64::from(actual2_uv) - i64::from(actual1_uv);
+        if measured_span == 0 {
+            return None;
+        }
+        let gain_ppm = (actual_span - measured_span) * 1_000_000 / measured_span;
+        let calibration = Calibration {
+            offset_uv: 0,
+            gain_ppm: i32::try_from(gain_ppm).ok()?,
+        };
+        let offset_uv = i64::from(actual1_uv) - calibration.scale(measured1_uv);
+        Some(Calibration {
+            offset_uv: i32::try_from(offset_uv).ok()?,
+            ..calibration
+        })
+    }
+
+    /// Return a calibration with the same gain correction and an offset that
+    /// corrects the uncalibrated reading `measured_uv` to zero, e.g. the
+    /// reading of shorted inputs.
+    pub fn zeroed_at(&self, measured_uv: i32) -> Self {
+        Calibration {
+            offset_uv: (-self.scale(measured_uv)).clamp(i64::from(i32::MIN), i64::from(i32::MAX))
+                as i32,
+            ..*self
+        }
+    }
+
+    /// Encode the calibration for storage.
+    ///
+    /// The format is the version byte
+    /// ([`FORMAT_VERSION`](constant.FORMAT_VERSION.html)), the offset and the
+    /// gain as big-endian `i32`, and a CRC-8 checksum (polynomial 0x31,
+    /// initial value 0xFF) of the preceding bytes.
+    ///
+    /// ```
+    /// use mcp3425::calibration::Calibration;
+    ///
+    /// let calibration = Calibration::new(-250, 1_000);
+    /// let bytes = calibration.to_bytes();
+    /// assert_eq!(Calibration::from_bytes(&bytes), Ok(calibration));
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 10] {
+        let mut bytes = [0; 10];
+        bytes[0] = FORMAT_VERSION;
+        BigEndian::write_i32(&mut bytes[1..5], self.offset_uv);
+        BigEndian::write_i32(&mut bytes[5..9], self.gain_ppm);
+        bytes[9] = crc8(&bytes[..9]);
+        bytes
+    }
+
+    /// Decode a calibration encoded with
+    /// [`to_bytes`](#method.to_bytes).
+    ///
+    /// Erased flash (all bytes 0xFF) is reported as an unsupported version.
+    pub fn from_bytes(bytes: &[u8; 10]) -> Result<Self, DecodeError> {
+        if bytes[0] != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(bytes[0]));
+        }
+        if crc8(&bytes[..9]) != bytes[9] {
+            return Err(DecodeError::Checksum);
+        }
+        Ok(Calibration {
+            offset_uv: BigEndian::read_i32(&bytes[1..5]),
+            gain_ppm: BigEndian::read_i32(&bytes[5..9]),
+        })
+    }
+
+    /// Apply the correction to a voltage in microvolts.
+    pub fn apply_uv(&self, microvolts: i32) -> i32 {
+        let corrected = self.scale(microvolts) + i64::from(self.offset_uv);
+        corrected.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+    }
+
+    /// Apply the gain correction.
+    fn scale(&self, microvolts: i32) -> i64 {
+        let microvolts = i64::from(microvolts);
+        microvolts + microvolts * i64::from(self.gain_ppm) / 1_000_000
+    }
+}
+
+/// A function returning the current temperature in millidegrees Celsius,
+/// e.g. from the internal sensor of the MCU or a sensor on the board.
+///
+/// See
+/// [`set_temperature_source`](../struct.MCP3425.html#method.set_temperature_source).
+pub type TemperatureFn = fn() -> i32;
+
+/// Source of the temperature for the tempco compensation.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Temperature {
+    /// A temperature set by the user, in millidegrees Celsius.
+    Fixed(i32),
+    /// A function that is called for the current temperature.
+    Source(TemperatureFn),
+}
+
+impl Temperature {
+    /// Return the temperature in millidegrees Celsius.
+    pub(crate) fn read_mc(&self) -> i32 {
+        match self {
+            Temperature::Fixed(temperature_mc) => *temperature_mc,
+            Temperature::Source(read_mc) => read_mc(),
+        }
+    }
+}
+
+/// Temperature coefficients of the offset and gain error, i.e. how much the
+/// uncalibrated reading drifts per degree Celsius away from a reference
+/// temperature.
+///
+/// The coefficients of the device are in the datasheet (typical 15ppm/°C
+/// gain drift), but the drift of shunts, dividers or sensors in front of
+/// the device is often larger, so they are best measured at two
+/// temperatures. A [`Calibration`](struct.Calibration.html) is assumed to
+/// be valid at the reference temperature, which defaults to 25°C:
+///
+/// ```
+/// use mcp3425::calibration::{Calibration, Tempco};
+///
+/// // The reading rises by 2µV and 40ppm per °C
+/// let tempco = Tempco::new(2, 40);
+/// let calibration = tempco.compensate(&Calibration::default(), 75_000);
+/// assert_eq!(calibration, Calibration::new(-100, -2_000));
+/// ```
+///
+/// The compensation is linear, which is accurate for the usual outdoor
+/// range of −40 to 85°C.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tempco {
+    /// Offset drift in microvolts per degree Celsius.
+    offset_uv_per_c: i32,
+    /// Gain drift in parts per million per degree Celsius.
+    gain_ppm_per_c: i32,
+    /// Reference temperature in millidegrees Celsius.
+    reference_mc: i32,
+}
+
+impl Tempco {
+    /// Create temperature coefficients from the offset drift in microvolts
+    /// per degree Celsius and the gain drift in parts per million per degree
+    /// Celsius, with a reference temperature of 25°C.
+    pub const fn new(offset_uv_per_c: i32, gain_ppm_per_c: i32) -> Self {
+        Tempco {
+            offset_uv_per_c,
+            gain_ppm_per_c,
+            reference_mc: 25_000,
+        }
+    }
+
+    /// Return the coefficients with the reference temperature replaced, in
+    /// millidegrees Celsius, e.g. the temperature of the calibration.
+    pub const fn with_reference(self, reference_mc: i32) -> Self {
+        Tempco {
+            reference_mc,
+            ..self
+        }
+    }
+
+    /// Return the offset drift in microvolts per degree Celsius.
+    pub const fn offset_uv_per_c(&self) -> i32 {
+        self.offset_uv_per_c
+    }
+
+    /// Return the gain drift in parts per million per degree Celsius.
+    pub const fn gain_ppm_per_c(&self) -> i32 {
+        self.gain_ppm_per_c
+    }
+
+    /// Return the reference temperature in millidegrees Celsius.
+    pub const fn reference_mc(&self) -> i32 {
+        self.reference_mc
+    }
+
+    /// Return `calibration`, which is valid at the reference temperature,
+    /// adjusted to correct the drift at `temperature_mc` millidegrees
+    /// Celsius.
+    pub fn compensate(&self, calibration: &Calibration, temperature_mc: i32) -> Calibration {
+        self.shift(calibration, temperature_mc, -1)
+    }
+
+    /// Reverse [`compensate`](#method.compensate), i.e. return the
+    /// calibration at the reference temperature for a `calibration` that was
+    /// measured at `temperature_mc` millidegrees Celsius.
+    pub fn uncompensate(&self, calibration: &Calibration, temperature_mc: i32) -> Calibration {
+        self.shift(calibration, temperature_mc, 1)
+    }
+
+    /// Return `config` with its calibration compensated for the current
+    /// temperature, if both a tempco and a temperature are set.
+    ///
+    /// This is shared by the blocking and the async driver.
+    pub(crate) fn apply(
+        tempco: Option<Self>,
+        temperature: Option<Temperature>,
+        config: &Config,
+    ) -> Config {
+        match (tempco, temperature) {
+            (Some(tempco), Some(temperature)) => Config {
+                calibration: Some(tempco.compensate(
+                    &config.calibration.unwrap_or_default(),
+                    temperature.read_mc(),
+                )),
+                ..*config
+            },
+            _ => *config,
+        }
+    }
+
+    /// Add `sign` times the drift at `temperature_mc` to `calibration`.
+    fn shift(&self, calibration: &Calibration, temperature_mc: i32, sign: i64) -> Calibration {
+        let delta_mc = sign * (i64::from(temperature_mc) - i64::from(self.reference_mc));
+        let drift = |coefficient: i32, value: i32| {
+            let shifted = i64::from(value) + i64::from(coefficient) * delta_mc / 1_000;
+            shifted.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+        };
+        Calibration {
+            offset_uv: drift(self.offset_uv_per_c, calibration.offset_uv),
+            gain_ppm: drift(self.gain_ppm_per_c, calibration.gain_ppm),
+        }
+    }
+}
+
+/// A calibration per input channel.
+///
+/// Configurations without an explicit
+/// [`calibration`](../struct.Config.html#structfield.calibration) use the
+/// calibration of their channel:
+///
+/// ```
+/// use mcp3425::calibration::{Calibration, ChannelCalibrations};
+/// use mcp3425::{Channel, Config};
+///
+/// let calibrations =
+///     ChannelCalibrations::new().with(Channel::Channel1, Calibration::new(-120, 850));
+/// let config = calibrations.apply(&Config::default());
+/// assert_eq!(config.calibration, Some(Calibration::new(-120, 850)));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelCalibrations {
+    /// The calibrations by channel index.
+    calibrations: [Option<Calibration>; 4],
+}
+
+impl ChannelCalibrations {
+    /// Create a table without calibrations.
+    pub const fn new() -> Self {
+        ChannelCalibrations {
+            calibrations: [None; 4],
+        }
+    }
+
+    /// Return the table with the calibration of `channel` replaced.
+    pub const fn with(mut self, channel: Channel, calibration: Calibration) -> Self {
+        self.calibrations[channel.index() as usize] = Some(calibration);
+        self
+    }
+
+    /// Set or clear the calibration of `channel`.
+    pub fn set(&mut self, channel: Channel, calibration: Option<Calibration>) {
+        self.calibrations[usize::from(channel.index())] = calibration;
+    }
+
+    /// Return the calibration of `channel`.
+    pub fn get(&self, channel: Channel) -> Option<Calibration> {
+        self.calibrations[usize::from(channel.index())]
+    }
+
+    /// Return `config` with the calibration of its channel, unless it
+    /// already has a calibration.
+    pub fn apply(&self, config: &Config) -> Config {
+        Config {
+            calibration: config.calibration.or(self.get(config.channel)),
+            ..*config
+        }
+    }
+}
+
+/// Calculate the CRC-8 of `data` with the polynomial 0x31 and the initial
+/// value 0xFF.
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0xFF, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x31
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_points() {
+        let calibration = Calibration::from_points(500, 0, 1_996_000, 2_000_000).unwrap();
+        // The gain is quantized to 1ppm, i.e. 2µV at full scale
+        let close = |measured: i32, actual: i32| {
+            let corrected = calibration.apply_uv(measured);
+            assert!((corrected - actual).abs() <= 2, "{}", corrected);
+        };
+        close(500, 0);
+        close(1_996_000, 2_000_000);
+        // Halfway between the points
+        close(998_250, 1_000_000);
+        // The order of the points does not matter
+        let reversed = Calibration::from_points(1_996_000, 2_000_000, 500, 0).unwrap();
+        assert_eq!(reversed.gain_ppm, calibration.gain_ppm);
+        assert!((reversed.offset_uv - calibration.offset_uv).abs() <= 2);
+    }
+
+    #[test]
+    fn test_from_points_invalid() {
+        assert_eq!(Calibration::from_points(1_000, 0, 1_000, 5), None);
+        assert_eq!(Calibration::from_points(0, 0, 1, 1_000_000), None);
+    }
+
+    #[test]
+    fn test_new() {
+        let calibration = Calibration::new(-1_000, -500);
+        assert_eq!(calibration.offset_uv(), -1_000);
+        assert_eq!(calibration.gain_ppm(), -500);
+        assert_eq!(calibration.apply_uv(2_000_000), 1_998_000);
+        assert_eq!(calibration.apply_uv(-2_000_000), -2_000_000);
+    }
+
+    #[test]
+    fn test_zeroed_at() {
+        let calibration = Calibration::new(7, 10_000).zeroed_at(-1_000);
+        assert_eq!(calibration, Calibration::new(1_010, 10_000));
+        assert_eq!(calibration.apply_uv(-1_000), 0);
+    }
+
+    #[test]
+    fn test_error_limits() {
+        let typical = ErrorLimits::typical(Gain::Gain1);
+        assert_eq!(typical.error_bound_uv(-2_000_000), 1_015);
+        let maximum = ErrorLimits::maximum(Gain::Gain8);
+        assert_eq!(maximum.gain_ppm, 4_500);
+        assert_eq!(maximum.error_bound_uv(200_000), 940);
+    }
+
+    #[test]
+    fn test_bounded_calibration() {
+        let datasheet = BoundedCalibration::datasheet(Gain::Gain8);
+        assert_eq!(datasheet.calibration, Calibration::default());
+        assert_eq!(datasheet.limits, ErrorLimits::maximum(Gain::Gain8));
+        assert_eq!(datasheet.apply_uv(-200_000), (-200_000, 940));
+
+        let calibrated = BoundedCalibration {
+            calibration: Calibration::new(-250, 1_000),
+            limits: ErrorLimits::typical(Gain::Gain1),
+        };
+        assert_eq!(calibrated.apply_uv(1_000_250), (1_001_000, 515));
+    }
+
+    #[test]
+    fn test_channel_calibrations() {
+        let mut calibrations =
+            ChannelCalibrations::new().with(Channel::Channel1, Calibration::new(1, 2));
+        assert_eq!(
+            calibrations.get(Channel::Channel1),
+            Some(Calibration::new(1, 2))
+        );
+        // An explicit calibration takes precedence
+        let config = Config::default().with_calibration(Calibration::new(3, 4));
+        assert_eq!(calibrations.apply(&config), config);
+        calibrations.set(Channel::Channel1, None);
+        assert_eq!(calibrations.apply(&Config::default()), Config::default());
+    }
+
+    #[test]
+    fn test_tempco() {
+        let calibration = Calibration::new(-250, 1_000);
+        let tempco = Tempco::new(2, 15);
+        assert_eq!(tempco.compensate(&calibration, 25_000), calibration);
+        assert_eq!(
+            tempco.compensate(&calibration, -20_000),
+            Calibration::new(-160, 1_675)
+        );
+        let compensated = tempco.compensate(&calibration, 60_000);
+        assert_eq!(compensated, Calibration::new(-320, 475));
+        assert_eq!(tempco.uncompensate(&compensated, 60_000), calibration);
+
+        let tempco = tempco.with_reference(0);
+        assert_eq!(tempco.reference_mc(), 0);
+        assert_eq!(
+            tempco.compensate(&calibration, 500),
+            Calibration::new(-251, 993)
+        );
+    }
+
+    #[test]
+    fn test_crc8() {
+        // Check value of CRC-8/NRSC-5
+        assert_eq!(crc8(b"123456789"), 0xF7);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let calibration = Calibration::new(-123_456, 2_255);
+        let bytes = calibration.to_bytes();
+        assert_eq!(bytes[..9], [1, 0xFF, 0xFE, 0x1D, 0xC0, 0, 0, 0x08, 0xCF]);
+        assert_eq!(Calibration::from_bytes(&bytes), Ok(calibration));
+    }
+
+    #[test]
+    fn test_bytes_invalid() {
+        let err = Calibration::from_bytes(&[0xFF; 10]).unwrap_err();
+        assert_eq!(err, DecodeError::UnsupportedVersion(0xFF));
+        let mut bytes = Calibration::new(5, 5).to_bytes();
+        bytes[4] ^= 0x01;
+        let err = Calibration::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err, DecodeError::Checksum);
+    }
+
+    #[test]
+    fn test_default_is_identity() {
+        let calibration = Calibration::default();
+        assert_eq!(calibration.apply_uv(-2_048_000), -2_048_000);
+        assert_eq!(calibration.apply_uv(1_234), 1_234);
+    }
+}
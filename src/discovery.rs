@@ -0,0 +1,203 @@
+//! Discovery of MCP342x devices on a bus.
+//!
+//! These helpers are meant for bring-up tools and production tests. They only
+//! read from the devices, so no configuration is changed and no conversion is
+//! started.
+//!
+//! ```
+//! # use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+//! # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+//! use mcp3425::discovery::scan;
+//!
+//! # let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+//! # let mut expectations = vec![Transaction::read(0x68, vec![0, 0, 0x90]).with_error(nack)];
+//! # expectations.push(Transaction::read(0x69, vec![0, 0, 0x90]));
+//! # for addr in 0x6A..=0x6F {
+//! #     expectations.push(Transaction::read(addr, vec![0, 0, 0]).with_error(nack));
+//! # }
+//! # let mut i2c = Mock::new(&expectations);
+//! let found = scan(&mut i2c).unwrap();
+//! assert_eq!(found.len(), 1);
+//! for address in found.iter() {
+//!     // ...
+//! #   assert_eq!(address.value(), 0x69);
+//! }
+//! # i2c.done();
+//! ```
+
+use embedded_hal::i2c::{Error as I2cError, ErrorKind, I2c};
+
+use crate::address::Address;
+
+/// Resolution bits of the configuration register.
+const RESOLUTION_MASK: u8 = 0b00001100;
+
+/// Resolution bits of the configuration register for 18 bits.
+const RESOLUTION_18BIT: u8 = 0b00001100;
+
+/// The set of addresses found by [`scan`](fn.scan.html).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ScanResult {
+    /// One bit per address, bit 0 is `0x68`.
+    found: u8,
+}
+
+impl ScanResult {
+    /// Return whether a device responded at `address`.
+    pub fn contains(&self, address: Address) -> bool {
+        self.found & Self::bit(address) != 0
+    }
+
+    /// Return the number of devices found.
+    pub fn len(&self) -> usize {
+        self.found.count_ones() as usize
+    }
+
+    /// Return whether no device was found.
+    pub fn is_empty(&self) -> bool {
+        self.found == 0
+    }
+
+    /// Return the addresses of all devices found, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = Address> {
+        let found = *self;
+        Address::all().filter(move |address| found.contains(*address))
+    }
+
+    fn insert(&mut self, address: Address) {
+        self.found |= Self::bit(address);
+    }
+
+    fn bit(address: Address) -> u8 {
+        1 << (address.value() & 0b111)
+    }
+}
+
+/// Return whether a device acknowledges a read at `address`.
+///
+/// A missing acknowledge is reported as `Ok(false)`, all other bus errors are
+/// returned as they are.
+pub fn probe<I2C: I2c>(i2c: &mut I2C, address: Address) -> Result<bool, I2C::Error> {
+    let mut buf = [0u8; 3];
+    acknowledged(i2c.read(address.value(), &mut buf))
+}
+
+/// Like [`probe`](fn.probe.html), but additionally check that the data read
+/// back looks like it was sent by an MCP342x device.
+///
+/// After the output code and the configuration register, the device repeats
+/// the configuration register for every additional byte read. Devices that
+/// acknowledge, but don't follow this pattern, are reported as `Ok(false)`.
+pub fn probe_checked<I2C: I2c>(i2c: &mut I2C, address: Address) -> Result<bool, I2C::Error> {
+    let mut buf = [0u8; 5];
+    if !acknowledged(i2c.read(address.value(), &mut buf))? {
+        return Ok(false);
+    }
+    Ok(is_plausible(&buf))
+}
+
+/// Probe all eight MCP342x addresses with [`probe`](fn.probe.html).
+pub fn scan<I2C: I2c>(i2c: &mut I2C) -> Result<ScanResult, I2C::Error> {
+    scan_with(i2c, probe)
+}
+
+/// Probe all eight MCP342x addresses with
+/// [`probe_checked`](fn.probe_checked.html).
+pub fn scan_checked<I2C: I2c>(i2c: &mut I2C) -> Result<ScanResult, I2C::Error> {
+    scan_with(i2c, probe_checked)
+}
+
+fn scan_with<I2C, F>(i2c: &mut I2C, mut probe: F) -> Result<ScanResult, I2C::Error>
+where
+    I2C: I2c,
+    F: FnMut(&mut I2C, Address) -> Result<bool, I2C::Error>,
+{
+    let mut result = ScanResult::default();
+    for address in Address::all() {
+        if probe(i2c, address)? {
+            result.insert(address);
+        }
+    }
+    Ok(result)
+}
+
+/// Map a missing acknowledge to `Ok(false)`.
+fn acknowledged<E: I2cError>(result: Result<(), E>) -> Result<bool, E> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check a 5 byte read for the repeated configuration register.
+fn is_plausible(buf: &[u8; 5]) -> bool {
+    // 12 to 16 bits: two data bytes, then the configuration register
+    let short =
+        buf[2] == buf[3] && buf[3] == buf[4] && buf[2] & RESOLUTION_MASK != RESOLUTION_18BIT;
+    // 18 bits: three data bytes with a sign extended MSB, then the
+    // configuration register
+    let long = matches!(buf[0], 0x00 | 0x01 | 0xFE | 0xFF)
+        && buf[3] == buf[4]
+        && buf[3] & RESOLUTION_MASK == RESOLUTION_18BIT;
+    short || long
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction};
+    use rstest::rstest;
+
+    use super::*;
+
+    const NACK: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    #[test]
+    fn test_probe() {
+        let expectations = [
+            Transaction::read(0x68, vec![0, 0, 0x90]),
+            Transaction::read(0x69, vec![0, 0, 0]).with_error(NACK),
+            Transaction::read(0x6A, vec![0, 0, 0]).with_error(ErrorKind::Bus),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let address = |value| Address::try_from(value).unwrap();
+        assert!(probe(&mut i2c, address(0x68)).unwrap());
+        assert!(!probe(&mut i2c, address(0x69)).unwrap());
+        assert_eq!(probe(&mut i2c, address(0x6A)), Err(ErrorKind::Bus));
+        i2c.done();
+    }
+
+    #[rstest]
+    #[case([0x12, 0x34, 0x10, 0x10, 0x10], true)] // 12 bits
+    #[case([0x12, 0x34, 0x98, 0x98, 0x98], true)] // 16 bits, converting
+    #[case([0x01, 0x12, 0x34, 0x1C, 0x1C], true)] // 18 bits
+    #[case([0x12, 0x34, 0x1C, 0x1C, 0x1C], false)] // 18 bits, but too short
+    #[case([0x12, 0x34, 0x10, 0x10, 0x11], false)] // not repeated
+    fn test_probe_checked(#[case] data: [u8; 5], #[case] expected: bool) {
+        let expectations = [Transaction::read(0x6C, data.to_vec())];
+        let mut i2c = I2cMock::new(&expectations);
+        let address = Address::try_from(0x6C).unwrap();
+        assert_eq!(probe_checked(&mut i2c, address).unwrap(), expected);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_scan() {
+        let expectations: Vec<_> = Address::all()
+            .map(|address| {
+                let transaction = Transaction::read(address.value(), vec![0, 0, 0]);
+                match address.value() {
+                    0x68 | 0x6D => transaction,
+                    _ => transaction.with_error(NACK),
+                }
+            })
+            .collect();
+        let mut i2c = I2cMock::new(&expectations);
+        let found = scan(&mut i2c).unwrap();
+        assert_eq!(found.len(), 2);
+        let values: Vec<u8> = found.iter().map(u8::from).collect();
+        assert_eq!(values, [0x68, 0x6D]);
+        i2c.done();
+    }
+}
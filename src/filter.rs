@@ -0,0 +1,587 @@
+//! Smoothing and envelope tracking of measurements without floating point
+//! math.
+//!
+//! The filters are fed with voltages, e.g. from continuous mode reads, and
+//! return the smoothed voltage. [`EmaFilter`](struct.EmaFilter.html) reacts
+//! to changes gradually, [`MovingAverage`](struct.MovingAverage.html) weights
+//! the last samples equally, and [`KalmanFilter`](struct.KalmanFilter.html)
+//! derives the smoothing from the noise of the input.
+//! [`PeakDetector`](struct.PeakDetector.html) tracks the envelope instead,
+//! and [`WindowExtremes`](struct.WindowExtremes.html) the extremes of the
+//! last samples:
+//!
+//! ```
+//! use mcp3425::filter::EmaFilter;
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! # use mcp3425::{Config, MCP3425};
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b00010000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! # let mut adc = MCP3425::continuous(i2c, 0x68, NoopDelay);
+//! # adc.set_config(&Config::default()).unwrap();
+//! // Weight new samples with 1/8
+//! let mut filter = EmaFilter::new(1, 8);
+//! let smoothed = filter.update(adc.read_measurement().unwrap());
+//! # dev.done();
+//! ```
+
+use crate::{voltage_from_microvolts, voltage_to_microvolts, Voltage};
+
+/// Number of fractional bits of the filter states.
+const FRACTION_BITS: u32 = 8;
+
+/// Convert a voltage to a fixed-point microvolt value.
+fn to_fixed(voltage: &Voltage) -> i64 {
+    i64::from(voltage_to_microvolts(voltage)) << FRACTION_BITS
+}
+
+/// Convert a fixed-point microvolt value to a voltage, rounding to the
+/// nearest microvolt.
+fn from_fixed(value: i64) -> Voltage {
+    // The value is within the range of the samples, so it fits
+    voltage_from_microvolts(((value + (1 << (FRACTION_BITS - 1))) >> FRACTION_BITS) as i32)
+}
+
+/// Exponential moving average with a rational smoothing factor.
+///
+/// Every update moves the output by `alpha = numerator / denominator` of
+/// the difference to the new sample. Small factors smooth more, but follow
+/// changes of the input more slowly. The first sample initializes the
+/// output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EmaFilter {
+    /// Numerator of the smoothing factor.
+    numerator: u16,
+    /// Denominator of the smoothing factor.
+    denominator: u16,
+    /// The current output in fixed-point microvolts, `None` before the
+    /// first sample.
+    state: Option<i64>,
+}
+
+impl EmaFilter {
+    /// Create a filter with the smoothing factor `numerator / denominator`.
+    ///
+    /// Panics if the factor is not in the range `(0, 1]`.
+    pub const fn new(numerator: u16, denominator: u16) -> Self {
+        assert!(
+            numerator > 0 && numerator <= denominator,
+            "smoothing factor out of range"
+        );
+        EmaFilter {
+            numerator,
+            denominator,
+            state: None,
+        }
+    }
+
+    /// Add a sample and return the smoothed voltage.
+    pub fn update(&mut self, voltage: Voltage) -> Voltage {
+        let sample = to_fixed(&voltage);
+        let state = match self.state {
+            None => sample,
+            Some(state) => {
+                let step =
+                    (sample - state) * i64::from(self.numerator) / i64::from(self.denominator);
+                state + step
+            }
+        };
+        self.state = Some(state);
+        from_fixed(state)
+    }
+
+    /// Return the smoothed voltage, or `None` before the first sample.
+    pub fn value(&self) -> Option<Voltage> {
+        self.state.map(from_fixed)
+    }
+
+    /// Discard the filter state, so the next sample initializes the output.
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Boxcar average of the last `N` samples.
+///
+/// The samples are stored in a ring buffer on the stack, and every update
+/// takes constant time. Until `N` samples were added, the average of the
+/// samples so far is returned.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MovingAverage<const N: usize> {
+    /// The last samples in microvolts.
+    samples: [i32; N],
+    /// Index of the oldest sample, which is replaced next.
+    next: usize,
+    /// Number of valid samples.
+    len: usize,
+    /// Sum of the valid samples in microvolts.
+    sum: i64,
+}
+
+impl<const N: usize> MovingAverage<N> {
+    /// Create an empty moving average.
+    ///
+    /// Panics if `N` is 0.
+    pub const fn new() -> Self {
+        assert!(N > 0, "moving average over zero samples");
+        MovingAverage {
+            samples: [0; N],
+            next: 0,
+            len: 0,
+            sum: 0,
+        }
+    }
+
+    /// Add a sample, replacing the oldest one if the buffer is full, and
+    /// return the average.
+    pub fn update(&mut self, voltage: Voltage) -> Voltage {
+        let sample = voltage_to_microvolts(&voltage);
+        if self.len == N {
+            self.sum -= i64::from(self.samples[self.next]);
+        } else {
+            self.len += 1;
+        }
+        self.samples[self.next] = sample;
+        self.sum += i64::from(sample);
+        self.next = (self.next + 1) % N;
+        self.average()
+    }
+
+    /// Return the average, or `None` before the first sample.
+    pub fn value(&self) -> Option<Voltage> {
+        (self.len > 0).then(|| self.average())
+    }
+
+    /// Return whether `N` samples were added.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Discard all samples.
+    pub fn reset(&mut self) {
+        *self = MovingAverage::new();
+    }
+
+    /// Return the average of the valid samples, rounded towards zero.
+    fn average(&self) -> Voltage {
+        // The average is within the range of the samples, so it fits
+        voltage_from_microvolts((self.sum / self.len as i64) as i32)
+    }
+}
+
+impl<const N: usize> Default for MovingAverage<N> {
+    fn default() -> Self {
+        MovingAverage::new()
+    }
+}
+
+/// Scalar Kalman filter for a slowly varying input.
+///
+/// The input is modeled as a random walk with the variance
+/// `process_noise` per sample, measured with additive noise of the variance
+/// `measurement_noise`. Both are in µV². The ratio determines the
+/// smoothing: A small process noise relative to the measurement noise trusts
+/// the estimate more than new samples. For constant noise parameters, the
+/// filter converges to an exponential moving average with the optimal
+/// smoothing factor.
+///
+/// ```
+/// use mcp3425::filter::KalmanFilter;
+///
+/// // Measurement noise with a standard deviation of 2mV, input changes
+/// // by about 0.1mV per sample
+/// let mut filter = KalmanFilter::new(100 * 100, 2_000 * 2_000);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KalmanFilter {
+    /// Variance of the input change per sample in µV².
+    process_noise: u64,
+    /// Variance of the measurement noise in µV².
+    measurement_noise: u64,
+    /// The estimate in fixed-point microvolts, `None` before the first
+    /// sample.
+    estimate: Option<i64>,
+    /// Variance of the estimate in µV².
+    variance: u64,
+}
+
+impl KalmanFilter {
+    /// Create a filter with the noise variances in µV².
+    ///
+    /// Panics if `measurement_noise` is 0.
+    pub const fn new(process_noise: u32, measurement_noise: u32) -> Self {
+        assert!(measurement_noise > 0, "measurement noise must not be 0");
+        KalmanFilter {
+            process_noise: process_noise as u64,
+            measurement_noise: measurement_noise as u64,
+            estimate: None,
+            variance: 0,
+        }
+    }
+
+    /// Add a sample and return the new estimate.
+    pub fn update(&mut self, voltage: Voltage) -> Voltage {
+        let sample = to_fixed(&voltage);
+        let estimate = match self.estimate {
+            None => {
+                self.variance = self.measurement_noise;
+                sample
+            }
+            Some(estimate) => {
+                // Predict: The input may have changed since the last sample
+                let predicted = self.variance.saturating_add(self.process_noise);
+                // Update: Weight the sample with the Kalman gain P / (P + R)
+                let total = predicted.saturating_add(self.measurement_noise);
+                let step =
+                    i128::from(sample - estimate) * i128::from(predicted) / i128::from(total);
+                self.variance = (u128::from(predicted) * u128::from(self.measurement_noise)
+                    / u128::from(total)) as u64;
+                // The gain is at most 1, so the step fits
+                estimate + step as i64
+            }
+        };
+        self.estimate = Some(estimate);
+        from_fixed(estimate)
+    }
+
+    /// Return the estimate, or `None` before the first sample.
+    pub fn value(&self) -> Option<Voltage> {
+        self.estimate.map(from_fixed)
+    }
+
+    /// Return the variance of the estimate in µV².
+    pub fn variance(&self) -> u64 {
+        self.variance
+    }
+
+    /// Discard the estimate, so the next sample initializes the filter.
+    pub fn reset(&mut self) {
+        self.estimate = None;
+        self.variance = 0;
+    }
+}
+
+/// Running maximum and minimum of the input, e.g. to capture inrush
+/// currents or to monitor the envelope of a signal.
+///
+/// Without decay, the peak and valley hold the extreme values since the
+/// last [`reset`](#method.reset). With decay, they move towards the input
+/// by a fixed amount per sample, so the envelope follows a falling signal.
+///
+/// ```
+/// use mcp3425::filter::PeakDetector;
+///
+/// // Let the envelope fall by 0.5mV per sample
+/// let mut detector = PeakDetector::new().with_decay(500);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PeakDetector {
+    /// Decay per sample in microvolts.
+    decay_uv: u32,
+    /// The peak and valley in microvolts, `None` before the first sample.
+    extremes: Option<(i32, i32)>,
+}
+
+impl PeakDetector {
+    /// Create a detector without decay.
+    pub const fn new() -> Self {
+        PeakDetector {
+            decay_uv: 0,
+            extremes: None,
+        }
+    }
+
+    /// Let the peak and valley decay towards the input by `decay_uv`
+    /// microvolts per sample.
+    pub const fn with_decay(self, decay_uv: u32) -> Self {
+        PeakDetector { decay_uv, ..self }
+    }
+
+    /// Add a sample.
+    pub fn update(&mut self, voltage: Voltage) {
+        let sample = voltage_to_microvolts(&voltage);
+        let decay = i32::try_from(self.decay_uv).unwrap_or(i32::MAX);
+        self.extremes = Some(match self.extremes {
+            None => (sample, sample),
+            Some((peak, valley)) => (
+                peak.saturating_sub(decay).max(sample),
+                valley.saturating_add(decay).min(sample),
+            ),
+        });
+    }
+
+    /// Return the peak, or `None` before the first sample.
+    pub fn peak(&self) -> Option<Voltage> {
+        self.extremes.map(|(peak, _)| voltage_from_microvolts(peak))
+    }
+
+    /// Return the valley, or `None` before the first sample.
+    pub fn valley(&self) -> Option<Voltage> {
+        self.extremes
+            .map(|(_, valley)| voltage_from_microvolts(valley))
+    }
+
+    /// Discard the peak and valley, keeping the decay.
+    pub fn reset(&mut self) {
+        self.extremes = None;
+    }
+}
+
+/// Minimum and maximum of the last `N` samples, e.g. to measure the ripple
+/// of a power rail.
+///
+/// The candidates for the extremes are kept in two monotonic queues on the
+/// stack, so every update takes amortized constant time.
+///
+/// ```
+/// use mcp3425::filter::WindowExtremes;
+///
+/// let mut window = WindowExtremes::<64>::new();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WindowExtremes<const N: usize> {
+    /// Candidates for the minimum, increasing from front to back.
+    min: MonotonicQueue<N>,
+    /// Candidates for the maximum, decreasing from front to back.
+    max: MonotonicQueue<N>,
+    /// Number of samples added, wrapping around.
+    count: u32,
+}
+
+impl<const N: usize> WindowExtremes<N> {
+    /// Create an empty window.
+    ///
+    /// Panics if `N` is 0 or does not fit into a `u32`.
+    pub const fn new() -> Self {
+        assert!(N > 0, "window of zero samples");
+        assert!(N <= u32::MAX as usize, "window too large");
+        WindowExtremes {
+            min: MonotonicQueue::new(),
+            max: MonotonicQueue::new(),
+            count: 0,
+        }
+    }
+
+    /// Add a sample, dropping the oldest one if `N` samples were added.
+    pub fn update(&mut self, voltage: Voltage) {
+        let sample = voltage_to_microvolts(&voltage);
+        // The sample that left the window
+        let expired = self.count.wrapping_sub(N as u32);
+        self.min
+            .push(self.count, sample, expired, |new, old| new <= old);
+        self.max
+            .push(self.count, sample, expired, |new, old| new >= old);
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Return the minimum of the window, or `None` before the first sample.
+    pub fn min(&self) -> Option<Voltage> {
+        self.min.front().map(voltage_from_microvolts)
+    }
+
+    /// Return the maximum of the window, or `None` before the first sample.
+    pub fn max(&self) -> Option<Voltage> {
+        self.max.front().map(voltage_from_microvolts)
+    }
+
+    /// Return the difference between the maximum and the minimum, or `None`
+    /// before the first sample.
+    pub fn ripple(&self) -> Option<Voltage> {
+        Some(voltage_from_microvolts(
+            self.max.front()?.saturating_sub(self.min.front()?),
+        ))
+    }
+
+    /// Discard all samples.
+    pub fn reset(&mut self) {
+        *self = WindowExtremes::new();
+    }
+}
+
+impl<const N: usize> Default for WindowExtremes<N> {
+    fn default() -> Self {
+        WindowExtremes::new()
+    }
+}
+
+/// Ring buffer of `(index, microvolts)` samples, where every sample
+/// dominates the ones behind it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct MonotonicQueue<const N: usize> {
+    /// The samples, starting at `head`.
+    entries: [(u32, i32); N],
+    /// Position of the front sample.
+    head: usize,
+    /// Number of samples.
+    len: usize,
+}
+
+impl<const N: usize> MonotonicQueue<N> {
+    const fn new() -> Self {
+        MonotonicQueue {
+            entries: [(0, 0); N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Remove the front sample if its index is `expired`, remove all samples
+    /// from the back that the new one `dominates`, and append it.
+    fn push(&mut self, index: u32, sample: i32, expired: u32, dominates: fn(i32, i32) -> bool) {
+        if self.len > 0 && self.entries[self.head].0 == expired {
+            self.head = (self.head + 1) % N;
+            self.len -= 1;
+        }
+        while self.len > 0 && dominates(sample, self.entries[(self.head + self.len - 1) % N].1) {
+            self.len -= 1;
+        }
+        // The expired sample was removed, so there is room for the new one
+        self.entries[(self.head + self.len) % N] = (index, sample);
+        self.len += 1;
+    }
+
+    /// Return the front sample.
+    fn front(&self) -> Option<i32> {
+        (self.len > 0).then(|| self.entries[self.head].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(millivolts: i32) -> Voltage {
+        voltage_from_microvolts(millivolts * 1000)
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut filter = EmaFilter::new(1, 4);
+        assert_eq!(filter.value(), None);
+        assert_eq!(filter.update(mv(100)), mv(100));
+        assert_eq!(filter.update(mv(200)), mv(125));
+        assert_eq!(filter.update(mv(200)), voltage_from_microvolts(143_750));
+        filter.reset();
+        assert_eq!(filter.update(mv(-10)), mv(-10));
+    }
+
+    /// The output converges to a constant input despite integer math.
+    #[test]
+    fn test_ema_converges() {
+        let mut filter = EmaFilter::new(1, 16);
+        filter.update(mv(0));
+        for _ in 0..200 {
+            filter.update(mv(10));
+        }
+        assert_eq!(filter.value(), Some(mv(10)));
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let mut average = MovingAverage::<3>::new();
+        assert_eq!(average.value(), None);
+        assert_eq!(average.update(mv(3)), mv(3));
+        assert_eq!(average.update(mv(6)), voltage_from_microvolts(4_500));
+        assert!(!average.is_full());
+        assert_eq!(average.update(mv(9)), mv(6));
+        assert!(average.is_full());
+        // The oldest sample is replaced
+        assert_eq!(average.update(mv(-15)), mv(0));
+        average.reset();
+        assert_eq!(average.value(), None);
+    }
+
+    #[test]
+    fn test_kalman() {
+        let mut filter = KalmanFilter::new(0, 1_000_000);
+        assert_eq!(filter.value(), None);
+        assert_eq!(filter.update(mv(10)), mv(10));
+        assert_eq!(filter.variance(), 1_000_000);
+        // Without process noise, this is the mean of all samples
+        assert_eq!(filter.update(mv(20)), mv(15));
+        assert_eq!(filter.update(mv(30)), mv(20));
+        assert_eq!(filter.variance(), 333_333);
+        filter.reset();
+        assert_eq!(filter.update(mv(-5)), mv(-5));
+    }
+
+    /// With process noise, the variance settles instead of reaching zero.
+    #[test]
+    fn test_kalman_steady_state() {
+        let mut filter = KalmanFilter::new(1_000, 1_000_000);
+        for _ in 0..1_000 {
+            filter.update(mv(0));
+        }
+        // P² + QP - QR = 0 for the posterior variance P
+        let variance = filter.variance();
+        assert!((30_000..33_000).contains(&variance), "{}", variance);
+    }
+
+    #[test]
+    fn test_peak_detector() {
+        let mut detector = PeakDetector::new();
+        assert_eq!(detector.peak(), None);
+        for millivolts in [5, 40, -3, 12] {
+            detector.update(mv(millivolts));
+        }
+        assert_eq!(detector.peak(), Some(mv(40)));
+        assert_eq!(detector.valley(), Some(mv(-3)));
+        detector.reset();
+        assert_eq!(detector.valley(), None);
+    }
+
+    #[test]
+    fn test_window_extremes() {
+        let mut window = WindowExtremes::<3>::new();
+        assert_eq!(window.ripple(), None);
+        let expected = [
+            (5, 5, 5),
+            (1, 1, 5),
+            (7, 1, 7),
+            (3, 1, 7),
+            (4, 3, 7),
+            (2, 2, 4),
+            (2, 2, 4),
+            (2, 2, 2),
+        ];
+        for (sample, min, max) in expected {
+            window.update(mv(sample));
+            assert_eq!((window.min(), window.max()), (Some(mv(min)), Some(mv(max))));
+        }
+        assert_eq!(window.ripple(), Some(mv(0)));
+        window.reset();
+        assert_eq!(window.min(), None);
+    }
+
+    /// The result matches a brute force search over the window.
+    #[test]
+    fn test_window_extremes_brute_force() {
+        let samples: Vec<i32> = (0..200).map(|i: i32| (i * 37 % 101) - 50).collect();
+        let mut window = WindowExtremes::<7>::new();
+        for (i, &sample) in samples.iter().enumerate() {
+            window.update(mv(sample));
+            let start = (i + 1).saturating_sub(7);
+            let slice = &samples[start..=i];
+            let min = *slice.iter().min().unwrap();
+            let max = *slice.iter().max().unwrap();
+            assert_eq!(window.ripple(), Some(mv(max - min)));
+        }
+    }
+
+    /// With decay, the envelope follows a falling input.
+    #[test]
+    fn test_peak_detector_decay() {
+        let mut detector = PeakDetector::new().with_decay(10_000);
+        detector.update(mv(100));
+        detector.update(mv(0));
+        assert_eq!(detector.peak(), Some(mv(90)));
+        assert_eq!(detector.valley(), Some(mv(0)));
+        detector.update(mv(0));
+        detector.update(mv(0));
+        assert_eq!(detector.peak(), Some(mv(70)));
+    }
+}
@@ -0,0 +1,401 @@
+//! Management of several ADCs as one unit.
+//!
+//! A [`Mcp342xGroup`](struct.Mcp342xGroup.html) owns a fixed number of
+//! one-shot drivers, together with the configuration that should be used for
+//! each of them, and measures them all with a single call.
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{group::Mcp342xGroup, Config, Gain, Resolution, MCP3425};
+//!
+//! # let bus = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10000001]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! #     Transaction::write(0x69, vec![0b10000100]),
+//! #     Transaction::read(0x69, vec![0, 0, 0]),
+//! # ]);
+//! # let (i2c1, i2c2) = (bus.clone(), bus.clone());
+//! let adc1 = MCP3425::oneshot(i2c1, 0x68, NoopDelay);
+//! let adc2 = MCP3425::oneshot(i2c2, 0x69, NoopDelay);
+//!
+//! let mut group = Mcp342xGroup::new([adc1, adc2]);
+//! group.set_config(0, Config::default().with_gain(Gain::Gain2));
+//! group.set_config(1, Config::default().with_resolution(Resolution::Bits14Sps60));
+//!
+//! let voltages = group.measure_all().unwrap();
+//! # bus.clone().done();
+//! ```
+//!
+//! A [`ChannelExpander`](struct.ChannelExpander.html) presents the channels
+//! of several multi-channel devices (e.g. four MCP3428) as a single flat
+//! range of virtual channels, and samples one channel of every device at the
+//! same time using the I²C general call.
+//!
+//! Drivers that were switched to a specific model with
+//! [`with_model`](../struct.MCP3425.html#method.with_model) can be added to a
+//! group after switching them back with `with_model(AnyModel)`.
+
+use core::array;
+use core::fmt;
+
+use embedded_hal::delay::DelayNs;
+
+use crate::transport::Transport;
+use crate::{
+    general_call_conversion, Channel, Config, Error, MeasureError, OneShotMode, ReadError, Voltage,
+    MCP3425,
+};
+
+/// Error returned by
+/// [`Mcp342xGroup::measure_all`](struct.Mcp342xGroup.html#method.measure_all)
+/// if at least one device failed.
+///
+/// The results of all devices, including the successful ones, are kept in
+/// device order.
+pub struct GroupError<E, const N: usize> {
+    /// The result of every device in the group.
+    pub results: [Result<Voltage, MeasureError<E>>; N],
+}
+
+impl<E, const N: usize> GroupError<E, N> {
+    /// Return the number of devices that failed.
+    pub fn failure_count(&self) -> usize {
+        self.results.iter().filter(|result| result.is_err()).count()
+    }
+
+    /// Return the index and error of every device that failed.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &MeasureError<E>)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.as_ref().err().map(|err| (index, err)))
+    }
+}
+
+impl<E: fmt::Debug, const N: usize> fmt::Debug for GroupError<E, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GroupError")
+            .field("results", &self.results)
+            .finish()
+    }
+}
+
+/// A fixed number of one-shot ADCs, each with its own configuration.
+#[derive(Debug)]
+pub struct Mcp342xGroup<I2C, D, const N: usize> {
+    /// The devices in this group.
+    devices: [MCP3425<I2C, D, OneShotMode>; N],
+    /// The configuration used for each device.
+    configs: [Config; N],
+}
+
+impl<I2C, D, const N: usize> Mcp342xGroup<I2C, D, N>
+where
+    I2C: Transport,
+    D: DelayNs,
+{
+    /// Create a new group. All devices start with the default configuration.
+    pub fn new(devices: [MCP3425<I2C, D, OneShotMode>; N]) -> Self {
+        Mcp342xGroup {
+            devices,
+            configs: [Config::default(); N],
+        }
+    }
+
+    /// Set the configuration used for the device at `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_config(&mut self, index: usize, config: Config) {
+        self.configs[index] = config;
+    }
+
+    /// Return the configuration used for the device at `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn config(&self, index: usize) -> &Config {
+        &self.configs[index]
+    }
+
+    /// Return a mutable reference to the driver at `index`, or `None` if the
+    /// index is out of bounds.
+    pub fn device_mut(&mut self, index: usize) -> Option<&mut MCP3425<I2C, D, OneShotMode>> {
+        self.devices.get_mut(index)
+    }
+
+    /// Measure every device with its configuration, in device order.
+    ///
+    /// A failing device does not stop the remaining measurements. If any
+    /// device failed, a [`GroupError`](struct.GroupError.html) with the
+    /// results of all devices is returned.
+    pub fn measure_all(&mut self) -> Result<[Voltage; N], GroupError<I2C::Error, N>> {
+        let results: [Result<Voltage, MeasureError<I2C::Error>>; N] =
+            array::from_fn(|i| self.devices[i].measure(&self.configs[i]));
+        if results.iter().any(Result::is_err) {
+            return Err(GroupError { results });
+        }
+        Ok(results.map(|result| match result {
+            Ok(voltage) => voltage,
+            Err(_) => unreachable!(),
+        }))
+    }
+
+    /// Destroy the group and return the drivers.
+    pub fn release(self) -> [MCP3425<I2C, D, OneShotMode>; N] {
+        self.devices
+    }
+}
+
+/// Several multi-channel devices on the same bus, addressed as `N` virtual
+/// channels.
+///
+/// The virtual channels are numbered device by device: With `CHIPS` devices,
+/// every device provides `N / CHIPS` channels, so virtual channel `i` is
+/// channel `i % (N / CHIPS)` of device `i / (N / CHIPS)`. Each virtual
+/// channel has its own configuration; its `channel` is ignored.
+///
+/// ```
+/// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+/// use mcp3425::{group::ChannelExpander, Config, Gain, MCP3425};
+///
+/// # let bus = Mock::new(&[
+/// #     Transaction::write(0x68, vec![0b00000000]),
+/// #     Transaction::write(0x69, vec![0b00000011]),
+/// #     Transaction::write(0x00, vec![0b00001000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0]),
+/// #     Transaction::read(0x69, vec![0, 0, 0]),
+/// # ]);
+/// # let (i2c1, i2c2) = (bus.clone(), bus.clone());
+/// let adc1 = MCP3425::oneshot(i2c1, 0x68, NoopDelay);
+/// let adc2 = MCP3425::oneshot(i2c2, 0x69, NoopDelay);
+///
+/// let mut expander = ChannelExpander::<_, _, 2, 2>::new([adc1, adc2]);
+/// expander.set_config(1, Config::default().with_gain(Gain::Gain8));
+/// let [ch0, ch1] = expander.measure_all().unwrap();
+/// # bus.clone().done();
+/// ```
+#[derive(Debug)]
+pub struct ChannelExpander<I2C, D, const CHIPS: usize, const N: usize> {
+    /// The devices, sharing the same bus.
+    devices: [MCP3425<I2C, D, OneShotMode>; CHIPS],
+    /// The configuration of every virtual channel.
+    configs: [Config; N],
+}
+
+impl<I2C, D, const CHIPS: usize, const N: usize> ChannelExpander<I2C, D, CHIPS, N>
+where
+    I2C: Transport,
+    D: DelayNs,
+{
+    /// Create a new expander. All virtual channels start with the default
+    /// configuration.
+    ///
+    /// The bus handle and delay of the first device are used to send the
+    /// general call and to wait for the conversions. Devices whose result is
+    /// not ready by then are polled with their own delay.
+    ///
+    /// Panics if `N` is not a multiple of `CHIPS`, or if a device would
+    /// need more channels than enabled with Cargo features.
+    pub fn new(devices: [MCP3425<I2C, D, OneShotMode>; CHIPS]) -> Self {
+        assert!(
+            CHIPS > 0 && N % CHIPS == 0,
+            "virtual channels not evenly distributed"
+        );
+        assert!(
+            N / CHIPS <= Channel::all().len(),
+            "too many channels per device"
+        );
+        let per_chip = N / CHIPS;
+        ChannelExpander {
+            devices,
+            configs: array::from_fn(|index| Config {
+                channel: Channel::all()[index % per_chip],
+                ..Config::default()
+            }),
+        }
+    }
+
+    /// Set the configuration of the virtual channel `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_config(&mut self, index: usize, config: Config) {
+        self.configs[index] = Config {
+            channel: self.local_channel(index),
+            ..config
+        };
+    }
+
+    /// Return the configuration of the virtual channel `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn config(&self, index: usize) -> &Config {
+        &self.configs[index]
+    }
+
+    /// Measure every virtual channel and return the results in order.
+    ///
+    /// The devices convert at the same time, one channel per device and
+    /// general call. Bus errors while configuring or triggering the devices
+    /// abort the whole measurement. Errors that only concern a single
+    /// channel are returned in that channel's slot.
+    #[allow(clippy::type_complexity)]
+    pub fn measure_all(
+        &mut self,
+    ) -> Result<[Result<Voltage, ReadError<I2C::Error>>; N], Error<I2C::Error>> {
+        let per_chip = N / CHIPS;
+        let mut results: [Option<Result<Voltage, ReadError<I2C::Error>>>; N] =
+            array::from_fn(|_| None);
+        for local in 0..per_chip {
+            let mut wait_us = 0;
+            for (chip, device) in self.devices.iter_mut().enumerate() {
+                let config = &self.configs[chip * per_chip + local];
+                device.prepare(config)?;
+                wait_us = wait_us.max(config.resolution.conversion_time_us());
+            }
+
+            // Start one conversion on every device at once, and wait for the
+            // typical time of the slowest conversion
+            let first = &mut self.devices[0];
+            general_call_conversion(&mut first.i2c).map_err(Error::I2c)?;
+            first.delay.delay_us(wait_us);
+
+            // Poll every device until its maximum conversion time has passed
+            for (chip, device) in self.devices.iter_mut().enumerate() {
+                results[chip * per_chip + local] = Some(device.poll_prepared(wait_us));
+            }
+        }
+        Ok(results.map(|result| match result {
+            Some(result) => result,
+            None => unreachable!(),
+        }))
+    }
+
+    /// Destroy the expander and return the drivers.
+    pub fn release(self) -> [MCP3425<I2C, D, OneShotMode>; CHIPS] {
+        self.devices
+    }
+
+    /// Return the device channel of the virtual channel `index`.
+    fn local_channel(&self, index: usize) -> Channel {
+        assert!(index < N, "virtual channel out of bounds");
+        let local = index % (N / CHIPS);
+        // The channel count was checked in `new`
+        Channel::all()[local]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use super::*;
+    use crate::{Gain, Resolution};
+
+    /// Every device is measured with its own configuration.
+    #[test]
+    fn test_measure_all() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000001]),
+            Transaction::read(0x68, vec![0, 1, 0]),
+            Transaction::write(0x69, vec![0b10000100]),
+            Transaction::read(0x69, vec![0, 2, 0]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let adc1 = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+        let adc2 = MCP3425::oneshot(bus.clone(), 0x69, NoopDelay);
+
+        let mut group = Mcp342xGroup::new([adc1, adc2]);
+        group.set_config(0, Config::default().with_gain(Gain::Gain2));
+        group.set_config(
+            1,
+            Config::default().with_resolution(Resolution::Bits14Sps60),
+        );
+        assert!(group.measure_all().is_ok());
+
+        group.release();
+        bus.clone().done();
+    }
+
+    /// A failing device is reported with its index, the other devices are
+    /// still measured.
+    #[test]
+    fn test_measure_all_reports_failures() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0x07, 0xFF, 0]),
+            Transaction::write(0x69, vec![0b10000000]),
+            Transaction::read(0x69, vec![0, 0, 0]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let adc1 = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+        let adc2 = MCP3425::oneshot(bus.clone(), 0x69, NoopDelay);
+
+        let mut group = Mcp342xGroup::new([adc1, adc2]);
+        let err = group.measure_all().unwrap_err();
+        assert_eq!(err.failure_count(), 1);
+        let (index, error) = err.failures().next().unwrap();
+        assert_eq!(index, 0);
+        assert!(matches!(error, MeasureError::VoltageTooHigh), "{:?}", error);
+        assert!(err.results[1].is_ok());
+
+        bus.clone().done();
+    }
+
+    /// Every round converts one channel per device.
+    #[test]
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    fn test_channel_expander() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00000000]),
+            Transaction::write(0x69, vec![0b00000000]),
+            Transaction::write(0x00, vec![0b00001000]),
+            Transaction::read(0x68, vec![0, 1, 0]),
+            Transaction::read(0x69, vec![0, 3, 0]),
+            Transaction::write(0x68, vec![0b00100001]),
+            Transaction::write(0x69, vec![0b00100000]),
+            Transaction::write(0x00, vec![0b00001000]),
+            Transaction::read(0x68, vec![0, 2, 0b00100001]),
+            Transaction::read(0x69, vec![0x07, 0xFF, 0b00100000]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let adc1 = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+        let adc2 = MCP3425::oneshot(bus.clone(), 0x69, NoopDelay);
+
+        let mut expander = ChannelExpander::<_, _, 2, 4>::new([adc1, adc2]);
+        expander.set_config(1, Config::default().with_gain(Gain::Gain2));
+        assert_eq!(expander.config(1).channel, Channel::Channel2);
+        let [ch0, ch1, ch2, ch3] = expander.measure_all().unwrap();
+        assert!(ch0.is_ok() && ch1.is_ok() && ch2.is_ok());
+        assert!(matches!(ch3, Err(ReadError::VoltageTooHigh)), "{:?}", ch3);
+
+        expander.release();
+        bus.clone().done();
+    }
+
+    /// A device that is still converting after the typical conversion time is
+    /// polled until it is ready.
+    #[test]
+    fn test_channel_expander_polls_slow_device() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00000000]),
+            Transaction::write(0x69, vec![0b00000000]),
+            Transaction::write(0x00, vec![0b00001000]),
+            Transaction::read(0x68, vec![0, 1, 0]),
+            Transaction::read(0x69, vec![0, 0, 0b10000000]),
+            Transaction::read(0x69, vec![0, 0, 0b10000000]),
+            Transaction::read(0x69, vec![0, 3, 0]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let adc1 = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+        let adc2 = MCP3425::oneshot(bus.clone(), 0x69, NoopDelay);
+
+        let mut expander = ChannelExpander::<_, _, 2, 2>::new([adc1, adc2]);
+        let [ch0, ch1] = expander.measure_all().unwrap();
+        assert!(ch0.is_ok() && ch1.is_ok(), "{:?} {:?}", ch0, ch1);
+
+        expander.release();
+        bus.clone().done();
+    }
+}
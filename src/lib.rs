@@ -16,16 +16,46 @@
 //! - `dual_channel` for MCP3426/7
 //! - `quad_channel` for MCP3428
 //!
+//! The 18-bit variants
+//! [MCP3421](https://ww1.microchip.com/downloads/en/DeviceDoc/22003e.pdf) and
+//! [MCP3422/3/4](https://ww1.microchip.com/downloads/en/DeviceDoc/22088c.pdf)
+//! share the same register map, but support an additional 18-bit resolution.
+//! Enable the `eighteen_bit` Cargo feature to use it (and `dual_channel` or
+//! `quad_channel` for MCP3422/3 and MCP3424 respectively).
+//!
 //! ## Cargo Features
 //!
 //! The following feature flags exists:
 //!
 //! - `dual_channel` for dual-channel support (MCP3426/7/8)
 //! - `quad_channel` for dual-channel support (MCP3428)
+//! - `eighteen_bit` for the 18-bit resolution of the MCP3421/2/3/4
 //! - `measurements`: Use the
 //!   [measurements](https://github.com/thejpster/rust-measurements) crate
 //!   to represent voltages instead of the custom
 //!   [`Voltage`](https://docs.rs/mcp3425/*/mcp3425/struct.Voltage.html) wrapper
+//! - `async`: Async driver based on the `embedded-hal-async` traits, see the
+//!   [`asynch`](asynch/index.html) module
+//! - `embassy`: Wait for conversions in the async driver with
+//!   `embassy_time::Timer` (implies `async`)
+//! - `shared_bus`: Constructors for sharing the I²C bus with other drivers
+//!   through `embedded-hal-bus`, see the
+//!   [`shared_bus`](shared_bus/index.html) module
+//! - `alloc`: Measurement methods returning a `Vec`, e.g.
+//!   [`measure_vec`](struct.MCP3425.html#method.measure_vec)
+//! - `serde`: `Serialize` and `Deserialize` implementations for the
+//!   [`Calibration`](calibration/struct.Calibration.html)
+//! - `uom`: Conversion of voltages to `uom::si::f32::ElectricPotential`, see
+//!   [`electric_potential`](fn.electric_potential.html)
+//! - `fixed`: Voltages in volts as fixed-point numbers without floating
+//!   point math, see
+//!   [`Voltage::as_volts_fixed`](struct.Voltage.html#method.as_volts_fixed)
+//!   (not with `measurements`). This feature is exempt from the MSRV of
+//!   1.75, since current releases of `fixed` and its dependencies need a
+//!   newer Rust.
+//! - `ufmt`: `uDisplay` implementation for
+//!   [`Voltage`](struct.Voltage.html), like its `Display` implementation
+//!   (not with `measurements`)
 //!
 //! ## Usage
 //!
@@ -76,6 +106,9 @@
 //! # }
 //! ```
 //!
+//! Measured voltages are input-referred, i.e. the PGA gain has already been
+//! divided out.
+//!
 //! Note: If you enable the `dual_channel` or `quad_channel` Cargo features,
 //! you can also use the method `.with_channel(...)` on the `Config` struct (if
 //! your model supports multiple input channels).
@@ -89,7 +122,7 @@
 //! ```no_run
 //! # extern crate linux_embedded_hal;
 //! # use linux_embedded_hal::{Delay, I2cdev};
-//! # use mcp3425::{MCP3425, Config, Resolution, Gain, Error};
+//! # use mcp3425::{MCP3425, Config, Resolution, Gain, MeasureError};
 //! # fn main() {
 //! # use mcp3425::Channel;
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -98,11 +131,11 @@
 //! let config = Config::default();
 //! match adc.measure(&config) {
 //!     Ok(voltage) => println!("ADC measured {} mV", voltage.as_millivolts()),
-//!     Err(Error::I2c(e)) => println!("An I2C error happened: {}", e),
-//!     Err(Error::VoltageTooHigh) => println!("Voltage is too high to measure"),
-//!     Err(Error::VoltageTooLow) => println!("Voltage is too low to measure"),
-//!     Err(Error::NotReady) => println!("Measurement not yet ready. This is a driver bug."),
-//!     Err(Error::NotInitialized) => unreachable!(),
+//!     Err(MeasureError::I2c(e)) => println!("An I2C error happened: {}", e),
+//!     Err(MeasureError::VoltageTooHigh) => println!("Voltage is too high to measure"),
+//!     Err(MeasureError::VoltageTooLow) => println!("Voltage is too low to measure"),
+//!     Err(MeasureError::NotReady) => println!("Measurement not yet ready. This is a driver bug."),
+//!     Err(MeasureError::ChannelNotSupported) => println!("The device does not have this channel"),
 //! }
 //! # }
 //! ```
@@ -117,7 +150,7 @@
 //! ```no_run
 //! # extern crate linux_embedded_hal;
 //! # use linux_embedded_hal::{Delay, I2cdev};
-//! # use mcp3425::{MCP3425, Config, Resolution, Gain, Error};
+//! # use mcp3425::{MCP3425, Config, Resolution, Gain, ReadError};
 //! # fn main() {
 //! # use mcp3425::Channel;
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -127,11 +160,11 @@
 //! adc.set_config(&config).unwrap();
 //! match adc.read_measurement() {
 //!     Ok(voltage) => println!("ADC measured {} mV", voltage.as_millivolts()),
-//!     Err(Error::I2c(e)) => println!("An I2C error happened: {}", e),
-//!     Err(Error::VoltageTooHigh) => println!("Voltage is too high to measure"),
-//!     Err(Error::VoltageTooLow) => println!("Voltage is too low to measure"),
-//!     Err(Error::NotReady) => println!("Measurement not yet ready. Polling too fast?"),
-//!     Err(Error::NotInitialized) => println!("You forgot to call .set_config"),
+//!     Err(ReadError::I2c(e)) => println!("An I2C error happened: {}", e),
+//!     Err(ReadError::VoltageTooHigh) => println!("Voltage is too high to measure"),
+//!     Err(ReadError::VoltageTooLow) => println!("Voltage is too low to measure"),
+//!     Err(ReadError::NotReady) => println!("Measurement not yet ready. Polling too fast?"),
+//!     Err(ReadError::NotInitialized) => println!("You forgot to call .set_config"),
 //! }
 //! # }
 //! ```
@@ -142,15 +175,54 @@
 #[macro_use]
 extern crate bitflags;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+use core::array;
+use core::cmp;
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+#[cfg(not(feature = "measurements"))]
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use core::time::Duration;
+
 use byteorder::{BigEndian, ByteOrder};
 use embedded_hal::delay::DelayNs;
-use embedded_hal::i2c::I2c;
 
 #[cfg(feature = "measurements")]
 extern crate measurements;
 #[cfg(feature = "measurements")]
 use measurements::voltage::Voltage;
 
+use builder::ConfigBuilder;
+use calibration::{Calibration, ChannelCalibrations, Tempco, Temperature, TemperatureFn};
+use model::{AnyModel, ChannelMarker, HasChannel, Model};
+use output::FromAdcCode;
+use transport::Transport;
+
+pub mod address;
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod builder;
+pub mod calibration;
+pub mod discovery;
+pub mod filter;
+pub mod group;
+pub mod model;
+pub mod monitor;
+pub mod mux;
+pub mod output;
+pub mod oversample;
+pub mod sampler;
+pub mod sequence;
+#[cfg(feature = "shared_bus")]
+pub mod shared_bus;
+pub mod standby;
+pub mod stats;
+pub mod transport;
+
 /// All possible errors in this crate
 #[derive(Debug)]
 pub enum Error<E> {
@@ -172,8 +244,154 @@ pub enum Error<E> {
     /// <https://github.com/dbrgn/mcp3425-rs/issues/>!
     ///
     NotReady,
+    /// The configured channel does not exist on the device model selected
+    /// with [`with_model`](struct.MCP3425.html#method.with_model).
+    ChannelNotSupported,
+    /// The configuration register read from the device selects a resolution
+    /// or channel that is not enabled with Cargo features.
+    InvalidConfig(InvalidConfig),
+    /// The configuration register read back from the device does not match
+    /// the written configuration.
+    ///
+    /// Both values are configuration register bytes without the RDY bit, see
+    /// [`Config::try_from_byte`](struct.Config.html#method.try_from_byte).
+    ConfigMismatch {
+        /// The configuration that was written.
+        expected: u8,
+        /// The configuration that was read back.
+        actual: u8,
+    },
+    /// The device did not finish a conversion within the time allowed by
+    /// [`set_ready_timeout`](struct.MCP3425.html#method.set_ready_timeout).
+    ///
+    /// This happens if the device is wedged or was disconnected while
+    /// waiting.
+    Timeout,
+}
+
+/// Errors of one-shot measurements, like
+/// [`measure`](struct.MCP3425.html#method.measure).
+///
+/// This is the subset of [`Error`](enum.Error.html) that can actually occur
+/// when measuring, and converts into it with `?`.
+#[derive(Debug)]
+pub enum MeasureError<E> {
+    /// I2C bus error
+    I2c(E),
+    /// Voltage is too high to be measured.
+    VoltageTooHigh,
+    /// Voltage is too low to be measured.
+    VoltageTooLow,
+    /// The conversion did not finish in time. This is probably a timing bug
+    /// that should be reported to
+    /// <https://github.com/dbrgn/mcp3425-rs/issues/>!
+    NotReady,
+    /// The configured channel does not exist on the device model selected
+    /// with [`with_model`](struct.MCP3425.html#method.with_model).
+    ChannelNotSupported,
+}
+
+impl<E> From<MeasureError<E>> for Error<E> {
+    fn from(error: MeasureError<E>) -> Self {
+        match error {
+            MeasureError::I2c(e) => Error::I2c(e),
+            MeasureError::VoltageTooHigh => Error::VoltageTooHigh,
+            MeasureError::VoltageTooLow => Error::VoltageTooLow,
+            MeasureError::NotReady => Error::NotReady,
+            MeasureError::ChannelNotSupported => Error::ChannelNotSupported,
+        }
+    }
+}
+
+/// Errors of reading measurements, like
+/// [`read_measurement`](struct.MCP3425.html#method.read_measurement).
+///
+/// This is the subset of [`Error`](enum.Error.html) that can actually occur
+/// when reading, and converts into it with `?`.
+#[derive(Debug)]
+pub enum ReadError<E> {
+    /// I2C bus error
+    I2c(E),
+    /// Voltage is too high to be measured.
+    VoltageTooHigh,
+    /// Voltage is too low to be measured.
+    VoltageTooLow,
+    /// The configuration has not been written to the device before.
+    NotInitialized,
+    /// The result was already read before.
+    ///
+    /// In continuous mode, this can happen if you poll faster than the sample
+    /// rate. See datasheet section 5.1.1 for more details.
+    NotReady,
+}
+
+impl<E> From<ReadError<E>> for Error<E> {
+    fn from(error: ReadError<E>) -> Self {
+        match error {
+            ReadError::I2c(e) => Error::I2c(e),
+            ReadError::VoltageTooHigh => Error::VoltageTooHigh,
+            ReadError::VoltageTooLow => Error::VoltageTooLow,
+            ReadError::NotInitialized => Error::NotInitialized,
+            ReadError::NotReady => Error::NotReady,
+        }
+    }
+}
+
+/// The error types returned by the driver.
+///
+/// The internal helpers are generic over this trait, so that every public
+/// method can return its own error type without converting between them.
+trait DriverError: Sized {
+    /// The I²C bus error type.
+    type Bus;
+
+    /// Wrap an I²C bus error.
+    fn i2c(error: Self::Bus) -> Self;
+
+    /// Return the error for a saturated output code: `VoltageTooHigh` if
+    /// `high` is set, `VoltageTooLow` otherwise.
+    fn saturated(high: bool) -> Self;
+
+    /// Return whether the error may go away when retrying the operation.
+    fn is_transient(&self) -> bool;
+
+    /// Return whether the error is caused by a saturated output code.
+    fn is_saturated(&self) -> bool;
+}
+
+/// Implement `DriverError` for an error enum with the common variants.
+macro_rules! impl_driver_error {
+    ($($error:ident),*) => {
+        $(
+            impl<E> DriverError for $error<E> {
+                type Bus = E;
+
+                fn i2c(error: E) -> Self {
+                    $error::I2c(error)
+                }
+
+                fn saturated(high: bool) -> Self {
+                    if high {
+                        $error::VoltageTooHigh
+                    } else {
+                        $error::VoltageTooLow
+                    }
+                }
+
+                fn is_transient(&self) -> bool {
+                    matches!(self, $error::I2c(_) | $error::NotReady)
+                }
+
+                fn is_saturated(&self) -> bool {
+                    matches!(self, $error::VoltageTooHigh | $error::VoltageTooLow)
+                }
+            }
+        )*
+    };
 }
 
+impl_driver_error!(Error, MeasureError, ReadError);
+
 bitflags! {
     struct ConfigRegister: u8 {
         const NOT_READY = 0b10000000;
@@ -192,7 +410,36 @@ impl ConfigRegister {
 }
 
 /// ADC reference voltage: +-2048mV
-const REF_MILLIVOLTS: i16 = 2048;
+const REF_MILLIVOLTS: i32 = 2048;
+
+/// Size of one LSB at 12 bits resolution in microvolts: 2 * 2048mV / 2^12
+const LSB_12BIT_MICROVOLTS: i32 = REF_MILLIVOLTS * 2 * 1000 / (1 << 12);
+
+/// Safety margin added to the conversion time when waiting for a one-shot
+/// measurement: 2ms
+const CONVERSION_MARGIN_US: u32 = 2_000;
+
+/// Interval for polling the RDY bit during the safety margin of a one-shot
+/// measurement: 250µs
+const READY_POLL_INTERVAL_US: u32 = 250;
+
+/// Interval for polling the RDY bit in fast one-shot measurements: 100µs
+const FAST_POLL_INTERVAL_US: u32 = 100;
+
+/// Maximum number of times a continuous mode result is polled for freshness
+/// in batch measurements, in steps of 1/16 conversion time.
+const MAX_PACING_POLLS: u8 = 32;
+
+/// Maximum number of samples discarded at each end by `measure_trimmed`,
+/// which keeps the discarded samples on the stack.
+const MAX_TRIMMED_DISCARD: u16 = 16;
+
+/// The I²C general call address.
+const GENERAL_CALL_ADDRESS: u8 = 0x00;
+
+/// General call command that starts a conversion on all devices on the bus.
+/// See datasheet section 5.4.
+const GENERAL_CALL_CONVERSION: u8 = 0b00001000;
 
 /// The two conversion mode structs implement this trait.
 ///
@@ -203,6 +450,7 @@ pub trait ConversionMode {
 }
 
 /// Use the MCP3425 in One-Shot mode.
+#[derive(Debug)]
 pub struct OneShotMode;
 
 impl ConversionMode for OneShotMode {
@@ -212,6 +460,7 @@ impl ConversionMode for OneShotMode {
 }
 
 /// Use the MCP3425 in Continuous Conversion mode.
+#[derive(Debug)]
 pub struct ContinuousMode;
 
 impl ConversionMode for ContinuousMode {
@@ -220,8 +469,105 @@ impl ConversionMode for ContinuousMode {
     }
 }
 
+/// Use the MCP3425 in Continuous Conversion mode, with a configuration that
+/// has already been written to the device.
+///
+/// A driver in this mode is created with
+/// [`configure`](struct.MCP3425.html#method.configure). Since the
+/// configuration is known to be written, reading measurements never returns
+/// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized), so
+/// functions that take a driver in this mode don't need to handle that case:
+///
+/// ```
+/// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+/// use embedded_hal::delay::DelayNs;
+/// use mcp3425::{transport::Transport, Config, ConfiguredContinuousMode, MCP3425};
+///
+/// fn log_voltage<I2C: Transport, D: DelayNs>(
+///     adc: &mut MCP3425<I2C, D, ConfiguredContinuousMode>,
+/// ) {
+///     if let Ok(voltage) = adc.read_measurement() {
+///         // ...
+/// #       let _ = voltage;
+///     }
+/// }
+///
+/// # let bus = Mock::new(&[
+/// #     Transaction::write(0x68, vec![0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 1, 0b00010000]),
+/// # ]);
+/// # let i2c = bus.clone();
+/// let adc = MCP3425::continuous(i2c, 0x68, NoopDelay);
+/// let mut adc = adc.configure(&Config::default()).map_err(|(e, _)| e).unwrap();
+/// log_voltage(&mut adc);
+/// # bus.clone().done();
+/// ```
+#[derive(Debug)]
+pub struct ConfiguredContinuousMode;
+
+impl ConversionMode for ConfiguredContinuousMode {
+    fn bits(&self) -> u8 {
+        ContinuousMode.bits()
+    }
+}
+
+/// Implemented by the conversion modes that convert continuously.
+///
+/// This allows methods like
+/// [`read_measurement`](struct.MCP3425.html#method.read_measurement) to be
+/// used in both [`ContinuousMode`](struct.ContinuousMode.html) and
+/// [`ConfiguredContinuousMode`](struct.ConfiguredContinuousMode.html).
+pub trait ContinuousConversion: ConversionMode {}
+
+impl ContinuousConversion for ContinuousMode {}
+
+impl ContinuousConversion for ConfiguredContinuousMode {}
+
+/// Common interface of the one-shot and continuous mode drivers.
+///
+/// This allows code built on top of this crate to be generic over the
+/// conversion mode:
+///
+/// ```
+/// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+/// use mcp3425::{AdcReader, Config, MCP3425};
+///
+/// fn sample<A: AdcReader>(adc: &mut A) -> Result<(), A::Error> {
+///     let voltage = adc.read(&Config::default())?;
+///     // ...
+/// #   let _ = voltage;
+///     Ok(())
+/// }
+///
+/// # let bus = Mock::new(&[
+/// #     Transaction::write(0x68, vec![0b10000000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0]),
+/// #     Transaction::write(0x69, vec![0b00010000]),
+/// #     Transaction::read(0x69, vec![0, 0, 0b00010000]),
+/// #     Transaction::read(0x69, vec![0, 0, 0b10010000]),
+/// # ]);
+/// let mut oneshot = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+/// let mut continuous = MCP3425::continuous(bus.clone(), 0x69, NoopDelay);
+/// sample(&mut oneshot).unwrap();
+/// sample(&mut continuous).unwrap();
+/// # bus.clone().done();
+/// ```
+pub trait AdcReader {
+    /// Error type of the driver.
+    type Error;
+
+    /// Return a measurement with the specified configuration.
+    ///
+    /// In one-shot mode, this triggers a new measurement. In continuous
+    /// mode, the configuration is only written if it differs from the
+    /// current one, then the latest result is read.
+    fn read(&mut self, config: &Config) -> Result<Voltage, Self::Error>;
+}
+
 /// Conversion bit resolution and sample rate
 ///
+/// * 3.75 SPS -> 18 bits (MCP3421/2/3/4 only)
 /// * 15 SPS -> 16 bits
 /// * 60 SPS -> 14 bits
 /// * 240 SPS -> 12 bits
@@ -229,8 +575,15 @@ impl ConversionMode for ContinuousMode {
 /// Defaults to 12 bits / 240 SPS (`Bits12Sps240`),
 /// matching the power-on defaults of the device.
 #[allow(dead_code)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Resolution {
+    /// 18 bits / 3.75 SPS. This allows you to measure voltage in 15.625 µV
+    /// steps.
+    ///
+    /// Note: Only supported by MCP3421/2/3/4, and if the `eighteen_bit` cargo
+    /// feature is enabled.
+    #[cfg(any(feature = "eighteen_bit", doc))]
+    Bits18Sps3_75 = 0b00001100,
     /// 16 bits / 15 SPS. This allows you to measure voltage in 62.5 µV steps.
     Bits16Sps15 = 0b00001000,
     /// 14 bits / 60 SPS. This allows you to measure voltage in 250 µV steps.
@@ -241,22 +594,38 @@ pub enum Resolution {
 
 impl Resolution {
     /// Return the bitmask for this sample rate.
-    pub fn bits(&self) -> u8 {
+    pub const fn bits(&self) -> u8 {
         *self as u8
     }
 
     /// Return the number of bits of accuracy this sample rate gives you.
-    pub fn res_bits(&self) -> u8 {
+    pub const fn res_bits(&self) -> u8 {
         match *self {
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 18,
             Resolution::Bits16Sps15 => 16,
             Resolution::Bits14Sps60 => 14,
             Resolution::Bits12Sps240 => 12,
         }
     }
 
+    /// Decode the sample rate bits of the configuration register.
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0b00001100 {
+            #[cfg(feature = "eighteen_bit")]
+            0b00001100 => Some(Resolution::Bits18Sps3_75),
+            0b00001000 => Some(Resolution::Bits16Sps15),
+            0b00000100 => Some(Resolution::Bits14Sps60),
+            0b00000000 => Some(Resolution::Bits12Sps240),
+            _ => None,
+        }
+    }
+
     /// Return the maximum output code.
-    pub fn max(&self) -> i16 {
+    pub fn max(&self) -> i32 {
         match *self {
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 131071,
             Resolution::Bits16Sps15 => 32767,
             Resolution::Bits14Sps60 => 8191,
             Resolution::Bits12Sps240 => 2047,
@@ -264,13 +633,115 @@ impl Resolution {
     }
 
     /// Return the minimum output code.
-    pub fn min(&self) -> i16 {
+    pub fn min(&self) -> i32 {
         match *self {
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => -131072,
             Resolution::Bits16Sps15 => -32768,
             Resolution::Bits14Sps60 => -8192,
             Resolution::Bits12Sps240 => -2048,
         }
     }
+
+    /// Return the time in microseconds a single conversion takes.
+    ///
+    /// Values found by experimentation, these do not seem to be specified
+    /// in the datasheet. To know how long to wait after triggering a
+    /// measurement, use
+    /// [`Config::time_until_ready`](struct.Config.html#method.time_until_ready)
+    /// instead, which includes a safety margin.
+    pub const fn conversion_time_us(&self) -> u32 {
+        match *self {
+            // Not measured, nominal conversion time at 3.75 SPS
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 267_000,
+            Resolution::Bits12Sps240 => 4_000,
+            Resolution::Bits14Sps60 => 15_000,
+            Resolution::Bits16Sps15 => 57_000,
+        }
+    }
+
+    /// Return the highest resolution whose one-shot measurement fits into
+    /// `max_latency`, or `None` if not even 12 bits fit.
+    ///
+    /// The latency of a measurement is its
+    /// [`time_until_ready`](struct.Config.html#method.time_until_ready),
+    /// i.e. the conversion time plus a safety margin. This allows adapting
+    /// the resolution to a varying time budget:
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use mcp3425::Resolution;
+    ///
+    /// let resolution = Resolution::best_for(Duration::from_millis(20));
+    /// assert_eq!(resolution, Some(Resolution::Bits14Sps60));
+    /// assert_eq!(Resolution::best_for(Duration::from_millis(5)), None);
+    /// ```
+    pub fn best_for(max_latency: Duration) -> Option<Self> {
+        [
+            #[cfg(feature = "eighteen_bit")]
+            Resolution::Bits18Sps3_75,
+            Resolution::Bits16Sps15,
+            Resolution::Bits14Sps60,
+            Resolution::Bits12Sps240,
+        ]
+        .into_iter()
+        .find(|resolution| {
+            let latency_us = resolution.conversion_time_us() + CONVERSION_MARGIN_US;
+            Duration::from_micros(u64::from(latency_us)) <= max_latency
+        })
+    }
+
+    /// Return the nominal number of samples per second.
+    pub fn samples_per_second(&self) -> f32 {
+        match *self {
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 3.75,
+            Resolution::Bits16Sps15 => 15.0,
+            Resolution::Bits14Sps60 => 60.0,
+            Resolution::Bits12Sps240 => 240.0,
+        }
+    }
+
+    /// Return the size of one LSB at gain 1 in microvolts.
+    ///
+    /// See [`Config::lsb_microvolts`](struct.Config.html#method.lsb_microvolts)
+    /// for the size taking the gain into account.
+    pub fn step_microvolts(&self) -> f32 {
+        LSB_12BIT_MICROVOLTS as f32 / (1u32 << (self.res_bits() - 12)) as f32
+    }
+
+    /// Return the worst-case conversion time.
+    ///
+    /// This is the inverse of the minimum data rate specified in the
+    /// datasheet (electrical characteristics), e.g. 176 SPS at 12 bits. It is
+    /// much longer than the typical
+    /// [`conversion_time_us`](#method.conversion_time_us).
+    pub const fn max_conversion_time(&self) -> Duration {
+        let micros = match *self {
+            // 2.75 SPS
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 363_637,
+            // 11 SPS
+            Resolution::Bits16Sps15 => 90_910,
+            // 44 SPS
+            Resolution::Bits14Sps60 => 22_728,
+            // 176 SPS
+            Resolution::Bits12Sps240 => 5_682,
+        };
+        Duration::from_micros(micros)
+    }
+
+    /// Return the number of bytes to read from the device: The output code
+    /// (3 bytes at 18 bits, 2 bytes otherwise), followed by the configuration
+    /// register.
+    fn read_len(&self) -> usize {
+        match *self {
+            #[cfg(any(feature = "eighteen_bit", doc))]
+            Resolution::Bits18Sps3_75 => 4,
+            _ => 3,
+        }
+    }
 }
 
 impl Default for Resolution {
@@ -280,12 +751,36 @@ impl Default for Resolution {
     }
 }
 
+impl PartialOrd for Resolution {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Resolutions are ordered by their number of bits, so the highest
+/// resolution is the greatest.
+///
+/// Note that `Resolution::max()` and `Resolution::min()` return output codes,
+/// use `Ord::max`/`Ord::min` to pick the higher or lower resolution:
+///
+/// ```
+/// # use mcp3425::Resolution;
+/// assert!(Resolution::Bits16Sps15 > Resolution::Bits12Sps240);
+/// let higher = Ord::max(Resolution::Bits14Sps60, Resolution::Bits16Sps15);
+/// assert_eq!(higher, Resolution::Bits16Sps15);
+/// ```
+impl Ord for Resolution {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.res_bits().cmp(&other.res_bits())
+    }
+}
+
 /// Programmable gain amplifier (PGA)
 ///
 /// Defaults to no amplification (`Gain1`),
 /// matching the power-on defaults of the device.
 #[allow(dead_code)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Gain {
     /// Amplification factor 1.
     Gain1 = 0b00000000,
@@ -299,9 +794,56 @@ pub enum Gain {
 
 impl Gain {
     /// Return the bitmask for this gain configuration.
-    pub fn bits(&self) -> u8 {
+    pub const fn bits(&self) -> u8 {
         *self as u8
     }
+
+    /// Return the amplification factor.
+    pub const fn factor(&self) -> u8 {
+        1 << self.bits()
+    }
+
+    /// Decode the gain bits of the configuration register.
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b00000011 {
+            0b00000000 => Gain::Gain1,
+            0b00000001 => Gain::Gain2,
+            0b00000010 => Gain::Gain4,
+            _ => Gain::Gain8,
+        }
+    }
+}
+
+impl TryFrom<u8> for Gain {
+    type Error = InvalidGain;
+
+    /// Return the gain with the amplification factor `factor` (1, 2, 4 or 8).
+    ///
+    /// ```
+    /// # use mcp3425::Gain;
+    /// assert_eq!(Gain::try_from(4), Ok(Gain::Gain4));
+    /// assert!(Gain::try_from(3).is_err());
+    /// ```
+    fn try_from(factor: u8) -> Result<Self, Self::Error> {
+        match factor {
+            1 => Ok(Gain::Gain1),
+            2 => Ok(Gain::Gain2),
+            4 => Ok(Gain::Gain4),
+            8 => Ok(Gain::Gain8),
+            _ => Err(InvalidGain(factor)),
+        }
+    }
+}
+
+/// Error returned when converting an amplification factor other than 1, 2, 4
+/// or 8 to a [`Gain`](enum.Gain.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidGain(pub u8);
+
+impl fmt::Display for InvalidGain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid gain factor: {}", self.0)
+    }
 }
 
 impl Default for Gain {
@@ -314,7 +856,7 @@ impl Default for Gain {
 /// Selected ADC channel
 ///
 /// Defaults to channel 1.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Channel {
     /// First channel (Default)
     Channel1 = 0b0000_0000,
@@ -338,6 +880,7 @@ pub enum Channel {
     Channel4 = 0b0110_0000,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Channel {
     fn default() -> Self {
         Self::Channel1
@@ -346,14 +889,164 @@ impl Default for Channel {
 
 impl Channel {
     /// Return the bitmask for this channel configuration.
-    pub fn bits(&self) -> u8 {
+    pub const fn bits(&self) -> u8 {
         *self as u8
     }
+
+    /// Return the zero-based channel number.
+    pub const fn index(&self) -> u8 {
+        self.bits() >> 5
+    }
+
+    /// Return all channels that are enabled with Cargo features, in
+    /// ascending order.
+    ///
+    /// ```
+    /// # use mcp3425::{Channel, Config, Gain, Resolution};
+    /// for channel in Channel::all() {
+    ///     let config = Config::new(Resolution::Bits16Sps15, Gain::Gain1, *channel);
+    ///     // ...
+    /// #   let _ = config;
+    /// }
+    /// ```
+    pub const fn all() -> &'static [Channel] {
+        &[
+            Channel::Channel1,
+            #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+            Channel::Channel2,
+            #[cfg(feature = "quad_channel")]
+            Channel::Channel3,
+            #[cfg(feature = "quad_channel")]
+            Channel::Channel4,
+        ]
+    }
+
+    /// Decode the channel selection bits of the configuration register.
+    fn from_bits(bits: u8) -> Option<Self> {
+        Channel::try_from((bits & 0b0110_0000) >> 5).ok()
+    }
+}
+
+impl TryFrom<u8> for Channel {
+    type Error = InvalidChannel;
+
+    /// Return the channel with the zero-based channel number `index`.
+    ///
+    /// Fails if the index is out of range, or if the channel is not enabled
+    /// with Cargo features.
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        Channel::all()
+            .get(usize::from(index))
+            .copied()
+            .ok_or(InvalidChannel(index))
+    }
+}
+
+/// Differential input pair, named after its `CHx+`/`CHx-` pins.
+///
+/// Every channel of the MCP342x is a differential input, so this is only a
+/// naming layer over [`Channel`](enum.Channel.html) for wiring differential
+/// sensors like shunts and bridges:
+///
+/// ```
+/// # use mcp3425::{Channel, DifferentialInput};
+/// let shunt = DifferentialInput::Ch1Diff;
+/// assert_eq!(Channel::from(shunt), Channel::Channel1);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DifferentialInput {
+    /// `CH1+` and `CH1-` (Default)
+    #[default]
+    Ch1Diff,
+    /// `CH2+` and `CH2-`
+    ///
+    /// Note: Only supported by MCP3426/7/8, and if the `dual_channel` or
+    /// `quad_channel` cargo feature is enabled.
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
+    Ch2Diff,
+    /// `CH3+` and `CH3-`
+    ///
+    /// Note: Only supported by MCP3428, and if the `quad_channel` cargo
+    /// feature is enabled.
+    #[cfg(any(feature = "quad_channel", doc))]
+    Ch3Diff,
+    /// `CH4+` and `CH4-`
+    ///
+    /// Note: Only supported by MCP3428, and if the `quad_channel` cargo
+    /// feature is enabled.
+    #[cfg(any(feature = "quad_channel", doc))]
+    Ch4Diff,
+}
+
+impl DifferentialInput {
+    /// Return the channel that selects this input pair.
+    pub const fn channel(&self) -> Channel {
+        match self {
+            DifferentialInput::Ch1Diff => Channel::Channel1,
+            #[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
+            DifferentialInput::Ch2Diff => Channel::Channel2,
+            #[cfg(any(feature = "quad_channel", doc))]
+            DifferentialInput::Ch3Diff => Channel::Channel3,
+            #[cfg(any(feature = "quad_channel", doc))]
+            DifferentialInput::Ch4Diff => Channel::Channel4,
+        }
+    }
+}
+
+impl From<DifferentialInput> for Channel {
+    fn from(input: DifferentialInput) -> Self {
+        input.channel()
+    }
+}
+
+impl From<Channel> for DifferentialInput {
+    fn from(channel: Channel) -> Self {
+        match channel {
+            Channel::Channel1 => DifferentialInput::Ch1Diff,
+            #[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
+            Channel::Channel2 => DifferentialInput::Ch2Diff,
+            #[cfg(any(feature = "quad_channel", doc))]
+            Channel::Channel3 => DifferentialInput::Ch3Diff,
+            #[cfg(any(feature = "quad_channel", doc))]
+            Channel::Channel4 => DifferentialInput::Ch4Diff,
+        }
+    }
+}
+
+/// Error returned when converting an index to a [`Channel`](enum.Channel.html)
+/// that does not exist or is not enabled with Cargo features.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidChannel(pub u8);
+
+impl fmt::Display for InvalidChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid or disabled channel index: {}", self.0)
+    }
+}
+
+/// How measurements at the limits of the input range are reported.
+///
+/// When the input voltage is outside of the measurable range, the device
+/// returns the largest (or smallest) output code.
+///
+/// Defaults to `Error`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SaturationPolicy {
+    /// Return [`Error::VoltageTooHigh`](enum.Error.html#variant.VoltageTooHigh)
+    /// or [`Error::VoltageTooLow`](enum.Error.html#variant.VoltageTooLow).
+    #[default]
+    Error,
+    /// Return the full-scale voltage, i.e. `±2048mV` divided by the gain.
+    Clamp,
+    /// Return the voltage of the saturated output code, which is one LSB
+    /// below the positive full-scale voltage.
+    Raw,
 }
 
 /// Device configuration: Resolution, gain and input channel.
 ///
-/// To instantiate this struct, use the `Default` implementation:
+/// To instantiate this struct, use [`Config::new`](#method.new) or the
+/// `Default` implementation:
 ///
 /// ```
 /// # use mcp3425::{Config, Resolution, Gain};
@@ -367,12 +1060,13 @@ impl Channel {
 /// - Resolution: Bits12Sps240
 /// - Gain: Gain1
 /// - Channel: Channel1
+/// - Saturation policy: Error
 ///
 /// Note: Creating and changing this instance does not have an immediate effect
 /// on the device. It is only written when a measurement is triggered, or when
 /// writing config explicitly with
 /// [`set_config`](struct.MCP3425.html#method.set_config).
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Config {
     /// Conversion bit resolution and sample rate.
     pub resolution: Resolution,
@@ -380,450 +1074,4669 @@ pub struct Config {
     pub gain: Gain,
     /// Selected input channel
     pub channel: Channel,
+    /// How saturated measurements are reported. This is not written to the
+    /// device.
+    pub saturation: SaturationPolicy,
+    /// Correction applied to every voltage measured with this configuration.
+    /// This is not written to the device.
+    pub calibration: Option<Calibration>,
 }
 
 impl Config {
+    /// Create a new configuration.
+    ///
+    /// This is a `const fn`, so configurations can be computed at compile
+    /// time:
+    ///
+    /// ```
+    /// # use mcp3425::{Channel, Config, Gain, Resolution};
+    /// static CONFIG: Config = Config::new(Resolution::Bits14Sps60, Gain::Gain2, Channel::Channel1);
+    /// ```
+    pub const fn new(resolution: Resolution, gain: Gain, channel: Channel) -> Self {
+        Config {
+            resolution,
+            gain,
+            channel,
+            saturation: SaturationPolicy::Error,
+            calibration: None,
+        }
+    }
+
+    /// Preset for the fastest conversions: 12 bits / 240 SPS, gain 1.
+    pub const fn fast() -> Self {
+        Config::new(Resolution::Bits12Sps240, Gain::Gain1, Channel::Channel1)
+    }
+
+    /// Preset for the highest resolution supported by all models: 16 bits /
+    /// 15 SPS, gain 1.
+    pub const fn high_resolution() -> Self {
+        Config::new(Resolution::Bits16Sps15, Gain::Gain1, Channel::Channel1)
+    }
+
+    /// Return a [`ConfigBuilder`](builder/struct.ConfigBuilder.html) that
+    /// validates the configuration when building it.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
     /// Create a new configuration where the resolution has been replaced
     /// with the specified value.
-    pub fn with_resolution(&self, resolution: Resolution) -> Self {
+    pub const fn with_resolution(&self, resolution: Resolution) -> Self {
         Config {
             resolution,
-            gain: self.gain,
-            channel: self.channel,
+            ..*self
         }
     }
 
     /// Create a new configuration where the gain has been replaced
     /// with the specified value.
-    pub fn with_gain(&self, gain: Gain) -> Self {
-        Config {
-            resolution: self.resolution,
-            gain,
-            channel: self.channel,
-        }
+    pub const fn with_gain(&self, gain: Gain) -> Self {
+        Config { gain, ..*self }
     }
 
     /// Create a new configuration where the channel has been replaced
     /// with the specified value.
     #[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
-    pub fn with_channel(&self, channel: Channel) -> Self {
+    pub const fn with_channel(&self, channel: Channel) -> Self {
+        Config { channel, ..*self }
+    }
+
+    /// Create a new configuration where the channel has been replaced
+    /// with the channel of the differential input pair.
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
+    pub const fn with_input(&self, input: DifferentialInput) -> Self {
+        self.with_channel(input.channel())
+    }
+
+    /// Create a new configuration where the saturation policy has been
+    /// replaced with the specified value.
+    pub const fn with_saturation_policy(&self, saturation: SaturationPolicy) -> Self {
         Config {
-            resolution: self.resolution,
-            gain: self.gain,
-            channel,
+            saturation,
+            ..*self
         }
     }
 
-    /// Return the bitmask for the combined configuration values.
-    fn bits(&self) -> u8 {
-        self.channel.bits() | self.resolution.bits() | self.gain.bits()
+    /// Create a new configuration that corrects every voltage with the
+    /// specified calibration.
+    ///
+    /// The calibration is only valid for the gain it was measured with, see
+    /// the [`calibration`](calibration/index.html) module.
+    pub const fn with_calibration(&self, calibration: Calibration) -> Self {
+        Config {
+            calibration: Some(calibration),
+            ..*self
+        }
     }
-}
 
-/// A voltage measurement.
-#[cfg(not(feature = "measurements"))]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Voltage {
-    millivolts: i16,
-}
+    /// Return the time in microseconds to wait after triggering a one-shot
+    /// measurement with this configuration until the result is ready.
+    ///
+    /// This is the conversion time of the resolution plus a safety margin of
+    /// 2ms. It can be used to arm a timer instead of blocking while the
+    /// conversion is running.
+    pub const fn time_until_ready(&self) -> u32 {
+        self.resolution.conversion_time_us() + CONVERSION_MARGIN_US
+    }
 
-#[cfg(not(feature = "measurements"))]
-impl Voltage {
-    /// Create a new `Voltage` instance from a millivolt measurement.
-    pub fn from_millivolts(millivolts: i16) -> Self {
-        Self { millivolts }
+    /// Return the input-referred full-scale range in millivolts.
+    ///
+    /// Input voltages between `-full_scale_mv()` and `+full_scale_mv()` can be
+    /// measured, e.g. ±2048mV at gain 1 and ±256mV at gain 8.
+    pub fn full_scale_mv(&self) -> i32 {
+        REF_MILLIVOLTS / i32::from(self.gain.factor())
     }
 
-    /// Return the voltage in millivolts.
-    pub fn as_millivolts(&self) -> i16 {
-        self.millivolts
+    /// Return the input-referred size of one LSB in microvolts.
+    ///
+    /// For example, one LSB is 1000µV at 12 bits and gain 1, and 7.8125µV at
+    /// 16 bits and gain 8. All values are exactly representable as `f32`.
+    pub fn lsb_microvolts(&self) -> f32 {
+        self.resolution.step_microvolts() / f32::from(self.gain.factor())
     }
 
-    /// Return the voltage in volts.
-    pub fn as_volts(&self) -> f32 {
-        self.millivolts as f32 / 1000.0
+    /// Return the configuration register byte for this configuration in the
+    /// specified conversion mode.
+    ///
+    /// The RDY bit is not set. In one-shot mode, writing the byte with the RDY
+    /// bit set starts a conversion.
+    ///
+    /// ```
+    /// # use mcp3425::{Config, ContinuousMode, Resolution};
+    /// let config = Config::default().with_resolution(Resolution::Bits16Sps15);
+    /// assert_eq!(config.to_byte(&ContinuousMode), 0b00011000);
+    /// ```
+    pub fn to_byte<M: ConversionMode>(&self, mode: &M) -> u8 {
+        mode.bits() | self.bits()
     }
-}
 
-/// Driver for the MCP3425 ADC
-#[derive(Debug, Default)]
-pub struct MCP3425<I2C, D, M> {
-    /// The concrete I²C device implementation.
-    i2c: I2C,
-    /// The I²C device address.
-    address: u8,
-    /// The concrete Delay implementation.
-    delay: D,
-    /// The ADC conversion mode.
-    mode: M,
-    /// The configuration being used by the last measurement.
-    config: Option<Config>,
+    /// Decode a configuration register byte, including the conversion mode
+    /// and the RDY bit. See [`DeviceStatus`](struct.DeviceStatus.html).
+    pub fn try_from_byte(byte: u8) -> Result<DeviceStatus, InvalidConfig> {
+        DeviceStatus::try_from(byte)
+    }
+
+    /// Return the bitmask for the combined configuration values.
+    const fn bits(&self) -> u8 {
+        self.channel.bits() | self.resolution.bits() | self.gain.bits()
+    }
 }
 
-impl<I2C, D, M> MCP3425<I2C, D, M>
-where
-    I2C: I2c,
-    D: DelayNs,
-    M: ConversionMode,
-{
-    /// Initialize the MCP3425 driver.
+/// The decoded content of the configuration register.
+///
+/// ```
+/// # use core::convert::TryFrom;
+/// # use mcp3425::{DeviceStatus, Gain, Resolution};
+/// let status = DeviceStatus::try_from(0b10010110).unwrap();
+/// assert!(!status.ready);
+/// assert!(status.continuous);
+/// assert_eq!(status.resolution, Resolution::Bits14Sps60);
+/// assert_eq!(status.gain, Gain::Gain4);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeviceStatus {
+    /// Whether the latest conversion result is available. When writing the
+    /// register in one-shot mode, this bit starts a conversion instead.
+    pub ready: bool,
+    /// Whether the device is in continuous conversion mode.
+    pub continuous: bool,
+    /// Conversion bit resolution and sample rate.
+    pub resolution: Resolution,
+    /// Programmable gain amplifier (PGA).
+    pub gain: Gain,
+    /// Selected input channel.
+    pub channel: Channel,
+}
+
+impl DeviceStatus {
+    /// Return the resolution, gain and channel as a configuration, with the
+    /// default saturation policy.
+    pub fn config(&self) -> Config {
+        Config::new(self.resolution, self.gain, self.channel)
+    }
+}
+
+impl TryFrom<u8> for DeviceStatus {
+    type Error = InvalidConfig;
+
+    /// Decode a configuration register byte.
     ///
-    /// This constructor is side-effect free, so it will not write any
-    /// configuration to the device until a first measurement is triggered.
-    pub fn new(i2c: I2C, address: u8, delay: D, mode: M) -> Self {
-        MCP3425 {
-            i2c,
-            address,
-            delay,
-            mode,
-            config: None,
+    /// Fails if the byte selects a resolution or a channel that is not
+    /// enabled with Cargo features.
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        let register = ConfigRegister::from_bits_truncate(byte);
+        Ok(DeviceStatus {
+            ready: register.is_ready(),
+            continuous: register.contains(ConfigRegister::MODE),
+            resolution: Resolution::from_bits(byte).ok_or(InvalidConfig(byte))?,
+            gain: Gain::from_bits(byte),
+            channel: Channel::from_bits(byte).ok_or(InvalidConfig(byte))?,
+        })
+    }
+}
+
+/// Error returned when decoding a configuration register byte that selects a
+/// resolution or channel which is not enabled with Cargo features.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidConfig(pub u8);
+
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported MCP342x configuration: {:#010b}", self.0)
+    }
+}
+
+/// A voltage measurement.
+///
+/// The voltage is stored in microvolts as an `i32`, so the resolution of 16
+/// and 18 bit readings is kept (one LSB is 7.8125µV at 16 bits and gain 8),
+/// and calibrated or scaled voltages beyond the input range don't overflow.
+///
+/// Voltages are ordered by their value, so they can be compared to limits
+/// directly:
+///
+/// ```
+/// use mcp3425::Voltage;
+///
+/// const LIMIT: Voltage = Voltage::from_millivolts(1_800);
+///
+/// let voltage = Voltage::from_microvolts(1_800_500);
+/// assert!(voltage > LIMIT);
+/// assert_eq!(voltage.min(LIMIT), LIMIT);
+/// ```
+#[cfg(not(feature = "measurements"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Voltage {
+    microvolts: i32,
+}
+
+#[cfg(not(feature = "measurements"))]
+impl Voltage {
+    /// Create a new `Voltage` instance from a millivolt measurement.
+    pub const fn from_millivolts(millivolts: i16) -> Self {
+        Self {
+            microvolts: millivolts as i32 * 1000,
         }
     }
 
-    /// Read an i16 and the configuration register from the device.
-    fn read_i16_and_config(&mut self) -> Result<(i16, ConfigRegister), Error<I2C::Error>> {
-        let mut buf = [0, 0, 0];
-        self.i2c.read(self.address, &mut buf).map_err(Error::I2c)?;
-        let measurement = BigEndian::read_i16(&buf[0..2]);
-        let config_reg = ConfigRegister::from_bits_truncate(buf[2]);
-        Ok((measurement, config_reg))
+    /// Create a new `Voltage` instance from a microvolt measurement.
+    pub const fn from_microvolts(microvolts: i32) -> Self {
+        Self { microvolts }
     }
 
-    /// Calculate the voltage for the measurement result at the specified sample rate.
+    /// Return the voltage in whole millivolts, rounded towards zero.
     ///
-    /// If the value is a saturation value, an error is returned.
-    fn calculate_voltage(
-        &self,
-        measurement: i16,
-        resolution: &Resolution,
-    ) -> Result<Voltage, Error<I2C::Error>> {
-        // Handle saturation / out of range values
-        if measurement == resolution.max() {
-            return Err(Error::VoltageTooHigh);
-        } else if measurement == resolution.min() {
-            return Err(Error::VoltageTooLow);
+    /// Voltages beyond the range of `i16` saturate, use
+    /// [`as_microvolts`](#method.as_microvolts) for those.
+    pub fn as_millivolts(&self) -> i16 {
+        (self.microvolts / 1000).clamp(i16::MIN.into(), i16::MAX.into()) as i16
+    }
+
+    /// Return the voltage in microvolts.
+    pub const fn as_microvolts(&self) -> i32 {
+        self.microvolts
+    }
+
+    /// Return the voltage in volts.
+    pub fn as_volts(&self) -> f32 {
+        self.microvolts as f32 / 1_000_000.0
+    }
+
+    /// Return the voltage in volts as a fixed-point number, rounded to the
+    /// nearest step of 2⁻¹⁶V (about 15µV).
+    ///
+    /// This uses integer math only, e.g. for targets without an FPU. Only
+    /// available if the `fixed` Cargo feature is enabled.
+    ///
+    /// ```
+    /// use fixed::types::I16F16;
+    /// # #[cfg(not(feature = "measurements"))]
+    /// # {
+    /// use mcp3425::Voltage;
+    ///
+    /// let voltage = Voltage::from_millivolts(-1_250);
+    /// assert_eq!(voltage.as_volts_fixed(), I16F16::from_num(-1.25));
+    /// # }
+    /// ```
+    #[cfg(feature = "fixed")]
+    pub fn as_volts_fixed(&self) -> fixed::types::I16F16 {
+        let scaled = i64::from(self.microvolts) << 16;
+        // Round half away from zero
+        let half = if scaled < 0 { -500_000 } else { 500_000 };
+        // |microvolts| < 2³¹, so the result is below 2³¹ / 15.26 and fits
+        fixed::types::I16F16::from_bits(((scaled + half) / 1_000_000) as i32)
+    }
+
+    /// Length of the longest voltage formatted by
+    /// [`write_to`](#method.write_to), `-2147.483648`.
+    pub const MAX_STR_LEN: usize = 12;
+
+    /// Format the voltage in volts with `decimals` decimals into `buf`, and
+    /// return the written text from the start of `buf`, e.g. `-1.235` for 3
+    /// decimals.
+    ///
+    /// This doesn't use the `core::fmt` machinery, which saves code size,
+    /// e.g. for character LCDs. The value is rounded half away from zero.
+    /// More than 6 decimals (1µV) are treated as 6. The bytes after the text
+    /// are overwritten as well. Return `None` if `buf` is too small, a buffer
+    /// of [`MAX_STR_LEN`](#associatedconstant.MAX_STR_LEN) bytes always
+    /// suffices.
+    ///
+    /// ```
+    /// use mcp3425::Voltage;
+    ///
+    /// let mut buf = [0; Voltage::MAX_STR_LEN];
+    /// let text = Voltage::from_microvolts(1_234_567).write_to(&mut buf, 2);
+    /// assert_eq!(text, Some("1.23"));
+    /// ```
+    pub fn write_to<'a>(&self, buf: &'a mut [u8], decimals: u8) -> Option<&'a str> {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let divisor = 10u64.pow(u32::from(MAX_DECIMALS - decimals));
+        let rounded = (u64::from(self.microvolts.unsigned_abs()) + divisor / 2) / divisor;
+        // The digits are written from the back, the sign is written last
+        let mut start = buf.len();
+        let mut push = |byte: u8| {
+            start = start.checked_sub(1)?;
+            buf[start] = byte;
+            Some(())
+        };
+        let mut rest = rounded;
+        for digit in 0.. {
+            if digit == decimals && decimals > 0 {
+                push(b'.')?;
+            }
+            push(b'0' + (rest % 10) as u8)?;
+            rest /= 10;
+            if rest == 0 && digit >= decimals {
+                break;
+            }
+        }
+        // No sign for values that round to zero
+        if self.microvolts < 0 && rounded > 0 {
+            push(b'-')?;
+        }
+        let len = buf.len() - start;
+        buf.copy_within(start.., 0);
+        // Only ASCII characters were written
+        core::str::from_utf8(&buf[..len]).ok()
+    }
+
+    /// Return the voltage at the top of a resistive divider, if this is the
+    /// voltage across the bottom resistor, rounded to the nearest microvolt.
+    ///
+    /// The resistances can be in any unit, e.g. ohms. Return `None` if
+    /// `r_bottom` is 0 or the result overflows.
+    ///
+    /// ```
+    /// use mcp3425::Voltage;
+    ///
+    /// // 12V rail through a 100k / 10k divider
+    /// let measured = Voltage::from_microvolts(1_090_909);
+    /// let rail = measured.scale_by_divider(100_000, 10_000).unwrap();
+    /// assert_eq!(rail.as_microvolts(), 11_999_999);
+    /// ```
+    pub fn scale_by_divider(self, r_top: u32, r_bottom: u32) -> Option<Self> {
+        if r_bottom == 0 {
+            return None;
         }
+        let numerator = i128::from(self.microvolts) * (i128::from(r_top) + i128::from(r_bottom));
+        let denominator = i128::from(r_bottom);
+        // Round half away from zero
+        let half = if numerator < 0 {
+            -denominator
+        } else {
+            denominator
+        } / 2;
+        i32::try_from((numerator + half) / denominator)
+            .ok()
+            .map(Self::from_microvolts)
+    }
 
-        let converted =
-            measurement as i32 * (REF_MILLIVOLTS * 2) as i32 / (1 << resolution.res_bits());
-        // The "allow" annotation is needed because there are different Voltage
-        // types, depending on the build flags.
-        #[allow(clippy::useless_conversion)]
-        Ok(Voltage::from_millivolts((converted as i16).into()))
+    /// Add two voltages, returning `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.microvolts
+            .checked_add(rhs.microvolts)
+            .map(Self::from_microvolts)
     }
 
-    /// Destroy the driver instance and return the I2C device.
-    pub fn destroy(self) -> I2C {
-        self.i2c
+    /// Subtract two voltages, returning `None` on overflow.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.microvolts
+            .checked_sub(rhs.microvolts)
+            .map(Self::from_microvolts)
+    }
+
+    /// Multiply the voltage by a factor, returning `None` on overflow.
+    pub fn checked_mul(self, rhs: i32) -> Option<Self> {
+        self.microvolts.checked_mul(rhs).map(Self::from_microvolts)
+    }
+
+    /// Divide the voltage by a divisor, rounding towards zero. Return `None`
+    /// if the divisor is 0 or on overflow.
+    pub fn checked_div(self, rhs: i32) -> Option<Self> {
+        self.microvolts.checked_div(rhs).map(Self::from_microvolts)
+    }
+
+    /// Add two voltages, saturating at the numeric bounds.
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_microvolts(self.microvolts.saturating_add(rhs.microvolts))
+    }
+
+    /// Subtract two voltages, saturating at the numeric bounds.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_microvolts(self.microvolts.saturating_sub(rhs.microvolts))
+    }
+
+    /// Multiply the voltage by a factor, saturating at the numeric bounds.
+    pub fn saturating_mul(self, rhs: i32) -> Self {
+        Self::from_microvolts(self.microvolts.saturating_mul(rhs))
     }
 }
 
-impl<I2C, D> MCP3425<I2C, D, OneShotMode>
-where
-    I2C: I2c,
-    D: DelayNs,
-{
-    /// Initialize the MCP3425 driver in One-Shot mode.
-    ///
-    /// This constructor is side-effect free, so it will not write any
-    /// configuration to the device until a first measurement is triggered.
-    pub fn oneshot(i2c: I2C, address: u8, delay: D) -> Self {
-        MCP3425 {
-            i2c,
-            address,
-            delay,
-            mode: OneShotMode,
-            config: None,
+/// Format the voltage in volts with the unit, e.g. `1.234500 V`.
+///
+/// The precision selects the number of decimals, up to the resolution of 6
+/// decimals (1µV), e.g. `format!("{:.3}", voltage)` gives `1.235 V`. The
+/// value is rounded half away from zero.
+#[cfg(not(feature = "measurements"))]
+impl fmt::Display for Voltage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decimals = f.precision().map_or(MAX_DECIMALS, |precision| {
+            precision.min(usize::from(MAX_DECIMALS)) as u8
+        });
+        let mut buf = [0; Voltage::MAX_STR_LEN];
+        let text = self.write_to(&mut buf, decimals).unwrap_or_default();
+        // `Formatter::pad` would truncate the text to the precision
+        let padding = f.width().unwrap_or(0).saturating_sub(text.len() + 2);
+        let (before, after) = match f.align() {
+            Some(fmt::Alignment::Left) => (0, padding),
+            Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            _ => (padding, 0),
+        };
+        let fill = f.fill();
+        for _ in 0..before {
+            fmt::Write::write_char(f, fill)?;
+        }
+        f.write_str(text)?;
+        f.write_str(" V")?;
+        for _ in 0..after {
+            fmt::Write::write_char(f, fill)?;
         }
+        Ok(())
+    }
+}
+
+/// Format the voltage in volts with the unit and all 6 decimals, e.g.
+/// `1.234500 V`.
+#[cfg(all(feature = "ufmt", not(feature = "measurements")))]
+impl ufmt::uDisplay for Voltage {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        let mut buf = [0; Voltage::MAX_STR_LEN];
+        f.write_str(self.write_to(&mut buf, MAX_DECIMALS).unwrap_or_default())?;
+        f.write_str(" V")
     }
+}
 
-    /// Change the conversion mode to continuous.
+/// Number of decimals of a voltage in volts at a resolution of 1µV.
+#[cfg(not(feature = "measurements"))]
+const MAX_DECIMALS: u8 = 6;
+
+/// Convert the voltage to volts.
+#[cfg(not(feature = "measurements"))]
+impl From<Voltage> for f32 {
+    fn from(voltage: Voltage) -> Self {
+        voltage.as_volts()
+    }
+}
+
+/// Convert the voltage to volts.
+#[cfg(not(feature = "measurements"))]
+impl From<Voltage> for f64 {
+    fn from(voltage: Voltage) -> Self {
+        f64::from(voltage.microvolts) / 1_000_000.0
+    }
+}
+
+/// Convert the voltage to microvolts.
+///
+/// The conversion can't fail, the blanket `TryFrom` implementation is
+/// available for generic code.
+#[cfg(not(feature = "measurements"))]
+impl From<Voltage> for i32 {
+    fn from(voltage: Voltage) -> Self {
+        voltage.microvolts
+    }
+}
+
+/// Create a voltage from microvolts.
+#[cfg(not(feature = "measurements"))]
+impl From<i32> for Voltage {
+    fn from(microvolts: i32) -> Self {
+        Voltage::from_microvolts(microvolts)
+    }
+}
+
+/// Error returned when converting a number of volts that is not finite or
+/// out of the range of [`Voltage`](struct.Voltage.html).
+#[cfg(not(feature = "measurements"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VoltageOutOfRange;
+
+#[cfg(not(feature = "measurements"))]
+impl fmt::Display for VoltageOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("voltage out of range")
+    }
+}
+
+/// Create a voltage from volts, rounded to the nearest microvolt.
+#[cfg(not(feature = "measurements"))]
+impl TryFrom<f64> for Voltage {
+    type Error = VoltageOutOfRange;
+
+    fn try_from(volts: f64) -> Result<Self, Self::Error> {
+        let microvolts = volts * 1_000_000.0;
+        let rounded = if microvolts < 0.0 {
+            microvolts - 0.5
+        } else {
+            microvolts + 0.5
+        };
+        // Also false for NaN
+        if rounded > f64::from(i32::MIN) - 1.0 && rounded < f64::from(i32::MAX) + 1.0 {
+            Ok(Voltage::from_microvolts(rounded as i32))
+        } else {
+            Err(VoltageOutOfRange)
+        }
+    }
+}
+
+/// Create a voltage from volts, rounded to the nearest microvolt.
+#[cfg(not(feature = "measurements"))]
+impl TryFrom<f32> for Voltage {
+    type Error = VoltageOutOfRange;
+
+    fn try_from(volts: f32) -> Result<Self, Self::Error> {
+        Voltage::try_from(f64::from(volts))
+    }
+}
+
+// The operators behave like the ones of the underlying `i32` microvolts, i.e.
+// they panic on overflow in debug builds. Use the checked or saturating
+// methods for voltages that may overflow.
+
+#[cfg(not(feature = "measurements"))]
+impl Add for Voltage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::from_microvolts(self.microvolts + rhs.microvolts)
+    }
+}
+
+#[cfg(not(feature = "measurements"))]
+impl AddAssign for Voltage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+#[cfg(not(feature = "measurements"))]
+impl Sub for Voltage {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::from_microvolts(self.microvolts - rhs.microvolts)
+    }
+}
+
+#[cfg(not(feature = "measurements"))]
+impl SubAssign for Voltage {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(not(feature = "measurements"))]
+impl Neg for Voltage {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::from_microvolts(-self.microvolts)
+    }
+}
+
+#[cfg(not(feature = "measurements"))]
+impl Mul<i32> for Voltage {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Self::from_microvolts(self.microvolts * rhs)
+    }
+}
+
+/// Divide by an integer, rounding towards zero.
+#[cfg(not(feature = "measurements"))]
+impl Div<i32> for Voltage {
+    type Output = Self;
+
+    fn div(self, rhs: i32) -> Self {
+        Self::from_microvolts(self.microvolts / rhs)
+    }
+}
+
+#[cfg(all(feature = "uom", not(feature = "measurements")))]
+impl From<Voltage> for uom::si::f32::ElectricPotential {
+    fn from(voltage: Voltage) -> Self {
+        electric_potential(&voltage)
+    }
+}
+
+/// Convert a voltage to a [uom](https://docs.rs/uom) quantity.
+///
+/// Without the `measurements` feature, `Voltage` also converts with `into()`.
+/// Only available if the `uom` Cargo feature is enabled.
+///
+/// ```
+/// # #[cfg(not(feature = "measurements"))]
+/// # {
+/// use mcp3425::{electric_potential, Voltage};
+/// use uom::si::electric_potential::microvolt;
+///
+/// let potential = electric_potential(&Voltage::from_microvolts(1_500));
+/// assert_eq!(potential.get::<microvolt>(), 1_500.0);
+/// # }
+/// ```
+#[cfg(feature = "uom")]
+pub fn electric_potential(voltage: &Voltage) -> uom::si::f32::ElectricPotential {
+    use uom::si::electric_potential::microvolt;
+    uom::si::f32::ElectricPotential::new::<microvolt>(voltage_to_microvolts(voltage) as f32)
+}
+
+/// A measurement together with the parameters it was acquired with.
+///
+/// Returned by [`measure_full`](struct.MCP3425.html#method.measure_full) and
+/// [`read_measurement_full`](struct.MCP3425.html#method.read_measurement_full).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Reading {
+    /// The measured voltage.
+    pub voltage: Voltage,
+    /// The signed output code the voltage was calculated from.
+    pub raw_code: i32,
+    /// The configuration used for the conversion.
+    pub config: Config,
+    /// Whether this is a new conversion result. This is `false` if the
+    /// result was already read before in continuous mode.
+    pub fresh: bool,
+    /// The number of new conversion results the driver has read, including
+    /// this one, wrapping around at `u32::MAX`. A result that is not fresh
+    /// repeats the sequence number of the previous reading, so duplicates
+    /// and results read elsewhere (e.g. with
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement))
+    /// can be detected.
+    pub sequence: u32,
+}
+
+impl Reading {
+    /// Return the reading relative to the full-scale range of its
+    /// configuration, from -1.0 to just below 1.0, e.g. for bar graphs or
+    /// ratiometric sensors.
     ///
-    /// This conversion is side-effect free, so it will not write any
-    /// configuration to the device until
-    /// [`set_config`](struct.MCP3425.html#method.set_config) is called.
-    pub fn into_continuous(self) -> MCP3425<I2C, D, ContinuousMode> {
-        MCP3425::continuous(self.i2c, self.address, self.delay)
+    /// The ratio is calculated from the output code, so it is independent
+    /// of the gain and the calibration.
+    pub fn fraction_of_full_scale(&self) -> f32 {
+        self.raw_code as f32 / self.full_scale_code() as f32
     }
 
-    /// Do a one-shot voltage measurement.
+    /// Return the reading relative to the full-scale range of its
+    /// configuration in per mille, from -1000 to 999, rounded towards zero.
     ///
-    /// Return the result in millivolts.
-    pub fn measure(&mut self, config: &Config) -> Result<Voltage, Error<I2C::Error>> {
-        let command = ConfigRegister::NOT_READY.bits() | self.mode.bits() | config.bits();
+    /// Like [`fraction_of_full_scale`](#method.fraction_of_full_scale), but
+    /// without floating point math.
+    pub fn per_mille_of_full_scale(&self) -> i16 {
+        // |raw_code| <= full scale, so the result fits
+        (self.raw_code * 1000 / self.full_scale_code()) as i16
+    }
+
+    /// Return the code at the full-scale voltage, one more than the maximum
+    /// code.
+    fn full_scale_code(&self) -> i32 {
+        Resolution::max(&self.config.resolution) + 1
+    }
+}
+
+/// A function returning the current time in microseconds, e.g. from a
+/// monotonic hardware timer.
+///
+/// See [`set_clock`](struct.MCP3425.html#method.set_clock).
+pub type ClockFn = fn() -> u64;
+
+/// A voltage together with the input channel it was measured on.
+///
+/// Returned by the multi-channel APIs like
+/// [`measure_all`](struct.MCP3425.html#method.measure_all) and
+/// [`run_scan`](struct.MCP3425.html#method.run_scan), so results don't have
+/// to be matched to channels by index.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChannelReading {
+    /// The input channel.
+    pub channel: Channel,
+    /// The measured voltage.
+    pub voltage: Voltage,
+}
+
+/// A channel reading with its acquisition offset.
+///
+/// Returned by [`snapshot`](struct.MCP3425.html#method.snapshot).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SnapshotReading {
+    /// The input channel.
+    pub channel: Channel,
+    /// The measured voltage.
+    pub voltage: Voltage,
+    /// The nominal time between the start of the first conversion of the
+    /// snapshot and the start of this conversion.
+    pub offset: Duration,
+}
+
+/// Retry behavior for transient errors.
+///
+/// If a measurement fails with an I²C bus error or a
+/// [`NotReady`](enum.Error.html#variant.NotReady) error, the driver waits
+/// `backoff_ms` milliseconds and tries again, up to `max_attempts` attempts in
+/// total. Once all attempts failed, the error of the first attempt is
+/// returned, since later errors are often just a consequence of it.
+///
+/// ```
+/// # use mcp3425::RetryPolicy;
+/// // Try up to three times, waiting 5 ms between attempts
+/// let policy = RetryPolicy::new(3, 5);
+/// ```
+///
+/// The default policy does not retry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u8,
+    /// Time to wait between two attempts, in milliseconds.
+    pub backoff_ms: u32,
+}
+
+impl RetryPolicy {
+    /// Create a new retry policy.
+    pub fn new(max_attempts: u8, backoff_ms: u32) -> Self {
+        RetryPolicy {
+            max_attempts,
+            backoff_ms,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Default implementation without any retries.
+    fn default() -> Self {
+        RetryPolicy::new(1, 0)
+    }
+}
+
+/// How the driver waits for a conversion to finish.
+///
+/// Set it with
+/// [`set_wait_strategy`](struct.MCP3425.html#method.set_wait_strategy).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Sleep for the typical conversion time (see
+    /// [`Resolution::conversion_time_us`](enum.Resolution.html#method.conversion_time_us)),
+    /// then poll the RDY bit every 250µs, until the maximum conversion time
+    /// from the datasheet (see
+    /// [`Resolution::max_conversion_time`](enum.Resolution.html#method.max_conversion_time))
+    /// has passed. In continuous mode,
+    /// [`set_config`](struct.MCP3425.html#method.set_config) polls every
+    /// millisecond after the typical conversion time.
+    ///
+    /// This returns results as soon as they are ready, while tolerating
+    /// devices at the slow end of the specified data rate.
+    #[default]
+    Polled,
+    /// Sleep for the maximum conversion time from the datasheet (see
+    /// [`Resolution::max_conversion_time`](enum.Resolution.html#method.max_conversion_time)),
+    /// then read the result once, without polling.
+    ///
+    /// This adds latency, since typical conversions finish much earlier, but
+    /// keeps the bus free while the conversion is running. In continuous
+    /// mode, [`set_config`](struct.MCP3425.html#method.set_config) returns
+    /// [`Error::Timeout`](enum.Error.html#variant.Timeout) if the first
+    /// result is not ready after the maximum conversion time.
+    WorstCaseSleep,
+    /// Sleep for the nominal conversion time, then poll the RDY bit every
+    /// `poll_interval_us` microseconds until the result is ready.
+    ///
+    /// A one-shot measurement that is not ready after
+    /// [`Resolution::max_conversion_time`](enum.Resolution.html#method.max_conversion_time)
+    /// fails with [`MeasureError::NotReady`](enum.MeasureError.html#variant.NotReady).
+    SleepThenPoll {
+        /// Time between two reads of the RDY bit, in microseconds.
+        poll_interval_us: u32,
+    },
+    /// Never sleep in continuous mode: Results are read right away, and
+    /// [`set_config`](struct.MCP3425.html#method.set_config) returns without
+    /// waiting for the first measurement. Use this if reads are paced
+    /// externally, e.g. by a timer, or together with a
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// One-shot measurements start the conversion themselves, so they can't
+    /// be paced externally: They sleep for the typical conversion time and
+    /// read the result once. A `RetryPolicy` re-reads the result without
+    /// starting another conversion. To pace one-shot conversions, use
+    /// [`arm`](struct.MCP3425.html#method.arm) and
+    /// [`harvest`](struct.MCP3425.html#method.harvest).
+    UserPaced,
+}
+
+/// Result of a [`ping`](struct.MCP3425.html#method.ping).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Presence {
+    /// The device responded and the latest conversion result is available.
+    Ready,
+    /// The device responded, but a conversion is still running (or, in
+    /// continuous mode, the latest result has already been read).
+    Converting,
+    /// The device did not acknowledge its address.
+    Absent,
+}
+
+impl Presence {
+    /// Classify a device that responded, based on its configuration register.
+    ///
+    /// When reading 4 bytes, the configuration register is the 4th byte at
+    /// all resolutions: at 12 to 16 bits, the device repeats it after the 3rd
+    /// byte.
+    fn from_config(config: u8) -> Self {
+        if ConfigRegister::from_bits_truncate(config).is_ready() {
+            Presence::Ready
+        } else {
+            Presence::Converting
+        }
+    }
+}
+
+/// Decode the data read from the device into the output code and the
+/// configuration register.
+///
+/// The buffer length must match [`Resolution::read_len`].
+fn decode_reading(buf: &[u8]) -> (i32, ConfigRegister) {
+    let (code, config) = buf.split_at(buf.len() - 1);
+    (
+        decode_code(code),
+        ConfigRegister::from_bits_truncate(config[0]),
+    )
+}
+
+/// Decode the output code (3 bytes at 18 bits, 2 bytes otherwise).
+fn decode_code(buf: &[u8]) -> i32 {
+    match buf.len() {
+        3 => BigEndian::read_i24(buf),
+        _ => BigEndian::read_i16(buf).into(),
+    }
+}
+
+/// Calculate the input voltage for the measurement result with the specified
+/// configuration.
+///
+/// The voltage is input-referred: The amplification of the PGA is taken into
+/// account.
+///
+/// If the value is a saturation value, an error is returned.
+fn calculate_voltage<Err: DriverError>(measurement: i32, config: &Config) -> Result<Voltage, Err> {
+    calculate(measurement, config)
+}
+
+/// Convert the measurement result with the specified configuration to the
+/// output type, like [`calculate_voltage`].
+fn calculate<T: FromAdcCode, Err: DriverError>(
+    measurement: i32,
+    config: &Config,
+) -> Result<T, Err> {
+    let resolution = &config.resolution;
+    let saturation = if measurement == resolution.max() {
+        Some((true, config.full_scale_mv()))
+    } else if measurement == resolution.min() {
+        Some((false, -config.full_scale_mv()))
+    } else {
+        None
+    };
+
+    let microvolts = match (saturation, config.saturation) {
+        (Some((high, _)), SaturationPolicy::Error) => return Err(Err::saturated(high)),
+        (Some((_, full_scale_mv)), SaturationPolicy::Clamp) => full_scale_mv * 1000,
+        _ => input_microvolts(measurement, config),
+    };
+    Ok(T::from_adc_code(measurement, microvolts, config))
+}
+
+/// Compile-time check that the sample count `N` of `measure_median` is odd.
+struct OddSampleCount<const N: usize>;
+
+impl<const N: usize> OddSampleCount<N> {
+    const ASSERT: () = assert!(N % 2 == 1, "median of an even number of samples");
+}
+
+/// Insert `code` into `extremes` if it comes before the last entry in the
+/// order given by `before`, keeping `extremes` sorted.
+fn keep_extreme(extremes: &mut [i32], code: i32, before: fn(i32, i32) -> bool) {
+    let mut index = match extremes.last() {
+        Some(&last) if before(code, last) => extremes.len() - 1,
+        _ => return,
+    };
+    while index > 0 && before(code, extremes[index - 1]) {
+        extremes[index] = extremes[index - 1];
+        index -= 1;
+    }
+    extremes[index] = code;
+}
+
+/// Create a voltage from a microvolt value.
+fn voltage_from_microvolts(microvolts: i32) -> Voltage {
+    // The "allow" annotation is needed because there are different Voltage
+    // types, depending on the build flags.
+    #[allow(clippy::useless_conversion)]
+    Voltage::from_microvolts(microvolts.into())
+}
+
+/// Return the voltage in microvolts.
+#[cfg(not(feature = "measurements"))]
+fn voltage_to_microvolts(voltage: &Voltage) -> i32 {
+    voltage.as_microvolts()
+}
+
+/// Return the voltage in microvolts, rounded to the nearest microvolt.
+#[cfg(feature = "measurements")]
+fn voltage_to_microvolts(voltage: &Voltage) -> i32 {
+    let microvolts = voltage.as_microvolts();
+    // Undo the rounding error of the conversion to volts
+    if microvolts < 0.0 {
+        (microvolts - 0.5) as i32
+    } else {
+        (microvolts + 0.5) as i32
+    }
+}
+
+/// Calculate the input voltage in microvolts for an output code with the
+/// specified configuration, including its calibration.
+fn input_microvolts(measurement: i32, config: &Config) -> i32 {
+    let microvolts = code_to_microvolts(measurement, &config.resolution, &config.gain);
+    config
+        .calibration
+        .map_or(microvolts, |calibration| calibration.apply_uv(microvolts))
+}
+
+/// Calculate the input voltage in microvolts for an output code at the
+/// specified resolution and gain.
+///
+/// One LSB is 1000µV at 12 bits and gain 1, and half as much for every
+/// additional bit or doubling of the gain. Scaling the 12-bit LSB keeps all
+/// intermediate values well within `i32`, even for 18-bit codes.
+fn code_to_microvolts(measurement: i32, resolution: &Resolution, gain: &Gain) -> i32 {
+    let divisor = (1 << (resolution.res_bits() - 12)) * i32::from(gain.factor());
+    measurement * LSB_12BIT_MICROVOLTS / divisor
+}
+
+/// Calculate the mean input voltage in microvolts for the sum of `n` output
+/// codes at the specified resolution and gain.
+///
+/// The sum is scaled instead of the mean code, to keep the fraction of the
+/// LSB. The mean is within the input range, so it fits.
+fn mean_microvolts(sum: i64, n: u16, resolution: &Resolution, gain: &Gain) -> i32 {
+    let divisor = (1i64 << (resolution.res_bits() - 12)) * i64::from(gain.factor()) * i64::from(n);
+    (sum * i64::from(LSB_12BIT_MICROVOLTS) / divisor) as i32
+}
+
+/// Start a conversion on all MCP342x devices on the bus at the same time.
+///
+/// This sends the I²C general call conversion command (datasheet section
+/// 5.4), which is received by every MCP342x device on the bus, regardless of
+/// its address. It can be used to sample several ADCs simultaneously:
+///
+/// 1. Write the configuration to every device with
+///    [`prepare`](struct.MCP3425.html#method.prepare)
+/// 2. Call this function
+/// 3. Wait for [`Config::time_until_ready`](struct.Config.html#method.time_until_ready)
+/// 4. Read the results with
+///    [`read_prepared`](struct.MCP3425.html#method.read_prepared)
+///
+/// Since the drivers own their I²C device, `i2c` is usually a separate
+/// handle to a shared bus (see the `shared_bus` feature).
+pub fn general_call_conversion<I2C: Transport>(i2c: &mut I2C) -> Result<(), I2C::Error> {
+    i2c.write_command(GENERAL_CALL_ADDRESS, GENERAL_CALL_CONVERSION)
+}
+
+/// Driver for the MCP3425 ADC
+///
+/// The `DEV` type parameter is the device [`model`](model/index.html), which
+/// allows checking channel selections at compile time.
+#[derive(Debug, Default)]
+pub struct MCP3425<I2C, D, M, DEV = AnyModel> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    /// The I²C device address.
+    address: u8,
+    /// The concrete Delay implementation.
+    delay: D,
+    /// The ADC conversion mode.
+    mode: M,
+    /// The configuration being used by the last measurement.
+    config: Option<Config>,
+    /// The configuration of the one-shot conversion started with `arm` that
+    /// has not been harvested yet.
+    armed: Option<Config>,
+    /// Retry behavior for transient errors.
+    retry: RetryPolicy,
+    /// Maximum time to wait for the first measurement after a configuration
+    /// change, or `None` for the default.
+    ready_timeout: Option<Duration>,
+    /// How to wait for conversions to finish.
+    wait: WaitStrategy,
+    /// Calibrations for configurations without their own calibration.
+    calibrations: ChannelCalibrations,
+    /// Temperature coefficients of the offset and gain error.
+    tempco: Option<Tempco>,
+    /// The temperature for the tempco compensation.
+    temperature: Option<Temperature>,
+    /// Number of consecutive saturated conversions before a saturation error
+    /// is reported.
+    saturation_debounce: u8,
+    /// Number of new conversion results read, wrapping around.
+    sequence: u32,
+    /// Time source for the age of the last result.
+    clock: Option<ClockFn>,
+    /// Time of the last new conversion result in microseconds, if a clock is
+    /// set.
+    last_fresh_us: Option<u64>,
+    /// The device model.
+    model: PhantomData<DEV>,
+}
+
+impl<I2C, D, M> MCP3425<I2C, D, M>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ConversionMode,
+{
+    /// Initialize the MCP3425 driver.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn new(i2c: I2C, address: u8, delay: D, mode: M) -> Self {
+        MCP3425 {
+            i2c,
+            address,
+            delay,
+            mode,
+            config: None,
+            armed: None,
+            retry: RetryPolicy::default(),
+            ready_timeout: None,
+            wait: WaitStrategy::Polled,
+            calibrations: ChannelCalibrations::new(),
+            tempco: None,
+            temperature: None,
+            saturation_debounce: 1,
+            sequence: 0,
+            clock: None,
+            last_fresh_us: None,
+            model: PhantomData,
+        }
+    }
+}
+
+impl<I2C, D, M, DEV> MCP3425<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ConversionMode,
+    DEV: Model,
+{
+    /// Specify the device model.
+    ///
+    /// This allows selecting channels with
+    /// [`channel_config`](struct.MCP3425.html#method.channel_config), which
+    /// are checked at compile time.
+    pub fn with_model<NEW: Model>(self, _model: NEW) -> MCP3425<I2C, D, M, NEW> {
+        MCP3425 {
+            i2c: self.i2c,
+            address: self.address,
+            delay: self.delay,
+            mode: self.mode,
+            config: self.config,
+            armed: self.armed,
+            retry: self.retry,
+            ready_timeout: self.ready_timeout,
+            wait: self.wait,
+            calibrations: self.calibrations,
+            tempco: self.tempco,
+            temperature: self.temperature,
+            saturation_debounce: self.saturation_debounce,
+            sequence: self.sequence,
+            clock: self.clock,
+            last_fresh_us: self.last_fresh_us,
+            model: PhantomData,
+        }
+    }
+
+    /// Create a new configuration where the channel has been replaced with
+    /// the specified channel.
+    ///
+    /// This only compiles if the device model has the selected channel.
+    pub fn channel_config<C>(&self, config: &Config, _channel: C) -> Config
+    where
+        C: ChannelMarker,
+        DEV: HasChannel<C>,
+    {
+        Config {
+            channel: C::CHANNEL,
+            ..*config
+        }
+    }
+
+    /// Change the conversion mode, keeping all other settings.
+    fn into_mode<N: ConversionMode>(self, mode: N) -> MCP3425<I2C, D, N, DEV> {
+        MCP3425 {
+            i2c: self.i2c,
+            address: self.address,
+            delay: self.delay,
+            mode,
+            config: None,
+            armed: None,
+            retry: self.retry,
+            ready_timeout: self.ready_timeout,
+            wait: self.wait,
+            calibrations: self.calibrations,
+            tempco: self.tempco,
+            temperature: self.temperature,
+            saturation_debounce: self.saturation_debounce,
+            sequence: self.sequence,
+            clock: self.clock,
+            last_fresh_us: self.last_fresh_us,
+            model: PhantomData,
+        }
+    }
+
+    /// Read the output code and the configuration register from the device.
+    fn read_code_and_config<Err: DriverError<Bus = I2C::Error>>(
+        &mut self,
+        resolution: &Resolution,
+    ) -> Result<(i32, ConfigRegister), Err> {
+        let mut buf = [0; 4];
+        let buf = &mut buf[..resolution.read_len()];
+        self.i2c.read_data(self.address, buf).map_err(Err::i2c)?;
+        let (code, config_reg) = decode_reading(buf);
+        self.track_result(config_reg);
+        Ok((code, config_reg))
+    }
+
+    /// Count a new conversion result for the sequence number and the age of
+    /// the last result, if the configuration register read with it is ready.
+    fn track_result(&mut self, config_reg: ConfigRegister) {
+        if config_reg.is_ready() {
+            self.sequence = self.sequence.wrapping_add(1);
+            self.last_fresh_us = self.clock.map(|now_us| now_us());
+        }
+    }
+
+    /// Make sure that the device model has the configured channel.
+    ///
+    /// Otherwise, the device would ignore the channel selection bits and
+    /// measure a different input.
+    fn check_channel(&self, config: &Config) -> Result<(), MeasureError<I2C::Error>> {
+        if config.channel.index() < DEV::CHANNELS {
+            Ok(())
+        } else {
+            Err(MeasureError::ChannelNotSupported)
+        }
+    }
+
+    /// Read and decode the configuration register of the device, without
+    /// changing its configuration or starting a conversion.
+    ///
+    /// This returns the configuration the device actually uses, which may
+    /// differ from the one cached by the driver, e.g. after a power glitch.
+    ///
+    /// The configuration register can only be read together with the output
+    /// register, so this marks the latest result as read: A pending result,
+    /// e.g. in continuous mode, is consumed, and a following
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement)
+    /// returns [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady)
+    /// until the next conversion has finished. The returned
+    /// [`ready`](struct.DeviceStatus.html#structfield.ready) flag tells
+    /// whether a result was consumed. A consumed result is counted in the
+    /// [`sequence`](struct.MCP3425.html#method.sequence) number and the
+    /// [`last_reading_age`](struct.MCP3425.html#method.last_reading_age).
+    pub fn read_status(&mut self) -> Result<DeviceStatus, Error<I2C::Error>> {
+        // The configuration register is the 4th byte at all resolutions: at
+        // 12 to 16 bits, the device repeats it after the 3rd byte.
+        let mut buf = [0; 4];
+        self.i2c
+            .read_data(self.address, &mut buf)
+            .map_err(Error::I2c)?;
+        self.track_result(ConfigRegister::from_bits_truncate(buf[3]));
+        DeviceStatus::try_from(buf[3]).map_err(Error::InvalidConfig)
+    }
+
+    /// Check whether the device is present, without changing its
+    /// configuration or starting a conversion.
+    ///
+    /// A missing acknowledge is reported as
+    /// [`Presence::Absent`](enum.Presence.html#variant.Absent), all other bus
+    /// errors are returned as errors.
+    ///
+    /// The presence is probed by reading the output register, since the
+    /// RDY bit is needed to tell a finished conversion from a running one.
+    /// Like any read, this marks the latest result as read: A pending
+    /// result, e.g. in continuous mode, is consumed, and a following
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement)
+    /// returns [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady)
+    /// until the next conversion has finished. Like with
+    /// [`read_status`](struct.MCP3425.html#method.read_status), a consumed
+    /// result is counted in the sequence number and the age of the last
+    /// result.
+    pub fn ping(&mut self) -> Result<Presence, Error<I2C::Error>> {
+        let mut buf = [0; 4];
+        match self.i2c.read_data(self.address, &mut buf) {
+            Ok(()) => {
+                self.track_result(ConfigRegister::from_bits_truncate(buf[3]));
+                Ok(Presence::from_config(buf[3]))
+            }
+            Err(e) if I2C::is_absent(&e) => Ok(Presence::Absent),
+            Err(e) => Err(Error::I2c(e)),
+        }
+    }
+
+    /// Set the retry behavior for transient errors in
+    /// [`measure`](struct.MCP3425.html#method.measure) and
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement).
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    /// Set the maximum time that
+    /// [`set_config`](struct.MCP3425.html#method.set_config) waits for the
+    /// first measurement, before giving up with
+    /// [`Error::Timeout`](enum.Error.html#variant.Timeout).
+    ///
+    /// By default, this is twice the maximum conversion time of the
+    /// configured resolution (see
+    /// [`Resolution::max_conversion_time`](enum.Resolution.html#method.max_conversion_time)).
+    pub fn set_ready_timeout(&mut self, timeout: Duration) {
+        self.ready_timeout = Some(timeout);
+    }
+
+    /// Set how the driver waits for conversions to finish.
+    pub fn set_wait_strategy(&mut self, strategy: WaitStrategy) {
+        self.wait = strategy;
+    }
+
+    /// Set the calibration of every channel, which is applied to all
+    /// configurations without their own
+    /// [`calibration`](struct.Config.html#structfield.calibration).
+    ///
+    /// The calibrations are applied when a configuration is used, e.g. by
+    /// [`measure`](struct.MCP3425.html#method.measure) or
+    /// [`set_config`](struct.MCP3425.html#method.set_config), so a changed
+    /// table only affects continuous mode reads after the next `set_config`.
+    pub fn set_channel_calibrations(&mut self, calibrations: ChannelCalibrations) {
+        self.calibrations = calibrations;
+    }
+
+    /// Return the calibration of every channel.
+    pub fn channel_calibrations(&self) -> &ChannelCalibrations {
+        &self.calibrations
+    }
+
+    /// Return `config` with the calibration of its channel, unless it
+    /// already has a calibration.
+    fn calibrated(&self, config: &Config) -> Config {
+        self.calibrations.apply(config)
+    }
+
+    /// Set the temperature coefficients of the offset and gain error, or
+    /// `None` to disable the temperature compensation.
+    ///
+    /// The compensation is applied to every voltage calculated by the
+    /// driver, on top of the calibration of the configuration, once a
+    /// temperature is known from
+    /// [`set_temperature`](struct.MCP3425.html#method.set_temperature) or
+    /// [`set_temperature_source`](struct.MCP3425.html#method.set_temperature_source).
+    /// Unlike the calibration, the current temperature is also used by
+    /// continuous mode reads without a new `set_config`.
+    pub fn set_tempco(&mut self, tempco: Option<Tempco>) {
+        self.tempco = tempco;
+    }
+
+    /// Set the current temperature in millidegrees Celsius for the tempco
+    /// compensation, e.g. from an external sensor that is read
+    /// periodically.
+    ///
+    /// This replaces a source set with
+    /// [`set_temperature_source`](struct.MCP3425.html#method.set_temperature_source).
+    pub fn set_temperature(&mut self, temperature_mc: i32) {
+        self.temperature = Some(Temperature::Fixed(temperature_mc));
+    }
+
+    /// Set a function that is called for the current temperature whenever a
+    /// voltage is calculated with tempco compensation.
+    ///
+    /// This replaces a temperature set with
+    /// [`set_temperature`](struct.MCP3425.html#method.set_temperature).
+    pub fn set_temperature_source(&mut self, read_mc: TemperatureFn) {
+        self.temperature = Some(Temperature::Source(read_mc));
+    }
+
+    /// Return `config` with its calibration compensated for the current
+    /// temperature, if a tempco and a temperature are set.
+    pub(crate) fn compensated(&self, config: &Config) -> Config {
+        Tempco::apply(self.tempco, self.temperature, config)
+    }
+
+    /// Convert `measurement` to a voltage with the calibration of `config`,
+    /// compensated for the current temperature.
+    fn voltage<Err: DriverError>(&self, measurement: i32, config: &Config) -> Result<Voltage, Err> {
+        self.convert(measurement, config)
+    }
+
+    /// Convert `measurement` to the output type, like
+    /// [`voltage`](#method.voltage).
+    fn convert<T: FromAdcCode, Err: DriverError>(
+        &self,
+        measurement: i32,
+        config: &Config,
+    ) -> Result<T, Err> {
+        calculate(measurement, &self.compensated(config))
+    }
+
+    /// Require `readings` consecutive saturated conversions before
+    /// [`measure`](struct.MCP3425.html#method.measure) or
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement)
+    /// report
+    /// [`VoltageTooHigh`](enum.MeasureError.html#variant.VoltageTooHigh) or
+    /// `VoltageTooLow`.
+    ///
+    /// If a conversion is saturated, the next conversion is read instead, so
+    /// a single full-scale sample caused by ESD or switching noise does not
+    /// surface as an error. This only affects the default
+    /// [`SaturationPolicy::Error`](enum.SaturationPolicy.html#variant.Error).
+    ///
+    /// The debounce applies to every sample of the methods that apply the
+    /// saturation policy per sample, e.g. `measure_full`, `measure_fast`,
+    /// `measure_avg` and the [`Oversampler`](oversample/struct.Oversampler.html).
+    /// Methods that return saturated codes as they are (`measure_raw`,
+    /// `read_measurement_raw`, `measure_stats`) or reject them by ranking
+    /// (`measure_median`, `measure_trimmed`) are not debounced, neither are
+    /// `read_measurement_full` and `read_measurement_unchecked`, which
+    /// return whatever result the device holds.
+    ///
+    /// Defaults to 1, i.e. every saturated conversion is reported. 0 is
+    /// treated as 1.
+    pub fn set_saturation_debounce(&mut self, readings: u8) {
+        self.saturation_debounce = readings.max(1);
+    }
+
+    /// Set the time source used to track the age of the last conversion
+    /// result, see
+    /// [`last_reading_age`](struct.MCP3425.html#method.last_reading_age).
+    pub fn set_clock(&mut self, now_us: ClockFn) {
+        self.clock = Some(now_us);
+    }
+
+    /// Return the number of new conversion results read so far, wrapping
+    /// around at `u32::MAX`.
+    ///
+    /// This is the [`sequence`](struct.Reading.html#structfield.sequence)
+    /// number of the last fresh reading.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Return the time since the last new conversion result was read.
+    ///
+    /// This allows detecting stale data in continuous mode, e.g. if the
+    /// reading task stalled. Return `None` if no clock was set with
+    /// [`set_clock`](struct.MCP3425.html#method.set_clock) or no new result
+    /// was read yet.
+    pub fn last_reading_age(&self) -> Option<Duration> {
+        let now_us = self.clock?();
+        let last_us = self.last_fresh_us?;
+        Some(Duration::from_micros(now_us.saturating_sub(last_us)))
+    }
+
+    /// Read from the device until the RDY bit is cleared.
+    ///
+    /// `waited_us` is the time already waited since the conversion was
+    /// started. Return `None` if the result is not ready once `timeout_us`
+    /// have passed.
+    fn poll_until_ready<Err: DriverError<Bus = I2C::Error>>(
+        &mut self,
+        resolution: &Resolution,
+        mut waited_us: u32,
+        poll_interval_us: u32,
+        timeout_us: u32,
+    ) -> Result<Option<i32>, Err> {
+        loop {
+            let (measurement, config_reg) = self.read_code_and_config(resolution)?;
+            if config_reg.is_ready() {
+                return Ok(Some(measurement));
+            }
+            if waited_us >= timeout_us {
+                return Ok(None);
+            }
+            // Not yet ready, wait some more time
+            self.delay.delay_us(poll_interval_us);
+            waited_us = waited_us.saturating_add(poll_interval_us.max(1));
+        }
+    }
+
+    /// Run `op` until it returns a result that is not a saturation error, up
+    /// to the configured number of saturated conversions.
+    fn with_debounce<T, Err: DriverError>(
+        &mut self,
+        mut op: impl FnMut(&mut Self) -> Result<T, Err>,
+    ) -> Result<T, Err> {
+        let mut saturated = 0;
+        loop {
+            match op(self) {
+                Err(e) if e.is_saturated() && saturated + 1 < self.saturation_debounce => {
+                    saturated += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Run `op`, retrying it according to the retry policy.
+    fn with_retries<T, Err: DriverError>(
+        &mut self,
+        mut op: impl FnMut(&mut Self) -> Result<T, Err>,
+    ) -> Result<T, Err> {
+        let mut attempt = 1;
+        let mut first_error = None;
+        loop {
+            match op(self) {
+                Err(e) if e.is_transient() => {
+                    if attempt >= self.retry.max_attempts {
+                        return Err(first_error.unwrap_or(e));
+                    }
+                    first_error.get_or_insert(e);
+                    attempt += 1;
+                    self.delay.delay_ms(self.retry.backoff_ms);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Return a mutable reference to the I2C device, e.g. for bus recovery
+    /// sequences.
+    ///
+    /// The configuration cached by the driver is not updated, so don't use
+    /// this to change the configuration of the device.
+    pub fn bus_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
+    }
+
+    /// Return the I²C device address.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Change the I²C device address, e.g. after the address of an MCP342x
+    /// device was changed by latching its address pins.
+    pub fn set_address(&mut self, address: u8) {
+        self.address = address;
+    }
+
+    /// Destroy the driver instance and return the I2C device.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Destroy the driver instance and return the I2C device and the delay.
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+impl<I2C, D> MCP3425<I2C, D, OneShotMode>
+where
+    I2C: Transport,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in One-Shot mode.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn oneshot(i2c: I2C, address: u8, delay: D) -> Self {
+        MCP3425::new(i2c, address, delay, OneShotMode)
+    }
+}
+
+impl<I2C, D, DEV> MCP3425<I2C, D, OneShotMode, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    /// Change the conversion mode to continuous.
+    ///
+    /// This conversion is side-effect free, so it will not write any
+    /// configuration to the device until
+    /// [`set_config`](struct.MCP3425.html#method.set_config) is called.
+    pub fn into_continuous(self) -> MCP3425<I2C, D, ContinuousMode, DEV> {
+        self.into_mode(ContinuousMode)
+    }
+
+    /// Change the conversion mode to continuous and write the specified
+    /// configuration to the device, like
+    /// [`set_config`](struct.MCP3425.html#method.set_config).
+    ///
+    /// If writing the configuration fails, the error is returned together
+    /// with the unchanged one-shot driver. If the configuration was written
+    /// but the first measurement does not become ready, the device is put
+    /// back into one-shot standby (like
+    /// [`power_down`](struct.MCP3425.html#method.power_down)) before the
+    /// error is returned, so that it matches the mode of the returned driver.
+    #[allow(clippy::type_complexity, clippy::result_large_err)]
+    pub fn into_continuous_with(
+        self,
+        config: &Config,
+    ) -> Result<MCP3425<I2C, D, ContinuousMode, DEV>, (Error<I2C::Error>, Self)> {
+        let previous = self.config;
+        let mut adc = self.into_continuous();
+        let result = adc
+            .check_channel(config)
+            .map_err(Error::from)
+            .and_then(|()| adc.write_config(config))
+            .and_then(|()| {
+                adc.wait_for_first_measurement(config).map_err(|e| {
+                    // The device is converting continuously by now
+                    let _ = adc.power_down();
+                    e
+                })
+            });
+        match result {
+            Ok(()) => Ok(adc),
+            Err(e) => {
+                let mut adc = adc.into_oneshot();
+                adc.config = previous;
+                Err((e, adc))
+            }
+        }
+    }
+
+    /// Do a one-shot voltage measurement.
+    ///
+    /// Return the result in millivolts.
+    ///
+    /// Every measurement writes the configuration register, even if the
+    /// configuration did not change: Writing it with the RDY bit set is the
+    /// only way to start a one-shot conversion of a single device (see
+    /// datasheet section 5.1.1), so the write also acts as the trigger. To
+    /// start conversions on several devices with a single command, see
+    /// [`general_call_conversion`](fn.general_call_conversion.html).
+    ///
+    /// Transient errors are retried according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    pub fn measure(&mut self, config: &Config) -> Result<Voltage, MeasureError<I2C::Error>> {
+        self.measure_as(config)
+    }
+
+    /// Do a one-shot measurement like
+    /// [`measure`](struct.MCP3425.html#method.measure), and convert the
+    /// result to a user-defined type, see the [`output`](output/index.html)
+    /// module.
+    pub fn measure_as<T: FromAdcCode>(
+        &mut self,
+        config: &Config,
+    ) -> Result<T, MeasureError<I2C::Error>> {
+        self.check_channel(config)?;
+        self.with_debounce(|adc| adc.measure_once(config))
+    }
+
+    /// Do a one-shot measurement and return the signed output code, without
+    /// converting it to a voltage.
+    ///
+    /// The code is returned as `i32`, since 18 bit codes don't fit into an
+    /// `i16`. The [`SaturationPolicy`](enum.SaturationPolicy.html) of the
+    /// configuration is not applied, saturated codes are returned as they are.
+    ///
+    /// Transient errors are retried according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    pub fn measure_raw(&mut self, config: &Config) -> Result<i32, MeasureError<I2C::Error>> {
+        let wait = self.wait;
+        self.check_channel(config)?;
+        self.measure_raw_once(config, wait)
+    }
+
+    /// Do a one-shot measurement and return the voltage together with the
+    /// output code and the configuration used.
+    ///
+    /// Transient errors are retried according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    pub fn measure_full(&mut self, config: &Config) -> Result<Reading, MeasureError<I2C::Error>> {
+        let wait = self.wait;
+        self.check_channel(config)?;
+        let raw_code = self.measure_checked(config, wait)?;
+        let config = self.calibrated(config);
+        Ok(Reading {
+            voltage: self.voltage(raw_code, &config)?,
+            raw_code,
+            config,
+            fresh: true,
+            sequence: self.sequence,
+        })
+    }
+
+    /// Do a one-shot voltage measurement, returning as soon as the result is
+    /// ready.
+    ///
+    /// This sleeps for the typical conversion time and then polls the RDY
+    /// bit every 100µs, regardless of the
+    /// [`WaitStrategy`](enum.WaitStrategy.html) of the driver. This shortens
+    /// the time per sample at the cost of more bus traffic.
+    ///
+    /// Transient errors are retried according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    pub fn measure_fast(&mut self, config: &Config) -> Result<Voltage, MeasureError<I2C::Error>> {
+        let wait = WaitStrategy::SleepThenPoll {
+            poll_interval_us: FAST_POLL_INTERVAL_US,
+        };
+        self.check_channel(config)?;
+        let measurement = self.measure_checked(config, wait)?;
+        self.voltage(measurement, &self.calibrated(config))
+    }
+
+    /// Do `n` consecutive one-shot measurements and return the mean voltage.
+    ///
+    /// The output codes are summed without overflow, and the mean is
+    /// calculated in microvolts, keeping the fraction of the LSB. If any
+    /// sample is saturated, the
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html) of the
+    /// configuration is applied to it, e.g. the default policy returns
+    /// [`MeasureError::VoltageTooHigh`](enum.MeasureError.html#variant.VoltageTooHigh)
+    /// instead of an average that is distorted by the saturated sample.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `n` is 0.
+    pub fn measure_avg(
+        &mut self,
+        config: &Config,
+        n: u16,
+    ) -> Result<Voltage, MeasureError<I2C::Error>> {
+        assert!(n > 0, "average of zero samples");
+        let wait = self.wait;
+        self.check_channel(config)?;
+        let mut sum = 0i64;
+        for _ in 0..n {
+            sum += i64::from(self.measure_checked(config, wait)?);
+        }
+        // Convert the mean without rounding it to a code first, then
+        // calibrate it
+        let mean_uv = mean_microvolts(sum, n, &config.resolution, &config.gain);
+        let calibrated = self.compensated(&self.calibrated(config));
+        Ok(voltage_from_microvolts(
+            calibrated
+                .calibration
+                .map_or(mean_uv, |calibration| calibration.apply_uv(mean_uv)),
+        ))
+    }
+
+    /// Do `n` consecutive one-shot measurements and append the voltages to
+    /// `voltages`.
+    ///
+    /// Any collection implementing `Extend` can be used, e.g. a
+    /// `heapless::Vec` in `no_std` environments. Note that
+    /// `heapless::Vec` panics if it is full.
+    ///
+    /// The first failing sample aborts the measurement, the voltages
+    /// measured before are kept in `voltages`. Transient errors are retried
+    /// per sample according to the [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// # use mcp3425::{Config, MCP3425};
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+    /// let mut voltages = heapless::Vec::<_, 8>::new();
+    /// adc.measure_extend(&Config::default(), 2, &mut voltages).unwrap();
+    /// assert_eq!(voltages.len(), 2);
+    /// # dev.done();
+    /// ```
+    pub fn measure_extend<C: Extend<Voltage>>(
+        &mut self,
+        config: &Config,
+        n: usize,
+        voltages: &mut C,
+    ) -> Result<(), MeasureError<I2C::Error>> {
+        for _ in 0..n {
+            voltages.extend(Some(self.measure(config)?));
+        }
+        Ok(())
+    }
+
+    /// Do `n` consecutive one-shot measurements and return the voltages.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Only available if the `alloc` Cargo feature is enabled.
+    #[cfg(feature = "alloc")]
+    pub fn measure_vec(
+        &mut self,
+        config: &Config,
+        n: usize,
+    ) -> Result<alloc::vec::Vec<Voltage>, MeasureError<I2C::Error>> {
+        let mut voltages = alloc::vec::Vec::with_capacity(n);
+        self.measure_extend(config, n, &mut voltages)?;
+        Ok(voltages)
+    }
+
+    /// Do `n` consecutive one-shot measurements and return their minimum,
+    /// maximum, mean and standard deviation, e.g. to characterize the noise
+    /// of an input.
+    ///
+    /// Saturated samples don't abort the measurement, the
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html) is not applied.
+    /// Instead, they are included with the voltage of their output code and
+    /// counted in `saturated_count`.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `n` is 0.
+    pub fn measure_stats(
+        &mut self,
+        config: &Config,
+        n: u16,
+    ) -> Result<stats::Stats, MeasureError<I2C::Error>> {
+        assert!(n > 0, "statistics of zero samples");
+        self.check_channel(config)?;
+        self.sample_stats(config, n, false)
+    }
+
+    /// Measure the offset of the shorted or grounded input selected by
+    /// `config`, and return `config` with a calibration that corrects it to
+    /// zero. Use the returned configuration for subsequent measurements.
+    ///
+    /// This averages `n` samples at 16 bits with the gain and channel of
+    /// `config`, which cancels the offset of the device (±0.5 LSB typical)
+    /// together with offsets of the board. The gain correction of an
+    /// existing calibration is kept, its offset is replaced. With
+    /// [tempco compensation](struct.MCP3425.html#method.set_tempco), the
+    /// offset is referred to the reference temperature of the tempco.
+    ///
+    /// A saturated sample means that the input is not shorted, so it always
+    /// aborts the calibration with
+    /// [`MeasureError::VoltageTooHigh`](enum.MeasureError.html#variant.VoltageTooHigh)
+    /// or `VoltageTooLow`, regardless of the
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html). Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `n` is 0.
+    pub fn calibrate_offset(
+        &mut self,
+        config: &Config,
+        n: u16,
+    ) -> Result<Config, MeasureError<I2C::Error>> {
+        assert!(n > 0, "calibration with zero samples");
+        let sample_config = Config {
+            resolution: Resolution::Bits16Sps15,
+            saturation: SaturationPolicy::Error,
+            ..*config
+        };
+        let mut sum = 0i64;
+        self.check_channel(config)?;
+        self.sample_codes(&sample_config, n, true, |code, _| sum += i64::from(code))?;
+        let offset_uv = mean_microvolts(sum, n, &sample_config.resolution, &config.gain);
+        let calibration = config.calibration.unwrap_or_default().zeroed_at(offset_uv);
+        // The offset was measured at the current temperature, refer it back to
+        // the reference temperature of the tempco
+        let calibration = match (self.tempco, self.temperature) {
+            (Some(tempco), Some(temperature)) => {
+                tempco.uncompensate(&calibration, temperature.read_mc())
+            }
+            _ => calibration,
+        };
+        Ok(config.with_calibration(calibration))
+    }
+
+    /// Do `n` consecutive one-shot measurements and return the RMS of the
+    /// AC component in microvolts, e.g. to quantify mains ripple or an AC
+    /// current through a shunt.
+    ///
+    /// The DC mean is removed first, so this is the standard deviation of
+    /// the samples, calculated with integer math. The samples should cover
+    /// whole periods of the signal, and the sample rate must be high enough
+    /// for it.
+    ///
+    /// If any sample is saturated, the
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html) of the configuration
+    /// is applied to it, e.g. the default policy returns
+    /// [`MeasureError::VoltageTooHigh`](enum.MeasureError.html#variant.VoltageTooHigh)
+    /// instead of a clipped RMS value.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `n` is 0.
+    pub fn measure_rms(
+        &mut self,
+        config: &Config,
+        n: u16,
+    ) -> Result<u32, MeasureError<I2C::Error>> {
+        assert!(n > 0, "RMS of zero samples");
+        self.check_channel(config)?;
+        self.sample_stats(config, n, true)
+            .map(|stats| stats.std_dev_uv)
+    }
+
+    /// Do `n` consecutive one-shot measurements of a presumed-stable input
+    /// and report the noise in output codes, e.g. to validate the analog
+    /// layout during bring-up.
+    ///
+    /// The report includes the peak-to-peak noise, the standard deviation
+    /// and the estimated effective number of bits. Saturated samples are
+    /// handled according to the
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html) of the
+    /// configuration, so by default, they fail the measurement.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `n` is 0.
+    pub fn measure_noise(
+        &mut self,
+        config: &Config,
+        n: u16,
+    ) -> Result<stats::NoiseReport, MeasureError<I2C::Error>> {
+        assert!(n > 0, "noise of zero samples");
+        let mut acc = stats::StatsAccumulator::new();
+        self.check_channel(config)?;
+        self.sample_codes(config, n, true, |code, _| acc.push(code, false))?;
+        Ok(acc
+            .noise_report(config.resolution.res_bits())
+            .expect("no samples"))
+    }
+
+    /// Do `n` consecutive one-shot measurements and return their
+    /// statistics. If `check_saturation` is set, the saturation policy is
+    /// applied to every sample.
+    fn sample_stats(
+        &mut self,
+        config: &Config,
+        n: u16,
+        check_saturation: bool,
+    ) -> Result<stats::Stats, MeasureError<I2C::Error>> {
+        let mut acc = stats::StatsAccumulator::new();
+        let calibrated = self.compensated(&self.calibrated(config));
+        self.sample_codes(config, n, check_saturation, |code, saturated| {
+            acc.push(input_microvolts(code, &calibrated), saturated)
+        })?;
+        Ok(acc.finish().expect("no samples"))
+    }
+
+    /// Do `n` consecutive one-shot measurements and pass every output code
+    /// to `sample`, together with whether it is saturated. If
+    /// `check_saturation` is set, the saturation policy is applied to every
+    /// sample.
+    fn sample_codes(
+        &mut self,
+        config: &Config,
+        n: u16,
+        check_saturation: bool,
+        mut sample: impl FnMut(i32, bool),
+    ) -> Result<(), MeasureError<I2C::Error>> {
+        let wait = self.wait;
+        let resolution = &config.resolution;
+        for _ in 0..n {
+            let code = if check_saturation {
+                self.measure_checked(config, wait)?
+            } else {
+                self.measure_raw_once(config, wait)?
+            };
+            sample(code, code == resolution.max() || code == resolution.min());
+        }
+        Ok(())
+    }
+
+    /// Do `N` consecutive one-shot measurements and return the median
+    /// voltage, to reject single-sample spikes e.g. from relay switching.
+    ///
+    /// Saturated samples are treated like any other sample, so a single
+    /// saturated spike is rejected as well. The
+    /// [`SaturationPolicy`](enum.SaturationPolicy.html) is only applied if
+    /// the median itself is saturated.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// `N` must be odd, an even `N` fails to compile.
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// use mcp3425::{Config, MCP3425};
+    ///
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+    /// let voltage = adc.measure_median::<3>(&Config::default()).unwrap();
+    /// # dev.done();
+    /// ```
+    pub fn measure_median<const N: usize>(
+        &mut self,
+        config: &Config,
+    ) -> Result<Voltage, MeasureError<I2C::Error>> {
+        let () = OddSampleCount::<N>::ASSERT;
+        let wait = self.wait;
+        self.check_channel(config)?;
+        let mut codes = [0i32; N];
+        for code in codes.iter_mut() {
+            *code = self.measure_raw_once(config, wait)?;
+        }
+        codes.sort_unstable();
+        self.voltage(codes[N / 2], &self.calibrated(config))
+    }
+
+    /// Do `n` consecutive one-shot measurements, discard the `discard`
+    /// highest and the `discard` lowest samples and return the mean of the
+    /// remaining ones.
+    ///
+    /// This trimmed mean rejects occasional glitches like
+    /// [`measure_median`](struct.MCP3425.html#method.measure_median), but
+    /// only keeps the `2 * discard` extreme samples in memory, so it is
+    /// cheaper for a large `n`. Saturated samples are treated like any other
+    /// sample. The [`SaturationPolicy`](enum.SaturationPolicy.html) is only
+    /// applied if the trimmed mean itself is saturated.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](struct.RetryPolicy.html).
+    ///
+    /// Panics if `discard` is larger than 16, or if `n` is not larger than
+    /// `2 * discard`.
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// use mcp3425::{Config, MCP3425};
+    ///
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+    /// // Discard the highest and the lowest of three samples
+    /// let voltage = adc.measure_trimmed(&Config::default(), 3, 1).unwrap();
+    /// # dev.done();
+    /// ```
+    pub fn measure_trimmed(
+        &mut self,
+        config: &Config,
+        n: u16,
+        discard: u16,
+    ) -> Result<Voltage, MeasureError<I2C::Error>> {
+        assert!(discard <= MAX_TRIMMED_DISCARD, "too many discarded samples");
+        assert!(
+            u32::from(n) > 2 * u32::from(discard),
+            "too few samples to discard"
+        );
+        // The lowest samples in ascending order, the highest in descending
+        // order
+        let k = usize::from(discard);
+        let mut lowest_buf = [i32::MAX; MAX_TRIMMED_DISCARD as usize];
+        let mut highest_buf = [i32::MIN; MAX_TRIMMED_DISCARD as usize];
+        let lowest = &mut lowest_buf[..k];
+        let highest = &mut highest_buf[..k];
+        let mut sum = 0i64;
+        self.check_channel(config)?;
+        self.sample_codes(config, n, false, |code, _| {
+            sum += i64::from(code);
+            keep_extreme(lowest, code, |a, b| a < b);
+            keep_extreme(highest, code, |a, b| a > b);
+        })?;
+        let discarded: i64 = lowest
+            .iter()
+            .chain(highest.iter())
+            .map(|&c| i64::from(c))
+            .sum();
+        let count = i64::from(n) - 2 * i64::from(discard);
+        // The mean is within the range of the codes, so it fits
+        self.voltage(((sum - discarded) / count) as i32, &self.calibrated(config))
+    }
+
+    /// Measure the first `N` input channels one after another, with the
+    /// resolution and gain of `config`.
+    ///
+    /// The channel of `config` is ignored. If the device model does not have
+    /// `N` channels,
+    /// [`MeasureError::ChannelNotSupported`](enum.MeasureError.html#variant.ChannelNotSupported)
+    /// is returned before measuring. The first failing measurement aborts
+    /// the sequence.
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// use mcp3425::{model::Mcp3426, Config, MCP3425};
+    ///
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10100000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay).with_model(Mcp3426);
+    /// let [ch1, ch2] = adc.measure_all(&Config::default()).unwrap();
+    /// # dev.done();
+    /// ```
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    pub fn measure_all<const N: usize>(
+        &mut self,
+        config: &Config,
+    ) -> Result<[ChannelReading; N], MeasureError<I2C::Error>> {
+        self.measure_channels(|channel| config.with_channel(channel))
+    }
+
+    /// Capture the first `N` input channels as quickly as possible, using
+    /// the fastest resolution and the gain at the same index of `gains`.
+    ///
+    /// Each reading comes with its nominal acquisition offset relative to
+    /// the first channel, i.e. the skew between correlated signals like
+    /// voltage and current. The offsets are based on the typical
+    /// [conversion time](enum.Resolution.html#method.conversion_time_us)
+    /// and don't include the I²C transfers. Errors are handled like in
+    /// [`measure_all`](#method.measure_all).
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// use core::time::Duration;
+    /// use mcp3425::{model::Mcp3427, Gain, MCP3425};
+    ///
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// #     Transaction::write(0x68, vec![0b10100011]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay).with_model(Mcp3427);
+    /// let [voltage, current] = adc.snapshot([Gain::Gain1, Gain::Gain8]).unwrap();
+    /// assert_eq!(current.offset, Duration::from_micros(4_000));
+    /// # dev.done();
+    /// ```
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    pub fn snapshot<const N: usize>(
+        &mut self,
+        gains: [Gain; N],
+    ) -> Result<[SnapshotReading; N], MeasureError<I2C::Error>> {
+        let resolution = Resolution::Bits12Sps240;
+        let readings = self.measure_channels::<N>(|channel| {
+            Config::new(resolution, gains[usize::from(channel.index())], channel)
+        })?;
+        let step_us = u64::from(resolution.conversion_time_us());
+        Ok(readings.map(|reading| SnapshotReading {
+            channel: reading.channel,
+            voltage: reading.voltage,
+            offset: Duration::from_micros(u64::from(reading.channel.index()) * step_us),
+        }))
+    }
+
+    /// Measure the first `N` input channels one after another, with the
+    /// configuration returned by `config_for` for each channel.
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    fn measure_channels<const N: usize>(
+        &mut self,
+        mut config_for: impl FnMut(Channel) -> Config,
+    ) -> Result<[ChannelReading; N], MeasureError<I2C::Error>> {
+        if N > usize::from(DEV::CHANNELS) {
+            return Err(MeasureError::ChannelNotSupported);
+        }
+        let mut error = None;
+        let readings: [Option<ChannelReading>; N] = array::from_fn(|index| {
+            if error.is_some() {
+                return None;
+            }
+            let channel = u8::try_from(index)
+                .ok()
+                .and_then(|index| Channel::try_from(index).ok());
+            let result = match channel {
+                Some(channel) => self
+                    .measure(&config_for(channel))
+                    .map(|voltage| ChannelReading { channel, voltage }),
+                None => Err(MeasureError::ChannelNotSupported),
+            };
+            result.map_err(|e| error = Some(e)).ok()
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+        Ok(readings.map(|reading| match reading {
+            Some(reading) => reading,
+            None => unreachable!(),
+        }))
+    }
+
+    /// Return an iterator that measures every channel of the device model
+    /// in turn, with the resolution and gain of `config`.
+    ///
+    /// Only channels that are enabled with Cargo features are measured.
+    ///
+    /// ```
+    /// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+    /// use mcp3425::{model::Mcp3425, Config, MCP3425};
+    ///
+    /// # let mut dev = Mock::new(&[
+    /// #     Transaction::write(0x68, vec![0b10000000]),
+    /// #     Transaction::read(0x68, vec![0, 0, 0]),
+    /// # ]);
+    /// # let i2c = dev.clone();
+    /// let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay).with_model(Mcp3425);
+    /// for (channel, result) in adc.sweep(&Config::default()) {
+    ///     // ...
+    /// #   let _ = (channel, result);
+    /// }
+    /// # dev.done();
+    /// ```
+    pub fn sweep(&mut self, config: &Config) -> sequence::Sweep<'_, I2C, D, DEV> {
+        sequence::Sweep::new(self, *config)
+    }
+
+    /// Measure every entry of `sequence` in order, with its configuration.
+    ///
+    /// A failing entry does not stop the remaining measurements. The results
+    /// are returned in the order of the entries.
+    pub fn run_scan<const N: usize>(
+        &mut self,
+        sequence: &sequence::ScanSequence<N>,
+    ) -> [Result<ChannelReading, MeasureError<I2C::Error>>; N] {
+        sequence.configs().map(|config| {
+            self.measure(&config).map(|voltage| ChannelReading {
+                channel: config.channel,
+                voltage,
+            })
+        })
+    }
+
+    /// Do a single one-shot voltage measurement.
+    fn measure_once<T: FromAdcCode>(
+        &mut self,
+        config: &Config,
+    ) -> Result<T, MeasureError<I2C::Error>> {
+        let measurement = self.measure_raw_once(config, self.wait)?;
+
+        // Calculate voltage from raw value
+        self.convert(measurement, &self.calibrated(config))
+    }
+
+    /// Do a one-shot measurement and return the output code, applying the
+    /// saturation policy of `config` after the saturation debounce.
+    fn measure_checked(
+        &mut self,
+        config: &Config,
+        wait: WaitStrategy,
+    ) -> Result<i32, MeasureError<I2C::Error>> {
+        self.with_debounce(|adc| {
+            let code = adc.measure_raw_once(config, wait)?;
+            calculate_voltage::<MeasureError<_>>(code, config)?;
+            Ok(code)
+        })
+    }
+
+    /// Do a single one-shot measurement and return the output code.
+    ///
+    /// Transient errors are retried according to the retry policy. Once the
+    /// conversion was started, retries re-read its result instead of
+    /// starting another conversion.
+    fn measure_raw_once(
+        &mut self,
+        config: &Config,
+        wait: WaitStrategy,
+    ) -> Result<i32, MeasureError<I2C::Error>> {
+        let command = ConfigRegister::NOT_READY.bits() | self.mode.bits() | config.bits();
+        let max_us = config.resolution.max_conversion_time().as_micros() as u32;
+        let (waited_us, poll_interval_us, timeout_us) = match wait {
+            WaitStrategy::Polled => (
+                config.resolution.conversion_time_us(),
+                READY_POLL_INTERVAL_US,
+                max_us,
+            ),
+            WaitStrategy::WorstCaseSleep => (max_us, 0, max_us),
+            WaitStrategy::SleepThenPoll { poll_interval_us } => (
+                config.resolution.conversion_time_us(),
+                poll_interval_us,
+                max_us,
+            ),
+            // The conversion is started here, so it can't be paced
+            // externally: Wait for the typical conversion time, read once
+            WaitStrategy::UserPaced => {
+                let conversion_time_us = config.resolution.conversion_time_us();
+                (conversion_time_us, 0, conversion_time_us)
+            }
+        };
+
+        let mut started = false;
+        self.with_retries(|adc| {
+            let waited_us = if started {
+                // Read the result of the running conversion again
+                timeout_us
+            } else {
+                // Send command
+                adc.i2c
+                    .write_command(adc.address, command)
+                    .map_err(MeasureError::I2c)?;
+                started = true;
+
+                // Wait for the conversion to finish
+                adc.delay.delay_us(waited_us);
+                waited_us
+            };
+
+            // Read result, making sure that the delay was sufficient
+            adc.poll_until_ready(&config.resolution, waited_us, poll_interval_us, timeout_us)?
+                .ok_or(MeasureError::NotReady)
+        })
+    }
+
+    /// Write the specified configuration to the device without starting a
+    /// conversion.
+    ///
+    /// The conversion can then be started with a
+    /// [`general_call_conversion`](fn.general_call_conversion.html), and the
+    /// result read with
+    /// [`read_prepared`](struct.MCP3425.html#method.read_prepared).
+    pub fn prepare(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        self.check_channel(config)?;
+        let command = self.mode.bits() | config.bits();
+        self.i2c
+            .write_command(self.address, command)
+            .map_err(Error::I2c)?;
+        self.config = Some(self.calibrated(config));
+        Ok(())
+    }
+
+    /// Read the result of a conversion that was started externally, using
+    /// the configuration written by
+    /// [`prepare`](struct.MCP3425.html#method.prepare).
+    ///
+    /// If `prepare` has not been called before,
+    /// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized) is
+    /// returned. If the conversion is still running,
+    /// [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady) is returned.
+    pub fn read_prepared(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+        let (measurement, config_reg) = self.read_code_and_config(&config.resolution)?;
+        if !config_reg.is_ready() {
+            return Err(ReadError::NotReady);
+        }
+        self.voltage(measurement, &config)
+    }
+
+    /// Like [`read_prepared`](struct.MCP3425.html#method.read_prepared), but
+    /// poll the RDY bit until the maximum conversion time has passed.
+    ///
+    /// `waited_us` is the time already waited since the conversion was
+    /// started.
+    fn poll_prepared(&mut self, waited_us: u32) -> Result<Voltage, ReadError<I2C::Error>> {
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+        let max_us = config.resolution.max_conversion_time().as_micros() as u32;
+        let measurement = self
+            .poll_until_ready(
+                &config.resolution,
+                waited_us,
+                READY_POLL_INTERVAL_US,
+                max_us,
+            )?
+            .ok_or(ReadError::NotReady)?;
+        self.voltage(measurement, &config)
+    }
+
+    /// Start a one-shot conversion without waiting for it.
+    ///
+    /// Return the time after which the result is ready (see
+    /// [`Config::time_until_ready`](struct.Config.html#method.time_until_ready)).
+    /// In the meantime, other devices can be accessed. Then read the result
+    /// with [`harvest`](struct.MCP3425.html#method.harvest).
+    pub fn arm(&mut self, config: &Config) -> Result<Duration, MeasureError<I2C::Error>> {
+        self.check_channel(config)?;
+        let command = ConfigRegister::NOT_READY.bits() | self.mode.bits() | config.bits();
+        self.i2c
+            .write_command(self.address, command)
+            .map_err(MeasureError::I2c)?;
+        self.config = Some(self.calibrated(config));
+        self.armed = self.config;
+        Ok(Duration::from_micros(config.time_until_ready().into()))
+    }
+
+    /// Read the result of the conversion started with
+    /// [`arm`](struct.MCP3425.html#method.arm).
+    ///
+    /// Every conversion can only be harvested once: If nothing is armed,
+    /// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized)
+    /// is returned. If the conversion is still running,
+    /// [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady) is
+    /// returned and it can be harvested again later.
+    ///
+    /// No deadline is recorded when arming: The conversion time depends on
+    /// the internal oscillator of the device, so the RDY bit is read to find
+    /// out whether the result is ready, even before the duration returned by
+    /// `arm` has passed.
+    pub fn harvest(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        let config = self.armed.ok_or(ReadError::NotInitialized)?;
+        let (measurement, config_reg) = self.read_code_and_config(&config.resolution)?;
+        if !config_reg.is_ready() {
+            return Err(ReadError::NotReady);
+        }
+        self.armed = None;
+        self.voltage(measurement, &config)
+    }
+}
+
+impl<I2C, D> MCP3425<I2C, D, ContinuousMode>
+where
+    I2C: Transport,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in Continuous Measurement mode.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn continuous(i2c: I2C, address: u8, delay: D) -> Self {
+        MCP3425::new(i2c, address, delay, ContinuousMode)
+    }
+}
+
+impl<I2C, D, DEV> MCP3425<I2C, D, ContinuousMode, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    /// Write the specified configuration to the device like
+    /// [`set_config`](struct.MCP3425.html#method.set_config), and return a
+    /// driver in [`ConfiguredContinuousMode`](struct.ConfiguredContinuousMode.html).
+    ///
+    /// If writing the configuration fails, the error is returned together
+    /// with the unchanged driver.
+    #[allow(clippy::type_complexity, clippy::result_large_err)]
+    pub fn configure(
+        mut self,
+        config: &Config,
+    ) -> Result<MCP3425<I2C, D, ConfiguredContinuousMode, DEV>, (Error<I2C::Error>, Self)> {
+        if let Err(e) = self.set_config(config) {
+            return Err((e, self));
+        }
+        let mut adc = self.into_mode(ConfiguredContinuousMode);
+        adc.config = Some(adc.calibrated(config));
+        Ok(adc)
+    }
+
+    /// Like [`set_config`](struct.MCP3425.html#method.set_config), but read
+    /// the configuration register back after writing it.
+    ///
+    /// If the device did not latch the expected configuration,
+    /// [`Error::ConfigMismatch`](enum.Error.html#variant.ConfigMismatch) is
+    /// returned, and the configuration must be written again before reading
+    /// measurements.
+    pub fn set_config_verified(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        self.check_channel(config)?;
+        self.write_config(config)?;
+
+        // The configuration register is the 4th byte at all resolutions: at
+        // 12 to 16 bits, the device repeats it after the 3rd byte.
+        let mut buf = [0; 4];
+        self.i2c
+            .read_data(self.address, &mut buf)
+            .map_err(Error::I2c)?;
+        let expected = config.to_byte(&self.mode);
+        let actual = buf[3] & !ConfigRegister::NOT_READY.bits();
+        if actual != expected {
+            self.config = None;
+            return Err(Error::ConfigMismatch { expected, actual });
+        }
+
+        self.wait_for_first_measurement(config)
+    }
+}
+
+impl<I2C, D, DEV> AdcReader for MCP3425<I2C, D, OneShotMode, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    type Error = Error<I2C::Error>;
+
+    fn read(&mut self, config: &Config) -> Result<Voltage, Self::Error> {
+        Ok(self.measure(config)?)
+    }
+}
+
+impl<I2C, D, M, DEV> AdcReader for MCP3425<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    type Error = Error<I2C::Error>;
+
+    fn read(&mut self, config: &Config) -> Result<Voltage, Self::Error> {
+        if self.config == Some(self.calibrated(config)) {
+            return Ok(self.read_measurement()?);
+        }
+        // Waiting for the first measurement already read the result, so it
+        // is not fresh anymore
+        self.set_config(config)?;
+        Ok(self.read_measurement_full()?.voltage)
+    }
+}
+
+impl<I2C, D, DEV> MCP3425<I2C, D, ConfiguredContinuousMode, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    /// Return the configuration that was last written to the device.
+    pub fn config(&self) -> &Config {
+        self.config
+            .as_ref()
+            .expect("configured driver without configuration")
+    }
+
+    /// Change the conversion mode back to
+    /// [`ContinuousMode`](struct.ContinuousMode.html). The device keeps
+    /// converting with the current configuration.
+    pub fn into_unconfigured(self) -> MCP3425<I2C, D, ContinuousMode, DEV> {
+        let config = self.config;
+        let mut adc = self.into_mode(ContinuousMode);
+        adc.config = config;
+        adc
+    }
+}
+
+impl<I2C, D, M, DEV> MCP3425<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    /// Change the conversion mode to one-shot.
+    ///
+    /// This conversion is side-effect free, so it will not write any
+    /// configuration to the device until a first one-shot measurement is
+    /// triggered.
+    pub fn into_oneshot(self) -> MCP3425<I2C, D, OneShotMode, DEV> {
+        self.into_mode(OneShotMode)
+    }
+
+    /// Change the conversion mode to one-shot and write the specified
+    /// configuration to the device, like
+    /// [`prepare`](struct.MCP3425.html#method.prepare).
+    ///
+    /// This stops the continuous conversions right away, instead of only with
+    /// the first one-shot measurement. If writing the configuration fails,
+    /// the error is returned together with the unchanged continuous driver.
+    #[allow(clippy::type_complexity, clippy::result_large_err)]
+    pub fn into_oneshot_with(
+        mut self,
+        config: &Config,
+    ) -> Result<MCP3425<I2C, D, OneShotMode, DEV>, (Error<I2C::Error>, Self)> {
+        let command = OneShotMode.bits() | config.bits();
+        let result = self
+            .check_channel(config)
+            .map_err(Error::from)
+            .and_then(|()| {
+                self.i2c
+                    .write_command(self.address, command)
+                    .map_err(Error::I2c)
+            });
+        if let Err(e) = result {
+            return Err((e, self));
+        }
+        let mut adc = self.into_oneshot();
+        adc.config = Some(adc.calibrated(config));
+        Ok(adc)
+    }
+
+    /// Write the specified configuration to the device and block until the
+    /// first measurement is ready.
+    ///
+    /// The wait-for-measurement logic is implemented with polling, since there
+    /// are no non-blocking `embedded_hal` traits yet.
+    ///
+    /// Note: Since the wait-until-ready logic needs to read the data register,
+    /// when reading the measurement immediately after setting the
+    /// configuration, that measurement will be returned as `NotFresh`.
+    ///
+    /// If the device does not become ready within the timeout set with
+    /// [`set_ready_timeout`](struct.MCP3425.html#method.set_ready_timeout),
+    /// [`Error::Timeout`](enum.Error.html#variant.Timeout) is returned.
+    pub fn set_config(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        self.check_channel(config)?;
+        self.write_config(config)?;
+        self.wait_for_first_measurement(config)
+    }
+
+    /// Write the specified configuration to the device and return
+    /// immediately, without waiting for the first measurement.
+    ///
+    /// The first result is available after
+    /// [`Config::time_until_ready`](struct.Config.html#method.time_until_ready).
+    /// Reading earlier returns the result of the previous configuration, or
+    /// [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady).
+    pub fn set_config_nowait(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        self.check_channel(config)?;
+        self.write_config(config)
+    }
+
+    /// Put the device into its low-power standby state.
+    ///
+    /// This writes a one-shot configuration without starting a conversion,
+    /// so the device stops converting after the current conversion. The
+    /// configuration is kept by the driver, use
+    /// [`resume`](struct.MCP3425.html#method.resume) to restart the
+    /// continuous conversions.
+    pub fn power_down(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.unwrap_or_default();
+        self.i2c
+            .write_command(self.address, OneShotMode.bits() | config.bits())
+            .map_err(Error::I2c)
+    }
+
+    /// Wrap the driver, so that the device is put into standby with
+    /// [`power_down`](struct.MCP3425.html#method.power_down) when it is
+    /// dropped. See the [`standby`](standby/index.html) module.
+    pub fn power_down_on_drop(self) -> standby::PowerDownOnDrop<I2C, D, M, DEV> {
+        standby::PowerDownOnDrop::new(self)
+    }
+
+    /// Restart the continuous conversions after
+    /// [`power_down`](struct.MCP3425.html#method.power_down), with the
+    /// configuration that was used before.
+    ///
+    /// Like [`set_config`](struct.MCP3425.html#method.set_config), this
+    /// blocks until the first measurement is ready. If no configuration was
+    /// written before,
+    /// [`Error::NotInitialized`](enum.Error.html#variant.NotInitialized) is
+    /// returned.
+    pub fn resume(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.ok_or(Error::NotInitialized)?;
+        self.set_config(&config)
+    }
+
+    /// Write the configuration register.
+    fn write_config(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        let command = self.mode.bits() | config.bits();
+        self.i2c
+            .write_command(self.address, command)
+            .map(|()| self.config = Some(self.calibrated(config)))
+            .map_err(Error::I2c)
+    }
+
+    /// Block until the first measurement after a configuration change is
+    /// ready.
+    fn wait_for_first_measurement(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
+        let max_us = config.resolution.max_conversion_time().as_micros() as u32;
+        let (waited_us, poll_interval_us) = match self.wait {
+            WaitStrategy::Polled => (config.resolution.conversion_time_us(), 1_000),
+            WaitStrategy::WorstCaseSleep => (max_us, 0),
+            WaitStrategy::SleepThenPoll { poll_interval_us } => {
+                (config.resolution.conversion_time_us(), poll_interval_us)
+            }
+            WaitStrategy::UserPaced => return Ok(()),
+        };
+
+        // Wait for first measurement
+        self.delay.delay_us(waited_us);
+
+        // Poll until ready, or until the device is considered wedged
+        let timeout_us = match self.wait {
+            WaitStrategy::WorstCaseSleep => max_us,
+            _ => {
+                let timeout = self
+                    .ready_timeout
+                    .unwrap_or(config.resolution.max_conversion_time() * 2);
+                u32::try_from(timeout.as_micros()).unwrap_or(u32::MAX)
+            }
+        };
+        self.poll_until_ready::<Error<_>>(
+            &config.resolution,
+            waited_us,
+            poll_interval_us,
+            timeout_us,
+        )?
+        .map(|_| ())
+        .ok_or(Error::Timeout)
+    }
+
+    /// Read a measurement from the device.
+    ///
+    /// Note that the [`set_config`](struct.MCP3425.html#method.set_config)
+    /// method MUST have been called before, otherwise
+    /// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized) will
+    /// be returned.
+    ///
+    /// If you poll faster than the sample rate,
+    /// [`ReadError::NotReady`](enum.ReadError.html#variant.NotReady) will be returned,
+    /// unless a [`RetryPolicy`](struct.RetryPolicy.html) is set.
+    pub fn read_measurement(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        self.read_measurement_as()
+    }
+
+    /// Read a measurement like
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement), and
+    /// convert the result to a user-defined type, see the
+    /// [`output`](output/index.html) module.
+    pub fn read_measurement_as<T: FromAdcCode>(&mut self) -> Result<T, ReadError<I2C::Error>> {
+        let mut first = true;
+        self.with_debounce(|adc| {
+            if core::mem::take(&mut first) {
+                adc.with_retries(|adc| adc.read_measurement_once())
+            } else {
+                // The last result was saturated, wait for the next one. Stale
+                // results are skipped before they are checked for saturation.
+                let config = adc.config.ok_or(ReadError::NotInitialized)?;
+                adc.read_paced(config.resolution.conversion_time_us(), &mut |adc| {
+                    let code = adc.read_measurement_raw_once()?;
+                    adc.convert(code, &config)
+                })
+            }
+        })
+    }
+
+    /// Read the latest measurement from the device without checking whether
+    /// it is a new result.
+    ///
+    /// Only the output code is read, the configuration register with the
+    /// "Not Ready" flag is skipped. This saves a byte of bus time per sample,
+    /// but the same result may be returned more than once if you poll faster
+    /// than the sample rate. Use this only if reads are paced externally.
+    ///
+    /// If [`set_config`](struct.MCP3425.html#method.set_config) has not been
+    /// called before,
+    /// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized) is
+    /// returned.
+    pub fn read_measurement_unchecked(&mut self) -> Result<Voltage, ReadError<I2C::Error>> {
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+        let mut buf = [0; 3];
+        let buf = &mut buf[..config.resolution.read_len() - 1];
+        self.i2c
+            .read_data(self.address, buf)
+            .map_err(ReadError::I2c)?;
+        self.voltage(decode_code(buf), &config)
+    }
+
+    /// Read a measurement from the device and return the signed output code,
+    /// without converting it to a voltage.
+    ///
+    /// Like [`measure_raw`](struct.MCP3425.html#method.measure_raw), the code
+    /// is returned as `i32` and saturated codes are not treated as errors.
+    /// Otherwise, this behaves like
+    /// [`read_measurement`](struct.MCP3425.html#method.read_measurement).
+    pub fn read_measurement_raw(&mut self) -> Result<i32, ReadError<I2C::Error>> {
+        self.with_retries(|adc| adc.read_measurement_raw_once())
+    }
+
+    /// Read the latest measurement from the device and return the voltage
+    /// together with the output code and the configuration used.
+    ///
+    /// Unlike [`read_measurement`](struct.MCP3425.html#method.read_measurement),
+    /// a result that was already read before is not an error, but returned
+    /// with [`fresh`](struct.Reading.html#structfield.fresh) set to `false`.
+    /// If [`set_config`](struct.MCP3425.html#method.set_config) has not been
+    /// called before,
+    /// [`ReadError::NotInitialized`](enum.ReadError.html#variant.NotInitialized) is
+    /// returned.
+    pub fn read_measurement_full(&mut self) -> Result<Reading, ReadError<I2C::Error>> {
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+        let (raw_code, config_reg) = self.read_code_and_config(&config.resolution)?;
+        Ok(Reading {
+            voltage: self.voltage(raw_code, &config)?,
+            raw_code,
+            config,
+            fresh: config_reg.is_ready(),
+            sequence: self.sequence,
+        })
+    }
+
+    /// Do a single attempt at reading the output code from the device.
+    fn read_measurement_raw_once(&mut self) -> Result<i32, ReadError<I2C::Error>> {
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+        let (measurement, config_reg) = self.read_code_and_config(&config.resolution)?;
+        if config_reg.is_ready() {
+            Ok(measurement)
+        } else {
+            Err(ReadError::NotReady)
+        }
+    }
+
+    /// Do a single attempt at reading a measurement from the device.
+    fn read_measurement_once<T: FromAdcCode>(&mut self) -> Result<T, ReadError<I2C::Error>> {
+        // Make sure that the configuration has been written to the device
+        let config = self.config.ok_or(ReadError::NotInitialized)?;
+
+        // Read measurement and config register
+        let (measurement, config_reg) = self.read_code_and_config(&config.resolution)?;
+
+        // Check "Not Ready" flag before looking at the value, so that a stale
+        // saturated result is reported as not ready. See datasheet section
+        // 5.1.1 for more details.
+        if !config_reg.is_ready() {
+            // The "Not Ready" flag is set. This means the conversion
+            // result is not updated since the last reading. A new
+            // conversion is under processing and the RDY bit will be
+            // cleared when the new conversion result is ready.
+            return Err(ReadError::NotReady);
+        }
+
+        // Calculate voltage from raw value
+        self.convert(measurement, &config)
+    }
+
+    /// Write the specified configuration to the device and fill `voltages`
+    /// with consecutive measurements, e.g. for burst captures.
+    ///
+    /// Every value is read as soon as the next conversion has finished, so
+    /// all values are fresh samples, spaced by the sample rate of the
+    /// configured resolution. If a result does not become ready within three
+    /// conversion times (one conversion time, then polls for two more),
+    /// [`Error::NotReady`](enum.Error.html#variant.NotReady) is returned.
+    pub fn measure_into(
+        &mut self,
+        config: &Config,
+        voltages: &mut [Voltage],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.fill_paced(config, voltages, Self::read_measurement_once)
+    }
+
+    /// Write the specified configuration to the device and fill `codes` with
+    /// the signed output codes of consecutive measurements.
+    ///
+    /// Like [`measure_raw`](struct.MCP3425.html#method.measure_raw), saturated
+    /// codes are not treated as errors. Otherwise, this behaves like
+    /// [`measure_into`](struct.MCP3425.html#method.measure_into).
+    pub fn measure_into_raw(
+        &mut self,
+        config: &Config,
+        codes: &mut [i32],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.fill_paced(config, codes, Self::read_measurement_raw_once)
+    }
+
+    /// Write the configuration and fill `values` with consecutive fresh
+    /// results of `read`.
+    fn fill_paced<T>(
+        &mut self,
+        config: &Config,
+        values: &mut [T],
+        mut read: impl FnMut(&mut Self) -> Result<T, ReadError<I2C::Error>>,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_config(config)?;
+        let conversion_time_us = config.resolution.conversion_time_us();
+        for value in values.iter_mut() {
+            *value = self.read_paced(conversion_time_us, &mut read)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the next conversion, then poll `read` until it is ready, for
+    /// up to two more conversion times.
+    fn read_paced<T>(
+        &mut self,
+        conversion_time_us: u32,
+        read: &mut impl FnMut(&mut Self) -> Result<T, ReadError<I2C::Error>>,
+    ) -> Result<T, ReadError<I2C::Error>> {
+        self.delay.delay_us(conversion_time_us);
+        let mut polls = 0;
+        loop {
+            match read(self) {
+                Err(ReadError::NotReady) if polls < MAX_PACING_POLLS => {
+                    polls += 1;
+                    self.delay.delay_us(conversion_time_us / 16);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Transactions of one-shot measurements that return the given output
+    /// codes. `config` is the configuration register without the RDY bit.
+    pub(crate) fn oneshot_expectations(addr: u8, config: u8, codes: &[i16]) -> Vec<Transaction> {
+        codes
+            .iter()
+            .flat_map(|code| {
+                let [high, low] = code.to_be_bytes();
+                [
+                    Transaction::write(addr, vec![0b10000000 | config]),
+                    Transaction::read(addr, vec![high, low, config]),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_wrapper() {
+        let a = Voltage::from_millivolts(2500);
+        assert_eq!(a.as_millivolts(), 2500i16);
+        assert_eq!(a.as_volts(), 2.5f32);
+
+        let b = Voltage::from_millivolts(-100);
+        assert_eq!(b.as_millivolts(), -100i16);
+        assert_eq!(b.as_volts(), -0.1f32);
+        assert_eq!(b.as_microvolts(), -100_000);
+
+        let c = Voltage::from_microvolts(-1_999);
+        assert_eq!(c.as_microvolts(), -1_999);
+        assert_eq!(c.as_millivolts(), -1i16);
+        assert_eq!(c.as_volts(), -0.001999f32);
+    }
+
+    /// Calibrated voltages beyond the input range are not truncated.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_calibrated_beyond_input_range() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0x07, 0xD0, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        // E.g. a sensor with a gain of 1.5 in front of the device
+        let config = Config::default().with_calibration(Calibration::new(0, 500_000));
+        let voltage = adc.measure(&config).unwrap();
+        assert_eq!(voltage.as_microvolts(), 3_000_000);
+        assert_eq!(voltage.as_millivolts(), 3_000);
+        assert_eq!(
+            Voltage::from_microvolts(40_000_000).as_millivolts(),
+            i16::MAX
+        );
+        adc.destroy().done();
+    }
+
+    #[test]
+    #[cfg(all(feature = "fixed", not(feature = "measurements")))]
+    fn test_volts_fixed() {
+        use fixed::types::I16F16;
+
+        assert_eq!(
+            Voltage::from_millivolts(2_047).as_volts_fixed(),
+            I16F16::from_bits(134_152)
+        );
+        // One step is 15.26µV
+        assert_eq!(
+            Voltage::from_microvolts(8).as_volts_fixed(),
+            I16F16::from_bits(1)
+        );
+        assert_eq!(
+            Voltage::from_microvolts(-7).as_volts_fixed(),
+            I16F16::from_bits(0)
+        );
+        assert_eq!(
+            Voltage::from_microvolts(i32::MIN).as_volts_fixed(),
+            I16F16::from_bits(-140_737_488)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "uom", not(feature = "measurements")))]
+    fn test_electric_potential() {
+        use uom::si::{electric_potential::microvolt, f32::ElectricPotential};
+
+        let potential: ElectricPotential = Voltage::from_microvolts(-781).into();
+        assert_eq!(potential.get::<microvolt>(), -781.0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_arithmetic() {
+        let high = Voltage::from_millivolts(1_200);
+        let low = Voltage::from_microvolts(-300);
+        assert_eq!((high - low).as_microvolts(), 1_200_300);
+        assert_eq!((high + low).as_microvolts(), 1_199_700);
+        assert_eq!((-low).as_microvolts(), 300);
+        assert_eq!((low * 3).as_microvolts(), -900);
+        assert_eq!((high / 7).as_microvolts(), 171_428);
+        let mut sum = high;
+        sum += high;
+        sum -= low;
+        assert_eq!(sum.as_microvolts(), 2_400_300);
+
+        let max = Voltage::from_microvolts(i32::MAX);
+        assert_eq!(max.checked_add(high), None);
+        assert_eq!(max.checked_sub(low), None);
+        assert_eq!(high.checked_sub(low), Some(high - low));
+        assert_eq!(max.checked_mul(2), None);
+        assert_eq!(high.checked_div(0), None);
+        assert_eq!(max.saturating_add(high), max);
+        assert_eq!((-max).saturating_sub(high).as_microvolts(), i32::MIN);
+        assert_eq!(low.saturating_mul(i32::MAX).as_microvolts(), i32::MIN);
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_conversions() {
+        let voltage = Voltage::from(-1_250_000);
+        assert_eq!(f32::from(voltage), -1.25);
+        assert_eq!(f64::from(voltage), -1.25);
+        assert_eq!(i32::from(voltage), -1_250_000);
+
+        assert_eq!(Voltage::try_from(-1.25f64), Ok(voltage));
+        assert_eq!(Voltage::try_from(0.000_000_6f32), Ok(Voltage::from(1)));
+        assert_eq!(
+            Voltage::try_from(2_147.483_647f64),
+            Ok(Voltage::from(i32::MAX))
+        );
+        assert_eq!(
+            Voltage::try_from(-2_147.483_648f64),
+            Ok(Voltage::from(i32::MIN))
+        );
+        assert_eq!(Voltage::try_from(2_147.483_648f64), Err(VoltageOutOfRange));
+        assert_eq!(Voltage::try_from(f32::NAN), Err(VoltageOutOfRange));
+        assert_eq!(Voltage::try_from(f64::NEG_INFINITY), Err(VoltageOutOfRange));
+    }
+
+    #[rstest]
+    #[case(1_000_000, 0, 1, Some(1_000_000))]
+    #[case(1_000_000, 2, 1, Some(3_000_000))]
+    #[case(-1_000, 1, 2, Some(-1_500))]
+    #[case(1, 1, 2, Some(2))] // 1.5µV rounds up
+    #[case(-1, 1, 2, Some(-2))]
+    #[case(1_000, 1, 0, None)]
+    #[case(2_048_000, 1_000_000, 1, None)]
+    #[case(-2_048_000, u32::MAX, u32::MAX, Some(-4_096_000))]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_scale_by_divider(
+        #[case] microvolts: i32,
+        #[case] r_top: u32,
+        #[case] r_bottom: u32,
+        #[case] expected: Option<i32>,
+    ) {
+        let scaled = Voltage::from_microvolts(microvolts).scale_by_divider(r_top, r_bottom);
+        assert_eq!(scaled.map(|voltage| voltage.as_microvolts()), expected);
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_ordering() {
+        let mut voltages = [
+            Voltage::from_millivolts(5),
+            Voltage::from_microvolts(-1),
+            Voltage::from_microvolts(4_999),
+        ];
+        voltages.sort();
+        assert_eq!(
+            voltages.map(|voltage| voltage.as_microvolts()),
+            [-1, 4_999, 5_000]
+        );
+        assert!(Voltage::from_microvolts(-1) < Voltage::from_millivolts(0));
+        assert_eq!(voltages.iter().max(), Some(&Voltage::from_millivolts(5)));
+    }
+
+    #[rstest]
+    #[case(1_234_500, None, "1.234500 V")]
+    #[case(1_234_500, Some(4), "1.2345 V")]
+    #[case(1_234_500, Some(0), "1 V")]
+    #[case(-1_999_500, Some(3), "-2.000 V")]
+    #[case(999_999, Some(2), "1.00 V")]
+    #[case(-7, Some(5), "-0.00001 V")]
+    #[case(-4, Some(5), "0.00000 V")]
+    #[case(12, Some(9), "0.000012 V")]
+    #[case(i32::MIN, None, "-2147.483648 V")]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_display(
+        #[case] microvolts: i32,
+        #[case] precision: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let voltage = Voltage::from_microvolts(microvolts);
+        let formatted = match precision {
+            Some(precision) => format!("{:.*}", precision, voltage),
+            None => format!("{}", voltage),
+        };
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_write_to() {
+        let mut buf = [0; 9];
+        let voltage = Voltage::from_microvolts(-12_345);
+        assert_eq!(voltage.write_to(&mut buf, 3), Some("-0.012"));
+        assert_eq!(voltage.write_to(&mut buf, 7), Some("-0.012345"));
+        assert_eq!(voltage.write_to(&mut buf[..5], 3), None);
+        assert_eq!(voltage.write_to(&mut buf[..0], 0), None);
+        assert_eq!(voltage.write_to(&mut buf[..1], 0), Some("0"));
+
+        let mut buf = [0; Voltage::MAX_STR_LEN];
+        let min = Voltage::from_microvolts(i32::MIN);
+        assert_eq!(min.write_to(&mut buf, 6), Some("-2147.483648"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_voltage_display_padding() {
+        let voltage = Voltage::from_millivolts(-5);
+        assert_eq!(format!("[{:12.3}]", voltage), "[    -0.005 V]");
+        assert_eq!(format!("[{:*<9.2}]", voltage), "[-0.01 V**]");
+    }
+
+    #[test]
+    #[cfg(all(feature = "ufmt", not(feature = "measurements")))]
+    fn test_voltage_udisplay() {
+        struct Buffer(String);
+
+        impl ufmt::uWrite for Buffer {
+            type Error = ();
+
+            fn write_str(&mut self, s: &str) -> Result<(), ()> {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+
+        let mut formatted = Buffer(String::new());
+        ufmt::uwrite!(formatted, "{}", Voltage::from_microvolts(-781)).unwrap();
+        assert_eq!(formatted.0, "-0.000781 V");
+    }
+
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, 1024, 0.5, 500)]
+    #[case(Resolution::Bits12Sps240, -2048, -1.0, -1000)]
+    #[case(Resolution::Bits16Sps15, 32767, 0.999_969_5, 999)]
+    #[case(Resolution::Bits14Sps60, -1, -0.000_122_070_31, 0)]
+    fn test_reading_full_scale(
+        #[case] resolution: Resolution,
+        #[case] raw_code: i32,
+        #[case] fraction: f32,
+        #[case] per_mille: i16,
+    ) {
+        let reading = Reading {
+            voltage: voltage_from_microvolts(0),
+            raw_code,
+            config: Config::default()
+                .with_resolution(resolution)
+                .with_gain(Gain::Gain4),
+            fresh: true,
+            sequence: 0,
+        };
+        assert_eq!(reading.fraction_of_full_scale(), fraction);
+        assert_eq!(reading.per_mille_of_full_scale(), per_mille);
+    }
+
+    /// Readings keep the microvolt resolution of 16 bits at gain 8.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_read_microvolts() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10001011]),
+            Transaction::read(0x68, vec![0, 100, 0b00001011]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let config = Config::default()
+            .with_resolution(Resolution::Bits16Sps15)
+            .with_gain(Gain::Gain8);
+        let voltage = adc.measure(&config).unwrap();
+        assert_eq!(voltage.as_microvolts(), 781);
+        assert_eq!(voltage.as_millivolts(), 0);
+        adc.destroy().done();
+    }
+
+    /// Instantiation in one-shot mode should not do any calls to the I2C bus.
+    #[test]
+    fn test_instantiation_oneshot() {
+        let expectations = [];
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::oneshot(dev, 0x42, NoopDelay);
+        adc.destroy().done();
+    }
+
+    /// Instantiation in continuous mode should not do any calls to the I2C bus.
+    #[test]
+    fn test_instantiation_continuous() {
+        let expectations = [];
+        let dev = I2cMock::new(&expectations);
+
+        let adc = MCP3425::continuous(dev, 0x42, NoopDelay);
+        adc.destroy().done();
+    }
+
+    /// Successfully measuring a voltage with default configuration.
+    #[rstest]
+    #[case(0b00000111, 0b11111110, 2046)] // Maximum (at 12 bits) - 1
+    #[case(0b00000000, 0b00000111, 7)]
+    #[case(0b00000000, 0b00000000, 0)]
+    #[case(0b11111111, 0b11111111, -1)]
+    #[case(0b11111000, 0b00000001, -2047)] // Minimum (at 12 bits) + 1
+    #[cfg(not(feature = "measurements"))]
+    fn test_read_voltage_oneshot(
+        #[case] byte0: u8,
+        #[case] byte1: u8,
+        #[case] expected_millivolts: i16,
+    ) {
+        let addr = 0x42;
+        let expectations = [
+            // Write default config to config register:
+            // - Bit 7: Initiate new conversion
+            // - Bits 6-5: Channel selection (first channel)
+            // - Bit 4: One-shot conversion mode
+            // - Bits 3-2: Set sample rate 240 SPS
+            // - Bits 1-0: Set PGA gain to 1
+            Transaction::write(addr, vec![0b10000000]),
+            // Device returns data
+            Transaction::read(addr, vec![byte0, byte1, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = adc.measure(&Config::default()).expect("Measuring failed");
+        assert_eq!(voltage.as_millivolts(), expected_millivolts);
+        adc.destroy().done();
+    }
+
+    /// Test saturation at various resolutions.
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, 0b10000000, 0b11111000, 0b00000111)] // 12 bits
+    #[case(Resolution::Bits14Sps60, 0b10000100, 0b11100000, 0b00011111)] // 14 bits
+    #[case(Resolution::Bits16Sps15, 0b10001000, 0b10000000, 0b01111111)] // 16 bits
+    fn test_saturation(
+        #[case] resolution: Resolution,
+        #[case] config: u8,
+        #[case] upper_byte_negative: u8,
+        #[case] upper_byte_positive: u8,
+    ) {
+        let addr = 0x42;
+        let expectations = [
+            // Write config
+            Transaction::write(addr, vec![config]),
+            // Device returns data: Negative saturation
+            Transaction::read(addr, vec![upper_byte_negative, 0b00000000, 0b00000000]),
+            // Write config
+            Transaction::write(addr, vec![config]),
+            // Device returns data: Positive saturation
+            Transaction::read(addr, vec![upper_byte_positive, 0b11111111, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+
+        // Test negative saturation
+        let err_negative = adc
+            .measure(&Config::default().with_resolution(resolution))
+            .unwrap_err();
+        assert!(
+            matches!(err_negative, MeasureError::VoltageTooLow),
+            "{:?}",
+            err_negative
+        );
+
+        // Test positive saturation
+        let err_positive = adc
+            .measure(&Config::default().with_resolution(resolution))
+            .unwrap_err();
+        assert!(
+            matches!(err_positive, MeasureError::VoltageTooHigh),
+            "{:?}",
+            err_positive
+        );
+
+        adc.destroy().done();
+    }
+
+    /// Saturated results are reported according to the saturation policy.
+    #[rstest]
+    #[case(SaturationPolicy::Clamp, Gain::Gain1, 2047, 2048)]
+    #[case(SaturationPolicy::Clamp, Gain::Gain1, -2048, -2048)]
+    #[case(SaturationPolicy::Clamp, Gain::Gain2, 2047, 1024)]
+    #[case(SaturationPolicy::Raw, Gain::Gain1, 2047, 2047)]
+    #[case(SaturationPolicy::Raw, Gain::Gain2, 2047, 1023)]
+    #[case(SaturationPolicy::Raw, Gain::Gain1, -2048, -2048)]
+    #[cfg(not(feature = "measurements"))]
+    fn test_saturation_policy(
+        #[case] policy: SaturationPolicy,
+        #[case] gain: Gain,
+        #[case] code: i32,
+        #[case] expected_millivolts: i16,
+    ) {
+        let config = Config::fast()
+            .with_gain(gain)
+            .with_saturation_policy(policy);
+        let voltage = calculate_voltage::<Error<()>>(code, &config).unwrap();
+        assert_eq!(voltage.as_millivolts(), expected_millivolts);
+    }
+
+    /// Raw measurements return the output code, including saturated codes.
+    #[rstest]
+    #[case(vec![0b00000111, 0b11111111, 0], 2047)]
+    #[case(vec![0b11111000, 0b00000000, 0], -2048)]
+    #[case(vec![0b11111111, 0b11111110, 0], -2)]
+    fn test_measure_raw(#[case] data: Vec<u8>, #[case] expected: i32) {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, data),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        assert_eq!(adc.measure_raw(&Config::fast()).unwrap(), expected);
+        adc.destroy().done();
+    }
+
+    /// Raw reads in continuous mode report stale results.
+    #[test]
+    fn test_read_measurement_raw() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00011000]),
+            Transaction::read(addr, vec![0x80, 0x00, 0b00011000]),
+            Transaction::read(addr, vec![0x80, 0x00, 0b10011000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let err = adc.read_measurement_raw().unwrap_err();
+        assert!(matches!(err, ReadError::NotInitialized), "{:?}", err);
+
+        adc.set_config(&Config::high_resolution()).unwrap();
+        let err = adc.read_measurement_raw().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Full readings carry the code and configuration of the conversion.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_full() {
+        let addr = 0x42;
+        let config = Config::fast().with_gain(Gain::Gain2);
+        let expectations = [
+            Transaction::write(addr, vec![0b10000001]),
+            Transaction::read(addr, vec![0x01, 0x00, 0b00000001]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let reading = adc.measure_full(&config).unwrap();
+        assert_eq!(reading.voltage.as_millivolts(), 128);
+        assert_eq!(reading.raw_code, 256);
+        assert_eq!(reading.config, config);
+        assert!(reading.fresh);
+        adc.destroy().done();
+    }
+
+    /// Stale results are returned as readings that are not fresh.
+    #[test]
+    fn test_read_measurement_full() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 5, 0b00010000]),
+            Transaction::read(addr, vec![0, 5, 0b00010000]),
+            Transaction::read(addr, vec![0, 5, 0b10010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_config(&Config::default()).unwrap();
+        let reading = adc.read_measurement_full().unwrap();
+        assert!(reading.fresh);
+        assert_eq!(reading.raw_code, 5);
+        // The first result was read by set_config
+        assert_eq!(reading.sequence, 2);
+        let reading = adc.read_measurement_full().unwrap();
+        assert!(!reading.fresh);
+        assert_eq!(reading.sequence, 2);
+        assert_eq!(reading.config, Config::default());
+        adc.destroy().done();
+    }
+
+    /// A single saturated conversion is discarded with debouncing.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_saturation_debounce_oneshot() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 7, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_saturation_debounce(2);
+        let voltage = adc.measure(&Config::default()).unwrap();
+        assert_eq!(voltage.as_millivolts(), 7);
+        let err = adc.measure(&Config::default()).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooHigh), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Averaging discards a single saturated sample with debouncing.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_saturation_debounce_avg() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 5, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 7, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_saturation_debounce(2);
+        let voltage = adc.measure_avg(&Config::default(), 2).unwrap();
+        assert_eq!(voltage.as_millivolts(), 6);
+        adc.destroy().done();
+    }
+
+    /// In continuous mode, the next conversion is read after a saturated one.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_saturation_debounce_continuous() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::read(addr, vec![0xF8, 0x00, 0b00010000]),
+            // Not yet ready, then ready
+            Transaction::read(addr, vec![0xF8, 0x00, 0b10010000]),
+            Transaction::read(addr, vec![0xFF, 0xFE, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_saturation_debounce(2);
+        adc.set_config(&Config::default()).unwrap();
+        let voltage = adc.read_measurement().unwrap();
+        assert_eq!(voltage.as_millivolts(), -2);
+        adc.destroy().done();
+    }
+
+    /// The age of the last result is tracked with a clock.
+    #[test]
+    fn test_last_reading_age() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NOW_US: AtomicU64 = AtomicU64::new(1_000);
+        fn now_us() -> u64 {
+            NOW_US.load(Ordering::Relaxed)
+        }
+
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 5, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        assert_eq!(adc.last_reading_age(), None);
+        adc.set_clock(now_us);
+        assert_eq!(adc.last_reading_age(), None);
+        adc.set_config(&Config::default()).unwrap();
+        NOW_US.store(5_000, Ordering::Relaxed);
+        assert_eq!(adc.last_reading_age(), Some(Duration::from_millis(4)));
+        assert_eq!(adc.sequence(), 1);
+        adc.destroy().done();
+    }
+
+    /// Unchecked reads skip the configuration register.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_read_measurement_unchecked() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::read(addr, vec![0x01, 0x00]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let err = adc.read_measurement_unchecked().unwrap_err();
+        assert!(matches!(err, ReadError::NotInitialized), "{:?}", err);
+
+        adc.set_config(&Config::default()).unwrap();
+        let voltage = adc.read_measurement_unchecked().unwrap();
+        assert_eq!(voltage.as_millivolts(), 256);
+        adc.destroy().done();
+    }
+
+    /// Setting the configuration without waiting does not poll the device.
+    #[test]
+    fn test_set_config_nowait() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00011000]),
+            Transaction::read(addr, vec![0, 0, 0b10011000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_config_nowait(&Config::high_resolution()).unwrap();
+        let err = adc.read_measurement().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Waiting for the first measurement gives up after the timeout.
+    #[test]
+    fn test_set_config_timeout() {
+        let addr = 0x42;
+        let not_ready = Transaction::read(addr, vec![0, 0, 0b10010000]);
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            not_ready.clone(),
+            not_ready.clone(),
+            not_ready,
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let conversion_time_us = Resolution::Bits12Sps240.conversion_time_us();
+        adc.set_ready_timeout(Duration::from_micros(u64::from(conversion_time_us) + 2000));
+        let err = adc.set_config(&Config::default()).unwrap_err();
+        assert!(matches!(err, Error::Timeout), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Polling one-shot measurements read until the result is ready.
+    #[test]
+    fn test_wait_strategy_sleep_then_poll() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0, 7, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_wait_strategy(WaitStrategy::SleepThenPoll {
+            poll_interval_us: 100,
+        });
+        assert_eq!(adc.measure_raw(&Config::default()).unwrap(), 7);
+        adc.destroy().done();
+    }
+
+    /// Worst-case sleeping one-shot measurements read the result only once,
+    /// after the maximum conversion time.
+    #[test]
+    fn test_wait_strategy_worst_case_sleep() {
+        use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 7, 0b00000000]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(5_682),
+            DelayTransaction::delay_us(5_682),
+        ]);
+        let mut adc = MCP3425::oneshot(dev, addr, delay);
+        adc.set_wait_strategy(WaitStrategy::WorstCaseSleep);
+        assert_eq!(adc.measure_raw(&Config::default()).unwrap(), 7);
+        let err = adc.measure_raw(&Config::default()).unwrap_err();
+        assert!(matches!(err, MeasureError::NotReady), "{:?}", err);
+        let (mut dev, mut delay) = adc.release();
+        dev.done();
+        delay.done();
+    }
+
+    /// Fast measurements poll the RDY bit after the typical conversion time.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_fast() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10001000]),
+            Transaction::read(addr, vec![0, 0, 0b10001000]),
+            Transaction::read(addr, vec![0, 0, 0b10001000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0b00001000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = adc.measure_fast(&Config::high_resolution()).unwrap();
+        assert_eq!(voltage.as_millivolts(), 62);
+        adc.destroy().done();
+    }
+
+    /// User paced continuous mode drivers never wait for the device, one-shot
+    /// measurements read the result once.
+    #[test]
+    fn test_wait_strategy_user_paced() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::write(addr, vec![0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_wait_strategy(WaitStrategy::UserPaced);
+        let err = adc.measure(&Config::default()).unwrap_err();
+        assert!(matches!(err, MeasureError::NotReady), "{:?}", err);
+
+        let mut adc = adc.into_continuous();
+        adc.set_config(&Config::default()).unwrap();
+        adc.destroy().done();
+    }
+
+    /// Retries of user paced one-shot measurements re-read the result without
+    /// starting another conversion.
+    #[test]
+    fn test_wait_strategy_user_paced_retries() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0, 0, 0b10000000]),
+            Transaction::read(addr, vec![0, 7, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_wait_strategy(WaitStrategy::UserPaced);
+        adc.set_retry_policy(RetryPolicy::new(3, 1));
+        assert_eq!(adc.measure_raw(&Config::default()).unwrap(), 7);
+        adc.destroy().done();
+    }
+
+    /// Number of RDY polls of a default 12 bit one-shot measurement, from the
+    /// typical to the maximum conversion time.
+    const NOT_READY_POLLS: usize = (5_682 - 4_000u32).div_ceil(READY_POLL_INTERVAL_US) as usize + 1;
+
+    /// Mode switches can write the configuration in one step.
+    #[test]
+    fn test_mode_switch_with_config() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010100]),
+            Transaction::read(addr, vec![0, 0, 0b00010100]),
+            Transaction::write(addr, vec![0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let config = Config::default().with_resolution(Resolution::Bits14Sps60);
+        let adc = adc
+            .into_continuous_with(&config)
+            .map_err(|(e, _)| e)
+            .unwrap();
+        let adc = adc
+            .into_oneshot_with(&Config::default())
+            .map_err(|(e, _)| e)
+            .unwrap();
+        adc.destroy().done();
+    }
+
+    /// A mode switch that times out stops the continuous conversions again.
+    #[test]
+    fn test_mode_switch_with_config_timeout() {
+        let addr = 0x42;
+        let not_ready = Transaction::read(addr, vec![0, 0, 0b10010000]);
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            not_ready.clone(),
+            not_ready.clone(),
+            not_ready,
+            Transaction::write(addr, vec![0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let conversion_time_us = Resolution::Bits12Sps240.conversion_time_us();
+        adc.set_ready_timeout(Duration::from_micros(u64::from(conversion_time_us) + 2000));
+        let Err((err, adc)) = adc.into_continuous_with(&Config::default()) else {
+            panic!("mode switch succeeded");
+        };
+        assert!(matches!(err, Error::Timeout), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// A failed mode switch returns the driver in its previous mode.
+    #[test]
+    fn test_mode_switch_with_config_error() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::write(addr, vec![0b00000000])
+                .with_error(embedded_hal::i2c::ErrorKind::Other),
+            Transaction::read(addr, vec![0, 9, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_config(&Config::default()).unwrap();
+        let Err((err, mut adc)) = adc.into_oneshot_with(&Config::default()) else {
+            panic!("mode switch succeeded");
+        };
+        assert!(
+            matches!(err, Error::I2c(embedded_hal::i2c::ErrorKind::Other)),
+            "{:?}",
+            err
+        );
+        assert_eq!(adc.read_measurement_raw().unwrap(), 9);
+        adc.destroy().done();
+    }
+
+    /// Configured drivers keep their configuration across reads.
+    #[test]
+    fn test_configure() {
+        let addr = 0x42;
+        let config = Config::high_resolution();
+        let expectations = [
+            Transaction::write(addr, vec![0b00011000]),
+            Transaction::read(addr, vec![0, 0, 0b00011000]),
+            Transaction::read(addr, vec![0x12, 0x34, 0b00011000]),
+            Transaction::read(addr, vec![0x12, 0x34, 0b10011000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let mut adc = adc.configure(&config).map_err(|(e, _)| e).unwrap();
+        assert_eq!(adc.config(), &config);
+        assert_eq!(adc.read_measurement_raw().unwrap(), 0x1234);
+
+        // The configuration is still known after leaving the typestate
+        let mut adc = adc.into_unconfigured();
+        let err = adc.read_measurement_raw().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Powering down writes a one-shot configuration, resuming restores the
+    /// previous configuration.
+    #[test]
+    fn test_power_down_resume() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010101]),
+            Transaction::read(addr, vec![0, 0, 0b00010101]),
+            Transaction::write(addr, vec![0b00000101]),
+            Transaction::write(addr, vec![0b00010101]),
+            Transaction::read(addr, vec![0, 0, 0b00010101]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let err = adc.resume().unwrap_err();
+        assert!(matches!(err, Error::NotInitialized), "{:?}", err);
+
+        let config = Config::new(Resolution::Bits14Sps60, Gain::Gain2, Channel::Channel1);
+        adc.set_config(&config).unwrap();
+        adc.power_down().unwrap();
+        adc.resume().unwrap();
+        adc.destroy().done();
+    }
+
+    /// The driver can be retargeted to another address.
+    #[test]
+    fn test_set_address() {
+        let expectations = [
+            Transaction::write(0x6A, vec![0b00000000]),
+            Transaction::write(0x6A, vec![0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        adc.set_address(0x6A);
+        assert_eq!(adc.address(), 0x6A);
+        adc.prepare(&Config::default()).unwrap();
+        embedded_hal::i2c::I2c::write(adc.bus_mut(), 0x6A, &[0]).unwrap();
+        adc.destroy().done();
+    }
+
+    /// Continuous mode readers only write changed configurations.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_adc_reader_continuous() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 1, 0b00010000]),
+            Transaction::read(addr, vec![0, 1, 0b10010000]),
+            Transaction::read(addr, vec![0, 2, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        assert_eq!(adc.read(&Config::default()).unwrap().as_millivolts(), 1);
+        assert_eq!(adc.read(&Config::default()).unwrap().as_millivolts(), 2);
+        adc.destroy().done();
+    }
+
+    /// Armed conversions can be harvested once they are ready.
+    #[test]
+    fn test_arm_harvest() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000100]),
+            Transaction::read(addr, vec![0, 0, 0b10000100]),
+            Transaction::read(addr, vec![0, 0, 0b00000100]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let config = Config::default().with_resolution(Resolution::Bits14Sps60);
+        let ready_after = adc.arm(&config).unwrap();
+        assert_eq!(ready_after, Duration::from_micros(17_000));
+
+        let err = adc.harvest().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        adc.harvest().unwrap();
+        let err = adc.harvest().unwrap_err();
+        assert!(matches!(err, ReadError::NotInitialized), "{:?}", err);
+        assert!(adc.config.is_some());
+        adc.destroy().done();
+    }
+
+    /// All channels are measured in order with the same resolution and gain.
+    #[test]
+    #[cfg(feature = "quad_channel")]
+    fn test_measure_all() {
+        use crate::model::Mcp3428;
+
+        let addr = 0x42;
+        let expectations: Vec<_> = (0..4u8)
+            .flat_map(|index| {
+                [
+                    Transaction::write(addr, vec![0b10000010 | index << 5]),
+                    Transaction::read(addr, vec![0, index, 0b00000010 | index << 5]),
+                ]
+            })
+            .collect();
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay).with_model(Mcp3428);
+        let config = Config::default().with_gain(Gain::Gain4);
+        let readings = adc.measure_all::<4>(&config).unwrap();
+        assert_eq!(readings[3].channel, Channel::Channel4);
+        let err = adc.measure_all::<5>(&config).unwrap_err();
+        assert!(
+            matches!(err, MeasureError::ChannelNotSupported),
+            "{:?}",
+            err
+        );
+        adc.destroy().done();
+    }
+
+    /// The mean is not rounded to an output code.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_avg() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[10, 11, 13]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = adc.measure_avg(&Config::default(), 3).unwrap();
+        assert_eq!(voltage.as_microvolts(), 11_333);
+        adc.destroy().done();
+    }
+
+    /// A single saturated sample fails the average.
+    #[test]
+    fn test_measure_avg_saturated() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 1, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0xF8, 0x00, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let err = adc.measure_avg(&Config::default(), 4).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooLow), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// Saturated samples are counted instead of failing the statistics.
+    #[test]
+    fn test_measure_stats() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 1, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let stats = adc.measure_stats(&Config::default(), 2).unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.saturated_count, 1);
+        assert_eq!(stats.std_dev_uv, 1_023_000);
+        adc.destroy().done();
+    }
+
+    /// The DC mean is removed from the RMS value.
+    #[test]
+    fn test_measure_rms() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[100, 102, 100, 98]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let rms = adc.measure_rms(&Config::default(), 4).unwrap();
+        assert_eq!(rms, 1_414);
+        adc.destroy().done();
+    }
+
+    #[test]
+    fn test_measure_noise() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[100, 102, 100, 98]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let report = adc.measure_noise(&Config::default(), 4).unwrap();
+        assert_eq!(report.peak_to_peak_codes, 4);
+        assert_eq!(report.noise_free_bits, 10.0);
+        assert!((report.effective_bits - 11.5).abs() < 0.01);
+        adc.destroy().done();
+    }
+
+    #[rstest(
+        latency_ms,
+        expected,
+        case(0, None),
+        case(5, None),
+        case(6, Some(Resolution::Bits12Sps240)),
+        case(17, Some(Resolution::Bits14Sps60)),
+        case(59, Some(Resolution::Bits16Sps15)),
+        case(1_000, Some(Resolution::Bits16Sps15))
+    )]
+    #[cfg(not(feature = "eighteen_bit"))]
+    fn test_best_for(latency_ms: u64, expected: Option<Resolution>) {
+        assert_eq!(
+            Resolution::best_for(Duration::from_millis(latency_ms)),
+            expected
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "eighteen_bit")]
+    fn test_best_for_18bit() {
+        let resolution = Resolution::best_for(Duration::from_millis(269));
+        assert_eq!(resolution, Some(Resolution::Bits18Sps3_75));
+        let resolution = Resolution::best_for(Duration::from_millis(268));
+        assert_eq!(resolution, Some(Resolution::Bits16Sps15));
+    }
+
+    /// Samples measured before a failure are kept.
+    #[test]
+    fn test_measure_extend_error() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 1, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let mut voltages = heapless::Vec::<_, 4>::new();
+        let err = adc
+            .measure_extend(&Config::default(), 3, &mut voltages)
+            .unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooHigh), "{:?}", err);
+        assert_eq!(voltages.len(), 1);
+        adc.destroy().done();
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_measure_vec() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[1, 2, 3]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltages = adc.measure_vec(&Config::default(), 3).unwrap();
+        assert_eq!(voltages.len(), 3);
+        adc.destroy().done();
+    }
+
+    #[test]
+    fn test_keep_extreme() {
+        let mut lowest = [i32::MAX; 3];
+        for code in [5, 1, 7, 3, 0, 9] {
+            keep_extreme(&mut lowest, code, |a, b| a < b);
+        }
+        assert_eq!(lowest, [0, 1, 3]);
+        let mut none: [i32; 0] = [];
+        keep_extreme(&mut none, 1, |a, b| a < b);
+    }
+
+    /// Glitches in both directions are discarded from the mean.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_trimmed() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[10, 2047, 12, -2048, 14]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = adc.measure_trimmed(&Config::default(), 5, 1).unwrap();
+        assert_eq!(voltage.as_millivolts(), 12);
+        adc.destroy().done();
+    }
+
+    /// At least one sample must remain after discarding.
+    #[test]
+    #[should_panic(expected = "too few samples to discard")]
+    fn test_measure_trimmed_too_few_samples() {
+        let mut adc = MCP3425::oneshot(I2cMock::new(&[]), 0x42, NoopDelay);
+        let _ = adc.measure_trimmed(&Config::default(), 2, 1);
+    }
+
+    /// The calibration of the configuration corrects the voltage, but not
+    /// the saturation check.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_calibrated() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        // Offset -2mV, gain +1%
+        let calibration = Calibration::from_points(0, -2_000, 1_000_000, 1_008_000).unwrap();
+        let config = Config::default().with_calibration(calibration);
+        assert_eq!(adc.measure(&config).unwrap().as_millivolts(), 1_008);
+        let err = adc.measure(&config).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooHigh), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// The tempco compensation uses the temperature at the time of the read,
+    /// also in continuous mode.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_tempco_compensation() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let config = Config::default();
+        adc.set_tempco(Some(Tempco::new(0, 1_000)));
+        // No compensation without a temperature
+        assert_eq!(adc.measure(&config).unwrap().as_millivolts(), 1_000);
+        adc.set_temperature(45_000);
+        assert_eq!(adc.measure(&config).unwrap().as_millivolts(), 980);
+        adc.set_temperature_source(|| 5_000);
+        assert_eq!(adc.measure(&config).unwrap().as_millivolts(), 1_020);
+        adc.destroy().done();
+
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0b00010000]),
+            Transaction::read(addr, vec![0x03, 0xE8, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_tempco(Some(Tempco::new(0, 1_000)));
+        adc.set_config(&config).unwrap();
+        assert_eq!(adc.read_measurement().unwrap().as_millivolts(), 1_000);
+        adc.set_temperature(35_000);
+        assert_eq!(adc.read_measurement().unwrap().as_millivolts(), 990);
+        adc.destroy().done();
+    }
+
+    /// The channel calibrations apply to one-shot and continuous mode, unless
+    /// the configuration has its own calibration.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_channel_calibrations() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 100, 0]),
+            Transaction::write(addr, vec![0b10000000]),
+            Transaction::read(addr, vec![0, 100, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let offset = Calibration::new(-5_000, 0);
+        adc.set_channel_calibrations(ChannelCalibrations::new().with(Channel::Channel1, offset));
+        let voltage = adc.measure(&Config::default()).unwrap();
+        assert_eq!(voltage.as_millivolts(), 95);
+        let config = Config::default().with_calibration(Calibration::default());
+        let voltage = adc.measure(&config).unwrap();
+        assert_eq!(voltage.as_millivolts(), 100);
+
+        adc.destroy().done();
+
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::read(addr, vec![0, 100, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_channel_calibrations(ChannelCalibrations::new().with(Channel::Channel1, offset));
+        adc.set_config(&Config::default()).unwrap();
+        assert_eq!(adc.read_measurement().unwrap().as_millivolts(), 95);
+        adc.destroy().done();
+    }
+
+    /// The offset is measured at 16 bits and keeps the gain correction.
+    #[test]
+    fn test_calibrate_offset() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0b00001000, &[3, 5, 4]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let config = Config::default().with_calibration(Calibration::new(100, 2_000));
+        let calibrated = adc.calibrate_offset(&config, 3).unwrap();
+        // Mean 4 LSB at 16 bits, i.e. 250µV
+        assert_eq!(calibrated.calibration, Some(Calibration::new(-250, 2_000)));
+        assert_eq!(calibrated.resolution, config.resolution);
+        adc.destroy().done();
+    }
+
+    /// A saturated sample aborts the calibration regardless of the policy.
+    #[test]
+    fn test_calibrate_offset_saturated() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10001000]),
+            Transaction::read(addr, vec![0x80, 0x00, 0b00001000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let config = Config::default().with_saturation_policy(SaturationPolicy::Clamp);
+        let err = adc.calibrate_offset(&config, 3).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooLow), "{:?}", err);
+        adc.destroy().done();
+    }
+
+    /// A saturated spike is rejected by the median.
+    #[test]
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_median() {
+        let addr = 0x42;
+        let expectations = oneshot_expectations(addr, 0, &[10, 2047, 12]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let voltage = adc.measure_median::<3>(&Config::default()).unwrap();
+        assert_eq!(voltage.as_millivolts(), 12);
+        adc.destroy().done();
+    }
+
+    /// Snapshots use the fastest resolution and report nominal offsets.
+    #[test]
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    fn test_snapshot() {
+        use crate::model::Mcp3426;
+
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b10000001]),
+            Transaction::read(addr, vec![0, 1, 0b00000001]),
+            Transaction::write(addr, vec![0b10100011]),
+            Transaction::read(addr, vec![0, 2, 0b00100011]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay).with_model(Mcp3426);
+        let [first, second] = adc.snapshot([Gain::Gain2, Gain::Gain8]).unwrap();
+        assert_eq!(first.offset, Duration::ZERO);
+        assert_eq!(second.channel, Channel::Channel2);
+        assert_eq!(second.offset, Duration::from_micros(4_000));
+        adc.destroy().done();
+    }
+
+    /// Releasing the driver returns the delay as well.
+    #[test]
+    fn test_release() {
+        let dev = I2cMock::new(&[]);
+        let adc = MCP3425::oneshot(dev, 0x42, NoopDelay);
+        let (mut dev, _delay): (_, NoopDelay) = adc.release();
+        dev.done();
+    }
+
+    /// Test the "not ready" response handling.
+    #[rstest]
+    fn test_not_ready() {
+        let addr = 0x42;
+        let default_config = 0b10000000;
+        // Write config
+        let mut expectations = vec![Transaction::write(addr, vec![default_config])];
+        // First bit in returned config register is set to 1 (not ready),
+        // until the safety margin has passed
+        let not_ready = Transaction::read(addr, vec![0b00000000, 0b00000000, 0b10000000]);
+        expectations.extend(iter::repeat(not_ready).take(NOT_READY_POLLS));
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+
+        let err = adc.measure(&Config::default()).unwrap_err();
+        assert!(matches!(err, MeasureError::NotReady), "{:?}", err);
+
+        adc.destroy().done();
+    }
+
+    /// Codes are converted to microvolts without overflowing or losing
+    /// precision.
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, 2047, 2_047_000)]
+    #[case(Resolution::Bits14Sps60, -8192, -2_048_000)]
+    #[case(Resolution::Bits16Sps15, 1, 62)]
+    #[case(Resolution::Bits16Sps15, 32767, 2_047_937)]
+    fn test_code_to_microvolts(
+        #[case] resolution: Resolution,
+        #[case] code: i32,
+        #[case] expected_microvolts: i32,
+    ) {
+        assert_eq!(
+            code_to_microvolts(code, &resolution, &Gain::Gain1),
+            expected_microvolts
+        );
+    }
 
-        // Send command
-        self.i2c
-            .write(self.address, &[command])
-            .map_err(Error::I2c)?;
+    /// The gain is divided out of the result.
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, Gain::Gain1, 2_047_000)]
+    #[case(Resolution::Bits12Sps240, Gain::Gain2, 1_023_500)]
+    #[case(Resolution::Bits12Sps240, Gain::Gain4, 511_750)]
+    #[case(Resolution::Bits12Sps240, Gain::Gain8, 255_875)]
+    #[case(Resolution::Bits14Sps60, Gain::Gain1, 2_047_750)]
+    #[case(Resolution::Bits14Sps60, Gain::Gain2, 1_023_875)]
+    #[case(Resolution::Bits14Sps60, Gain::Gain4, 511_937)]
+    #[case(Resolution::Bits14Sps60, Gain::Gain8, 255_968)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain1, 2_047_937)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain2, 1_023_968)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain4, 511_984)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain8, 255_992)]
+    #[cfg_attr(
+        feature = "eighteen_bit",
+        case(Resolution::Bits18Sps3_75, Gain::Gain1, 2_047_984)
+    )]
+    #[cfg_attr(
+        feature = "eighteen_bit",
+        case(Resolution::Bits18Sps3_75, Gain::Gain2, 1_023_992)
+    )]
+    #[cfg_attr(
+        feature = "eighteen_bit",
+        case(Resolution::Bits18Sps3_75, Gain::Gain4, 511_996)
+    )]
+    #[cfg_attr(
+        feature = "eighteen_bit",
+        case(Resolution::Bits18Sps3_75, Gain::Gain8, 255_998)
+    )]
+    fn test_code_to_microvolts_gain(
+        #[case] resolution: Resolution,
+        #[case] gain: Gain,
+        #[case] expected_microvolts: i32,
+    ) {
+        // Positive full-scale code
+        let code = Resolution::max(&resolution);
+        assert_eq!(
+            code_to_microvolts(code, &resolution, &gain),
+            expected_microvolts
+        );
+    }
 
-        // Determine time to wait for the conversion to finish.
-        // Values found by experimentation, these do not seem to be specified
-        // in the datasheet.
-        let sleep_ms = match config.resolution {
-            Resolution::Bits12Sps240 => 4,
-            Resolution::Bits14Sps60 => 15,
-            Resolution::Bits16Sps15 => 57,
-        };
-        self.delay.delay_ms(sleep_ms + 2); // Add two additional milliseconds as safety margin
+    /// The time until ready includes a safety margin on top of the
+    /// conversion time.
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, 6_000)]
+    #[case(Resolution::Bits14Sps60, 17_000)]
+    #[case(Resolution::Bits16Sps15, 59_000)]
+    fn test_time_until_ready(#[case] resolution: Resolution, #[case] expected_us: u32) {
+        let config = Config::default().with_resolution(resolution);
+        assert_eq!(config.time_until_ready(), expected_us);
+    }
 
-        // Read result
-        let (measurement, config_reg) = self.read_i16_and_config()?;
+    #[test]
+    fn test_channel_index_round_trip() {
+        for channel in Channel::all() {
+            assert_eq!(Channel::try_from(channel.index()), Ok(*channel));
+        }
+        let count = Channel::all().len() as u8;
+        assert_eq!(Channel::try_from(count), Err(InvalidChannel(count)));
+    }
 
-        // Make sure that the delay was sufficient
-        if !config_reg.is_ready() {
-            return Err(Error::NotReady);
+    #[test]
+    fn test_differential_input_round_trip() {
+        for channel in Channel::all() {
+            assert_eq!(DifferentialInput::from(*channel).channel(), *channel);
         }
+    }
 
-        // Calculate voltage from raw value
-        let voltage = self.calculate_voltage(measurement, &config.resolution)?;
+    #[rstest]
+    fn test_gain_factor_round_trip(
+        #[values(Gain::Gain1, Gain::Gain2, Gain::Gain4, Gain::Gain8)] gain: Gain,
+    ) {
+        assert_eq!(Gain::try_from(gain.factor()), Ok(gain));
+    }
 
-        Ok(voltage)
+    #[rstest]
+    #[case(0)]
+    #[case(3)]
+    #[case(16)]
+    fn test_gain_invalid_factor(#[case] factor: u8) {
+        assert_eq!(Gain::try_from(factor), Err(InvalidGain(factor)));
     }
-}
 
-impl<I2C, D> MCP3425<I2C, D, ContinuousMode>
-where
-    I2C: I2c,
-    D: DelayNs,
-{
-    /// Initialize the MCP3425 driver in Continuous Measurement mode.
-    ///
-    /// This constructor is side-effect free, so it will not write any
-    /// configuration to the device until a first measurement is triggered.
-    pub fn continuous(i2c: I2C, address: u8, delay: D) -> Self {
-        MCP3425 {
-            i2c,
-            address,
-            delay,
-            mode: ContinuousMode,
-            config: None,
-        }
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, 240.0, 1000.0)]
+    #[case(Resolution::Bits14Sps60, 60.0, 250.0)]
+    #[case(Resolution::Bits16Sps15, 15.0, 62.5)]
+    #[cfg_attr(
+        feature = "eighteen_bit",
+        case(Resolution::Bits18Sps3_75, 3.75, 15.625)
+    )]
+    fn test_resolution_metadata(
+        #[case] resolution: Resolution,
+        #[case] sps: f32,
+        #[case] step_uv: f32,
+    ) {
+        assert_eq!(resolution.samples_per_second(), sps);
+        assert_eq!(resolution.step_microvolts(), step_uv);
+        // The worst case is slower than the typical conversion time
+        let max_us = resolution.max_conversion_time().as_micros();
+        assert!(max_us > u128::from(resolution.conversion_time_us()));
+        assert!(max_us as f32 > 1_000_000.0 / sps);
     }
 
-    /// Change the conversion mode to one-shot.
-    ///
-    /// This conversion is side-effect free, so it will not write any
-    /// configuration to the device until a first one-shot measurement is
-    /// triggered.
-    pub fn into_oneshot(self) -> MCP3425<I2C, D, OneShotMode> {
-        MCP3425::oneshot(self.i2c, self.address, self.delay)
+    #[test]
+    fn test_gain_ordering() {
+        assert!(Gain::Gain1 < Gain::Gain2);
+        assert!(Gain::Gain8 > Gain::Gain4);
+        let max = [Gain::Gain2, Gain::Gain8, Gain::Gain1].into_iter().max();
+        assert_eq!(max, Some(Gain::Gain8));
     }
 
-    /// Write the specified configuration to the device and block until the
-    /// first measurement is ready.
-    ///
-    /// The wait-for-measurement logic is implemented with polling, since there
-    /// are no non-blocking `embedded_hal` traits yet.
-    ///
-    /// Note: Since the wait-until-ready logic needs to read the data register,
-    /// when reading the measurement immediately after setting the
-    /// configuration, that measurement will be returned as `NotFresh`.
-    pub fn set_config(&mut self, config: &Config) -> Result<(), Error<I2C::Error>> {
-        // Set configuration
-        let command = self.mode.bits() | config.bits();
-        self.i2c
-            .write(self.address, &[command])
-            .map(|()| self.config = Some(*config))
-            .map_err(Error::I2c)?;
+    #[test]
+    fn test_resolution_ordering() {
+        assert!(Resolution::Bits12Sps240 < Resolution::Bits14Sps60);
+        assert!(Resolution::Bits16Sps15 > Resolution::Bits14Sps60);
+        let mut resolutions = [
+            Resolution::Bits16Sps15,
+            Resolution::Bits12Sps240,
+            Resolution::Bits14Sps60,
+        ];
+        resolutions.sort();
+        assert_eq!(
+            resolutions,
+            [
+                Resolution::Bits12Sps240,
+                Resolution::Bits14Sps60,
+                Resolution::Bits16Sps15
+            ]
+        );
+        #[cfg(feature = "eighteen_bit")]
+        assert!(Resolution::Bits18Sps3_75 > Resolution::Bits16Sps15);
+    }
 
-        // Determine time to wait for first measurement.
-        // Values found by experimentation, these do not seem to be specified
-        // in the datasheet.
-        let sleep_ms = match config.resolution {
-            Resolution::Bits12Sps240 => 4,
-            Resolution::Bits14Sps60 => 15,
-            Resolution::Bits16Sps15 => 57,
-        };
-        self.delay.delay_ms(sleep_ms);
+    /// Configurations can be built at compile time.
+    #[test]
+    fn test_const_config() {
+        const CONFIG: Config = Config::high_resolution().with_gain(Gain::Gain8);
+        const WAIT_US: u32 = CONFIG.time_until_ready();
+        assert_eq!(
+            CONFIG,
+            Config::new(Resolution::Bits16Sps15, Gain::Gain8, Channel::Channel1)
+        );
+        assert_eq!(WAIT_US, 59_000);
+    }
 
-        // Poll until ready
-        let mut buf = [0, 0, 0];
-        loop {
-            self.i2c.read(self.address, &mut buf).map_err(Error::I2c)?;
-            if (buf[2] & ConfigRegister::NOT_READY.bits()) == ConfigRegister::NOT_READY.bits() {
-                // Not yet ready, wait some more time
-                self.delay.delay_ms(1);
-            } else {
-                break;
-            }
-        }
-        Ok(())
+    #[test]
+    fn test_presets() {
+        assert_eq!(Config::fast().resolution, Resolution::Bits12Sps240);
+        assert_eq!(
+            Config::high_resolution().resolution,
+            Resolution::Bits16Sps15
+        );
+        assert_eq!(Config::high_resolution().gain, Gain::Gain1);
     }
 
-    /// Read a measurement from the device.
-    ///
-    /// Note that the [`set_config`](struct.MCP3425.html#method.set_config)
-    /// method MUST have been called before, otherwise
-    /// [`Error::NotInitialized`](enum.Error.html#variant.NotInitialized) will
-    /// be returned.
-    ///
-    /// If you poll faster than the sample rate,
-    /// [`Error::NotReady`](enum.Error.html#variant.NotReady) will be returned.
-    pub fn read_measurement(&mut self) -> Result<Voltage, Error<I2C::Error>> {
-        // Make sure that the configuration has been written to the device
-        let config = self.config.ok_or(Error::NotInitialized)?;
+    #[rstest]
+    #[case(Gain::Gain1, 2048)]
+    #[case(Gain::Gain2, 1024)]
+    #[case(Gain::Gain4, 512)]
+    #[case(Gain::Gain8, 256)]
+    fn test_full_scale_mv(#[case] gain: Gain, #[case] expected_mv: i32) {
+        let config = Config::default().with_gain(gain);
+        assert_eq!(config.full_scale_mv(), expected_mv);
+    }
 
-        // Read measurement and config register
-        let (measurement, config_reg) = self.read_i16_and_config()?;
+    #[rstest]
+    #[case(Resolution::Bits12Sps240, Gain::Gain1, 1000.0)]
+    #[case(Resolution::Bits14Sps60, Gain::Gain2, 125.0)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain1, 62.5)]
+    #[case(Resolution::Bits16Sps15, Gain::Gain8, 7.8125)]
+    fn test_lsb_microvolts(
+        #[case] resolution: Resolution,
+        #[case] gain: Gain,
+        #[case] expected_uv: f32,
+    ) {
+        let config = Config::default()
+            .with_resolution(resolution)
+            .with_gain(gain);
+        assert_eq!(config.lsb_microvolts(), expected_uv);
+    }
 
-        // Calculate voltage from raw value
-        let voltage = self.calculate_voltage(measurement, &config.resolution)?;
+    /// Encoding and decoding the configuration register is lossless.
+    #[rstest]
+    fn test_config_byte_round_trip(
+        #[values(
+            Resolution::Bits12Sps240,
+            Resolution::Bits14Sps60,
+            Resolution::Bits16Sps15
+        )]
+        resolution: Resolution,
+        #[values(Gain::Gain1, Gain::Gain2, Gain::Gain4, Gain::Gain8)] gain: Gain,
+    ) {
+        let config = Config::default()
+            .with_resolution(resolution)
+            .with_gain(gain);
 
-        // Check "Not Ready" flag. See datasheet section 5.1.1 for more details.
-        if config_reg.is_ready() {
-            // The "Not Ready" flag is not set. This means the latest
-            // conversion result is ready.
-            Ok(voltage)
-        } else {
-            // The "Not Ready" flag is set. This means the conversion
-            // result is not updated since the last reading. A new
-            // conversion is under processing and the RDY bit will be
-            // cleared when the new conversion result is ready.
-            Err(Error::NotReady)
-        }
-    }
-}
+        let status = Config::try_from_byte(config.to_byte(&OneShotMode)).unwrap();
+        assert!(status.ready);
+        assert!(!status.continuous);
+        assert_eq!(status.config(), config);
 
-#[cfg(test)]
-mod tests {
-    use embedded_hal_mock::eh1::{
-        delay::NoopDelay,
-        i2c::{Mock as I2cMock, Transaction},
-    };
-    use rstest::rstest;
+        let byte = ConfigRegister::NOT_READY.bits() | config.to_byte(&ContinuousMode);
+        let status = DeviceStatus::try_from(byte).unwrap();
+        assert!(!status.ready);
+        assert!(status.continuous);
+        assert_eq!(status.config(), config);
+    }
 
-    use super::*;
+    /// Bits for resolutions and channels that are not enabled are rejected.
+    #[test]
+    #[cfg(not(any(
+        feature = "eighteen_bit",
+        feature = "dual_channel",
+        feature = "quad_channel"
+    )))]
+    fn test_config_byte_invalid() {
+        assert_eq!(
+            Config::try_from_byte(0b00001100),
+            Err(InvalidConfig(0b00001100))
+        );
+        assert_eq!(
+            Config::try_from_byte(0b00100000),
+            Err(InvalidConfig(0b00100000))
+        );
+    }
 
+    /// Transient errors are retried according to the retry policy.
     #[test]
-    #[cfg(not(feature = "measurements"))]
-    fn test_voltage_wrapper() {
-        let a = Voltage::from_millivolts(2500);
-        assert_eq!(a.as_millivolts(), 2500i16);
-        assert_eq!(a.as_volts(), 2.5f32);
+    fn test_retry_policy() {
+        let addr = 0x42;
+        let expectations = [
+            // First attempt: NACK
+            Transaction::write(addr, vec![0b10000000])
+                .with_error(embedded_hal::i2c::ErrorKind::Other),
+            // Second attempt: Not ready
+            Transaction::write(addr, vec![0b10000000]),
+        ];
+        let not_ready = Transaction::read(addr, vec![0b00000000, 0b00000000, 0b10000000]);
+        let expectations: Vec<_> = expectations
+            .into_iter()
+            .chain(iter::repeat(not_ready).take(NOT_READY_POLLS))
+            .chain([
+                // Third attempt: The result of the running conversion is
+                // read again
+                Transaction::read(addr, vec![0b00000000, 0b00000000, 0b00000000]),
+            ])
+            .collect();
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        adc.set_retry_policy(RetryPolicy::new(3, 5));
+        adc.measure(&Config::default()).expect("Measuring failed");
+        adc.destroy().done();
+    }
 
-        let b = Voltage::from_millivolts(-100);
-        assert_eq!(b.as_millivolts(), -100i16);
-        assert_eq!(b.as_volts(), -0.1f32);
+    /// The first error is returned once all attempts are exhausted.
+    #[test]
+    fn test_retry_policy_exhausted() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000000, 0b00000000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000000, 0b10000000]),
+            Transaction::read(addr, vec![0b00000000, 0b00000000, 0b00000000])
+                .with_error(embedded_hal::i2c::ErrorKind::Other),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        adc.set_retry_policy(RetryPolicy::new(2, 1));
+        adc.set_config(&Config::default()).unwrap();
+        let err = adc.read_measurement().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        adc.destroy().done();
     }
 
-    /// Instantiation in one-shot mode should not do any calls to the I2C bus.
+    /// A stale saturated result is reported as not ready.
     #[test]
-    fn test_instantiation_oneshot() {
-        let expectations = [];
+    fn test_read_measurement_stale_saturated() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00010000]),
+            Transaction::read(0x68, vec![0, 0, 0b00010000]),
+            Transaction::read(0x68, vec![0x07, 0xFF, 0b10010000]),
+        ];
         let dev = I2cMock::new(&expectations);
-        let adc = MCP3425::oneshot(dev, 0x42, NoopDelay);
+        let mut adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        adc.set_config(&Config::default()).unwrap();
+        let err = adc.read_measurement().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
         adc.destroy().done();
     }
 
-    /// Instantiation in continuous mode should not do any calls to the I2C bus.
+    /// Batch measurements skip stale results.
     #[test]
-    fn test_instantiation_continuous() {
-        let expectations = [];
+    #[cfg(not(feature = "measurements"))]
+    fn test_measure_into() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            // First sample: Not yet ready, then ready
+            Transaction::read(addr, vec![0, 0, 0b10010000]),
+            Transaction::read(addr, vec![0, 1, 0b00010000]),
+            // Second sample: Ready
+            Transaction::read(addr, vec![0, 2, 0b00010000]),
+        ];
         let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let mut voltages = [Voltage::from_millivolts(0); 2];
+        adc.measure_into(&Config::default(), &mut voltages).unwrap();
+        assert_eq!(voltages[0].as_millivolts(), 1);
+        assert_eq!(voltages[1].as_millivolts(), 2);
+        adc.destroy().done();
+    }
 
-        let adc = MCP3425::continuous(dev, 0x42, NoopDelay);
+    /// Saturated codes are not an error in raw batch measurements.
+    #[test]
+    fn test_measure_into_raw() {
+        let addr = 0x42;
+        let expectations = [
+            Transaction::write(addr, vec![0b00010000]),
+            Transaction::read(addr, vec![0, 0, 0b00010000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0b10010000]),
+            Transaction::read(addr, vec![0x07, 0xFF, 0b00010000]),
+            Transaction::read(addr, vec![0xFF, 0xFE, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, addr, NoopDelay);
+        let mut codes = [0; 2];
+        adc.measure_into_raw(&Config::default(), &mut codes)
+            .unwrap();
+        assert_eq!(codes, [2047, -2]);
         adc.destroy().done();
     }
 
-    /// Successfully measuring a voltage with default configuration.
+    /// Measuring at 18 bits reads three data bytes.
     #[rstest]
-    #[case(0b00000111, 0b11111110, 2046)] // Maximum (at 12 bits) - 1
-    #[case(0b00000000, 0b00000111, 7)]
-    #[case(0b00000000, 0b00000000, 0)]
-    #[case(0b11111111, 0b11111111, -1)]
-    #[case(0b11111000, 0b00000001, -2047)] // Minimum (at 12 bits) + 1
-    #[cfg(not(feature = "measurements"))]
-    fn test_read_voltage_oneshot(
+    #[case(0b00000001, 0b11111111, 0b11111110, 2047)] // Maximum - 1
+    #[case(0b00000000, 0b00000000, 0b01000000, 1)]
+    #[case(0b11111111, 0b11111111, 0b11111111, 0)] // Rounds towards zero
+    #[case(0b11111110, 0b00000000, 0b00000001, -2047)] // Minimum + 1
+    #[cfg(all(feature = "eighteen_bit", not(feature = "measurements")))]
+    fn test_read_voltage_18bit(
         #[case] byte0: u8,
         #[case] byte1: u8,
+        #[case] byte2: u8,
         #[case] expected_millivolts: i16,
     ) {
-        let addr = 0x42;
+        let addr = 0x68;
         let expectations = [
-            // Write default config to config register:
-            // - Bit 7: Initiate new conversion
-            // - Bits 6-5: Channel selection (first channel)
-            // - Bit 4: One-shot conversion mode
-            // - Bits 3-2: Set sample rate 240 SPS
-            // - Bits 1-0: Set PGA gain to 1
-            Transaction::write(addr, vec![0b10000000]),
-            // Device returns data
-            Transaction::read(addr, vec![byte0, byte1, 0b00000000]),
+            Transaction::write(addr, vec![0b10001100]),
+            Transaction::read(addr, vec![byte0, byte1, byte2, 0b00001100]),
         ];
         let dev = I2cMock::new(&expectations);
         let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
-        let voltage = adc.measure(&Config::default()).expect("Measuring failed");
+        let config = Config::default().with_resolution(Resolution::Bits18Sps3_75);
+        let voltage = adc.measure(&config).expect("Measuring failed");
         assert_eq!(voltage.as_millivolts(), expected_millivolts);
         adc.destroy().done();
     }
 
-    /// Test saturation at various resolutions.
     #[rstest]
-    #[case(Resolution::Bits12Sps240, 0b10000000, 0b11111000, 0b00000111)] // 12 bits
-    #[case(Resolution::Bits14Sps60, 0b10000100, 0b11100000, 0b00011111)] // 14 bits
-    #[case(Resolution::Bits16Sps15, 0b10001000, 0b10000000, 0b01111111)] // 16 bits
-    fn test_saturation(
-        #[case] resolution: Resolution,
-        #[case] config: u8,
-        #[case] upper_byte_negative: u8,
-        #[case] upper_byte_positive: u8,
-    ) {
-        let addr = 0x42;
+    #[case(vec![0, 0, 0x10, 0x10], Presence::Ready)]
+    #[case(vec![0, 0, 0x90, 0x90], Presence::Converting)]
+    #[case(vec![0, 0, 0, 0x9C], Presence::Converting)]
+    fn test_ping(#[case] data: Vec<u8>, #[case] expected: Presence) {
+        let expectations = [Transaction::read(0x68, data)];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        assert_eq!(adc.ping().unwrap(), expected);
+        // A consumed result is counted
+        assert_eq!(adc.sequence(), u32::from(expected == Presence::Ready));
+        adc.destroy().done();
+    }
+
+    #[test]
+    fn test_ping_absent() {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
         let expectations = [
-            // Write config
-            Transaction::write(addr, vec![config]),
-            // Device returns data: Negative saturation
-            Transaction::read(addr, vec![upper_byte_negative, 0b00000000, 0b00000000]),
-            // Write config
-            Transaction::write(addr, vec![config]),
-            // Device returns data: Positive saturation
-            Transaction::read(addr, vec![upper_byte_positive, 0b11111111, 0b00000000]),
+            Transaction::read(0x68, vec![0, 0, 0, 0]).with_error(nack),
+            Transaction::read(0x68, vec![0, 0, 0, 0]).with_error(ErrorKind::Bus),
         ];
         let dev = I2cMock::new(&expectations);
-        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
+        let mut adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        assert_eq!(adc.ping().unwrap(), Presence::Absent);
+        let err = adc.ping().unwrap_err();
+        assert!(matches!(err, Error::I2c(ErrorKind::Bus)), "{:?}", err);
+        adc.destroy().done();
+    }
 
-        // Test negative saturation
-        let err_negative = adc
-            .measure(&Config::default().with_resolution(resolution))
-            .unwrap_err();
+    #[test]
+    fn test_read_status() {
+        let expectations = [
+            Transaction::read(0x68, vec![0x12, 0x34, 0x19, 0x19]),
+            Transaction::read(0x68, vec![0x12, 0x34, 0x99, 0x99]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        assert!(adc.read_status().unwrap().ready);
+        assert_eq!(adc.sequence(), 1);
+        let status = adc.read_status().unwrap();
+        assert_eq!(adc.sequence(), 1);
+        assert_eq!(
+            status,
+            DeviceStatus {
+                ready: false,
+                continuous: true,
+                resolution: Resolution::Bits16Sps15,
+                gain: Gain::Gain2,
+                channel: Channel::Channel1,
+            }
+        );
+        adc.destroy().done();
+    }
+
+    /// A configuration that is not latched by the device is detected.
+    #[test]
+    fn test_set_config_verified() {
+        let config = Config::default().with_gain(Gain::Gain4);
+        let expectations = [
+            Transaction::write(0x68, vec![0b00010010]),
+            Transaction::read(0x68, vec![0, 0, 0x92, 0x92]),
+            Transaction::read(0x68, vec![0, 0, 0x12]),
+            Transaction::write(0x68, vec![0b00010010]),
+            Transaction::read(0x68, vec![0, 0, 0x90, 0x90]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        adc.set_config_verified(&config).unwrap();
+
+        let err = adc.set_config_verified(&config).unwrap_err();
         assert!(
-            matches!(err_negative, Error::VoltageTooLow),
+            matches!(
+                err,
+                Error::ConfigMismatch {
+                    expected: 0b00010010,
+                    actual: 0b00010000
+                }
+            ),
             "{:?}",
-            err_negative
+            err
         );
+        let err = adc.read_measurement().unwrap_err();
+        assert!(matches!(err, ReadError::NotInitialized), "{:?}", err);
 
-        // Test positive saturation
-        let err_positive = adc
-            .measure(&Config::default().with_resolution(resolution))
-            .unwrap_err();
+        adc.destroy().done();
+    }
+
+    /// Selecting a channel the model does not have fails without accessing
+    /// the bus.
+    #[test]
+    #[cfg(feature = "dual_channel")]
+    fn test_channel_not_supported() {
+        let dev = I2cMock::new(&[]);
+        let config = Config::default().with_channel(Channel::Channel2);
+
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay).with_model(model::Mcp3425);
+        let err = adc.measure(&config).unwrap_err();
         assert!(
-            matches!(err_positive, Error::VoltageTooHigh),
+            matches!(err, MeasureError::ChannelNotSupported),
             "{:?}",
-            err_positive
+            err
         );
 
+        let mut adc = adc.into_continuous();
+        let err = adc.set_config(&config).unwrap_err();
+        assert!(matches!(err, Error::ChannelNotSupported), "{:?}", err);
+
         adc.destroy().done();
     }
 
-    /// Test the "not ready" response handling.
-    #[rstest]
-    fn test_not_ready() {
-        let addr = 0x42;
-        let default_config = 0b10000000;
+    /// Two devices can be started simultaneously with a general call.
+    #[test]
+    fn test_general_call_conversion() {
         let expectations = [
-            // Write config
-            Transaction::write(addr, vec![default_config]),
-            // First bit in returned config register is set to 1 (not ready)
-            Transaction::read(addr, vec![0b00000000, 0b00000000, 0b10000000]),
+            Transaction::write(0x68, vec![0b00000000]),
+            Transaction::write(0x69, vec![0b00000000]),
+            Transaction::write(0x00, vec![0b00001000]),
+            Transaction::read(0x68, vec![0, 0, 0]),
+            Transaction::read(0x69, vec![0, 0, 0b10000000]),
         ];
-        let dev = I2cMock::new(&expectations);
-        let mut adc = MCP3425::oneshot(dev, addr, NoopDelay);
-
-        let err = adc.measure(&Config::default()).unwrap_err();
-        assert!(matches!(err, Error::NotReady), "{:?}", err);
-
-        adc.destroy().done();
+        let mut bus = I2cMock::new(&expectations);
+        let mut adc1 = MCP3425::oneshot(bus.clone(), 0x68, NoopDelay);
+        let mut adc2 = MCP3425::oneshot(bus.clone(), 0x69, NoopDelay);
+        adc1.prepare(&Config::default()).unwrap();
+        adc2.prepare(&Config::default()).unwrap();
+        general_call_conversion(&mut bus).unwrap();
+        adc1.read_prepared().unwrap();
+        let err = adc2.read_prepared().unwrap_err();
+        assert!(matches!(err, ReadError::NotReady), "{:?}", err);
+        bus.done();
     }
 
     /// Test that the configs are written correctly.
@@ -0,0 +1,172 @@
+//! Zero-sized device model markers for compile-time channel checking.
+//!
+//! The driver is generic over the device model. By default, the
+//! [`AnyModel`](struct.AnyModel.html) marker is used, which does not restrict
+//! the channels that can be selected. Use
+//! [`with_model`](../struct.MCP3425.html#method.with_model) to tell the
+//! driver which model it is talking to. Then channels can be selected with
+//! [`channel_config`](../struct.MCP3425.html#method.channel_config), which only
+//! compiles if the model actually has that channel:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock as I2cMock};
+//! # let dev = I2cMock::new(&[]);
+//! use mcp3425::model::{Ch1, Mcp3425};
+//! use mcp3425::{Config, MCP3425};
+//!
+//! let adc = MCP3425::oneshot(dev, 0x68, NoopDelay).with_model(Mcp3425);
+//! let config = adc.channel_config(&Config::default(), Ch1);
+//! # adc.destroy().done();
+//! ```
+//!
+//! Which channels a model has only depends on the model type, see
+//! [`HasChannel`](trait.HasChannel.html). Selecting a channel the model does
+//! not have is a compile error:
+//!
+//! ```compile_fail
+//! use mcp3425::model::{Ch3, HasChannel, Mcp3426};
+//!
+//! fn has_channel<M: HasChannel<C>, C>() {}
+//! has_channel::<Mcp3426, Ch3>();
+//! ```
+//!
+//! Note: To select the second to fourth channel with `channel_config`, the
+//! markers must also implement [`ChannelMarker`](trait.ChannelMarker.html),
+//! which requires the `dual_channel` or `quad_channel` Cargo features, just
+//! like the corresponding [`Channel`](../enum.Channel.html) variants.
+
+use crate::Channel;
+
+/// A device model of the MCP342x family.
+pub trait Model {
+    /// Number of input channels of this model.
+    const CHANNELS: u8;
+    /// Highest supported resolution in bits.
+    const MAX_RESOLUTION_BITS: u8 = 18;
+}
+
+/// A channel that can be selected at compile time.
+pub trait ChannelMarker {
+    /// The corresponding channel.
+    const CHANNEL: Channel;
+}
+
+/// Implemented by device models that have the input channel `C`.
+///
+/// This only depends on the model, not on Cargo features:
+///
+/// ```
+/// use mcp3425::model::{Ch3, HasChannel, Mcp3428};
+///
+/// fn has_channel<M: HasChannel<C>, C>() {}
+/// has_channel::<Mcp3428, Ch3>();
+/// ```
+pub trait HasChannel<C>: Model {}
+
+/// Unknown device model. All channels enabled by Cargo features can be
+/// selected.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct AnyModel;
+
+/// MCP3425: One channel, 16 bits.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3425;
+
+/// MCP3426: Two channels, 16 bits.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3426;
+
+/// MCP3427: Two channels, 16 bits, configurable address.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3427;
+
+/// MCP3428: Four channels, 16 bits, configurable address.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3428;
+
+/// MCP3421: One channel, 18 bits.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3421;
+
+/// MCP3422: Two channels, 18 bits.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3422;
+
+/// MCP3423: Two channels, 18 bits, configurable address.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3423;
+
+/// MCP3424: Four channels, 18 bits, configurable address.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mcp3424;
+
+impl Model for AnyModel {
+    const CHANNELS: u8 = 4;
+    const MAX_RESOLUTION_BITS: u8 = 18;
+}
+
+macro_rules! impl_model {
+    ($channels:expr, $bits:expr; $($model:ident),+) => {
+        $(
+            impl Model for $model {
+                const CHANNELS: u8 = $channels;
+                const MAX_RESOLUTION_BITS: u8 = $bits;
+            }
+        )+
+    };
+}
+
+impl_model!(1, 18; Mcp3421);
+impl_model!(2, 18; Mcp3422, Mcp3423);
+impl_model!(4, 18; Mcp3424);
+impl_model!(1, 16; Mcp3425);
+impl_model!(2, 16; Mcp3426, Mcp3427);
+impl_model!(4, 16; Mcp3428);
+
+/// First channel
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Ch1;
+
+impl ChannelMarker for Ch1 {
+    const CHANNEL: Channel = Channel::Channel1;
+}
+
+/// Second channel
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Ch2;
+
+#[cfg(any(feature = "dual_channel", feature = "quad_channel", doc))]
+impl ChannelMarker for Ch2 {
+    const CHANNEL: Channel = Channel::Channel2;
+}
+
+/// Third channel
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Ch3;
+
+#[cfg(any(feature = "quad_channel", doc))]
+impl ChannelMarker for Ch3 {
+    const CHANNEL: Channel = Channel::Channel3;
+}
+
+/// Fourth channel
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Ch4;
+
+#[cfg(any(feature = "quad_channel", doc))]
+impl ChannelMarker for Ch4 {
+    const CHANNEL: Channel = Channel::Channel4;
+}
+
+macro_rules! impl_has_channel {
+    ($channel:ident; $($model:ident),+) => {
+        $(
+            impl HasChannel<$channel> for $model {}
+        )+
+    };
+}
+
+impl_has_channel!(Ch1; AnyModel, Mcp3421, Mcp3422, Mcp3423, Mcp3424, Mcp3425, Mcp3426, Mcp3427, Mcp3428);
+impl_has_channel!(Ch2; AnyModel, Mcp3422, Mcp3423, Mcp3424, Mcp3426, Mcp3427, Mcp3428);
+impl_has_channel!(Ch3; AnyModel, Mcp3424, Mcp3428);
+impl_has_channel!(Ch4; AnyModel, Mcp3424, Mcp3428);
@@ -0,0 +1,333 @@
+//! Reporting of relevant changes of the input.
+//!
+//! A [`ThresholdMonitor`](struct.ThresholdMonitor.html) classifies readings
+//! against a low and a high limit with hysteresis, e.g. for undervoltage or
+//! overcurrent shutdowns.
+//!
+//! A [`DeadbandReporter`](struct.DeadbandReporter.html) wraps any
+//! [`AdcReader`](../trait.AdcReader.html) and only reports a reading if it
+//! differs enough from the last reported one, e.g. to save bandwidth on a
+//! telemetry link:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{monitor::DeadbandReporter, Config, MCP3425};
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 100, 0]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! let adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let mut reporter = DeadbandReporter::new(adc, 5);
+//! if let Some(voltage) = reporter.read(&Config::default()).unwrap() {
+//!     // Send the voltage
+//! #   let _ = voltage;
+//! }
+//! # dev.done();
+//! ```
+
+use crate::{voltage_to_microvolts, AdcReader, Config, Voltage};
+
+/// Wrapper that suppresses readings within a deadband around the last
+/// reported reading.
+#[derive(Debug)]
+pub struct DeadbandReporter<R> {
+    /// The wrapped driver.
+    reader: R,
+    /// Minimum change in millivolts that is reported.
+    delta_mv: u16,
+    /// The last reported voltage.
+    last: Option<Voltage>,
+}
+
+impl<R: AdcReader> DeadbandReporter<R> {
+    /// Create a reporter that reports readings which differ by more than
+    /// `delta_mv` millivolts from the last reported reading.
+    pub fn new(reader: R, delta_mv: u16) -> Self {
+        DeadbandReporter {
+            reader,
+            delta_mv,
+            last: None,
+        }
+    }
+
+    /// Read a measurement and return it if it should be reported.
+    ///
+    /// The first reading is always reported.
+    pub fn read(&mut self, config: &Config) -> Result<Option<Voltage>, R::Error> {
+        let voltage = self.reader.read(config)?;
+        Ok(self.filter(voltage))
+    }
+
+    /// Return `voltage` if it should be reported, e.g. for readings that
+    /// were not taken through this reporter.
+    pub fn filter(&mut self, voltage: Voltage) -> Option<Voltage> {
+        let changed = match &self.last {
+            None => true,
+            Some(last) => {
+                let difference = i64::from(voltage_to_microvolts(&voltage))
+                    - i64::from(voltage_to_microvolts(last));
+                difference.unsigned_abs() > u64::from(self.delta_mv) * 1000
+            }
+        };
+        if changed {
+            self.last = Some(voltage);
+            Some(voltage)
+        } else {
+            None
+        }
+    }
+
+    /// Return the last reported voltage.
+    pub fn last_reported(&self) -> Option<Voltage> {
+        self.last
+    }
+
+    /// Forget the last reported voltage, so the next reading is reported.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// Destroy the reporter and return the driver.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Classification of a reading by a
+/// [`ThresholdMonitor`](struct.ThresholdMonitor.html).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Level {
+    /// Between the limits.
+    #[default]
+    Normal,
+    /// At or above the high limit.
+    High,
+    /// At or below the low limit.
+    Low,
+}
+
+/// A change of the [`Level`](enum.Level.html) of a monitored input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Crossing {
+    /// The level before the reading.
+    pub from: Level,
+    /// The level after the reading.
+    pub to: Level,
+}
+
+/// Classification of readings against a low and a high limit.
+///
+/// A reading at or above the high limit switches to
+/// [`Level::High`](enum.Level.html#variant.High), a reading at or below the
+/// low limit to [`Level::Low`](enum.Level.html#variant.Low). To return to
+/// [`Level::Normal`](enum.Level.html#variant.Normal), the reading must be
+/// back inside the limits by more than the hysteresis, so a noisy input
+/// close to a limit does not toggle the level on every reading.
+///
+/// ```
+/// use mcp3425::monitor::{Level, ThresholdMonitor};
+///
+/// // Battery undervoltage below 1100mV, recovery above 1150mV
+/// let mut monitor = ThresholdMonitor::new(1_100_000, 2_000_000, 50_000);
+/// # #[cfg(not(feature = "measurements"))]
+/// # {
+/// # use mcp3425::Voltage;
+/// let crossing = monitor.update(Voltage::from_millivolts(1_050)).unwrap();
+/// assert_eq!(crossing.to, Level::Low);
+/// assert_eq!(monitor.update(Voltage::from_millivolts(1_120)), None);
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ThresholdMonitor {
+    /// Low limit in microvolts.
+    low_uv: i32,
+    /// High limit in microvolts.
+    high_uv: i32,
+    /// Hysteresis in microvolts.
+    hysteresis_uv: u32,
+    /// The current level.
+    level: Level,
+}
+
+impl ThresholdMonitor {
+    /// Create a monitor with the limits and hysteresis in microvolts,
+    /// starting at [`Level::Normal`](enum.Level.html#variant.Normal).
+    ///
+    /// The limits apply to the calibrated voltage, so they can be far
+    /// outside of the input range of the device, e.g. a 48V limit behind a
+    /// voltage divider.
+    ///
+    /// Panics if the low limit is not below the high limit.
+    pub const fn new(low_uv: i32, high_uv: i32, hysteresis_uv: u32) -> Self {
+        assert!(low_uv < high_uv, "low limit must be below high limit");
+        ThresholdMonitor {
+            low_uv,
+            high_uv,
+            hysteresis_uv,
+            level: Level::Normal,
+        }
+    }
+
+    /// Return the current level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Classify a reading and return the crossing if the level changed.
+    pub fn update(&mut self, voltage: Voltage) -> Option<Crossing> {
+        let microvolts = voltage_to_microvolts(&voltage);
+        let level = if microvolts >= self.high_uv {
+            Level::High
+        } else if microvolts <= self.low_uv {
+            Level::Low
+        } else {
+            // Widen, so that a large hysteresis can't overflow
+            let microvolts = i64::from(microvolts);
+            let hysteresis_uv = i64::from(self.hysteresis_uv);
+            match self.level {
+                Level::High if microvolts >= i64::from(self.high_uv) - hysteresis_uv => Level::High,
+                Level::Low if microvolts <= i64::from(self.low_uv) + hysteresis_uv => Level::Low,
+                _ => Level::Normal,
+            }
+        };
+        let from = self.level;
+        self.level = level;
+        (from != level).then_some(Crossing { from, to: level })
+    }
+
+    /// Read measurements until the level changes, and return the crossing.
+    ///
+    /// This blocks until a crossing occurs. The first failing reading aborts
+    /// the wait.
+    pub fn wait_for_crossing<R: AdcReader>(
+        &mut self,
+        reader: &mut R,
+        config: &Config,
+    ) -> Result<Crossing, R::Error> {
+        loop {
+            if let Some(crossing) = self.update(reader.read(config)?) {
+                return Ok(crossing);
+            }
+        }
+    }
+
+    /// Measure with the async one-shot driver until the level changes, and
+    /// return the crossing.
+    ///
+    /// The first failing measurement aborts the wait. Only available if the
+    /// `async` Cargo feature is enabled.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_crossing_async<I2C, D, DEV>(
+        &mut self,
+        adc: &mut crate::asynch::MCP3425<I2C, D, crate::OneShotMode, DEV>,
+        config: &Config,
+    ) -> Result<Crossing, crate::MeasureError<I2C::Error>>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: crate::asynch::Wait,
+        DEV: crate::model::Model,
+    {
+        loop {
+            if let Some(crossing) = self.update(adc.measure(config).await?) {
+                return Ok(crossing);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock as I2cMock};
+
+    use super::*;
+    use crate::tests::oneshot_expectations;
+    use crate::{voltage_from_microvolts, MCP3425};
+
+    fn mv(millivolts: i32) -> Voltage {
+        voltage_from_microvolts(millivolts * 1000)
+    }
+
+    #[test]
+    fn test_deadband() {
+        let expectations = oneshot_expectations(0x68, 0, &[100, 104, 106]);
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let mut reporter = DeadbandReporter::new(adc, 5);
+        let config = Config::default();
+        assert!(reporter.read(&config).unwrap().is_some());
+        assert_eq!(reporter.read(&config).unwrap(), None);
+        assert!(reporter.read(&config).unwrap().is_some());
+        reporter.into_inner().destroy().done();
+    }
+
+    /// Changes are measured from the last reported value, so slow drifts are
+    /// reported eventually.
+    #[test]
+    fn test_deadband_drift() {
+        let bus = I2cMock::new(&[]);
+        let mut reporter = DeadbandReporter::new(MCP3425::oneshot(bus, 0x68, NoopDelay), 2);
+        assert_eq!(reporter.filter(mv(0)), Some(mv(0)));
+        assert_eq!(reporter.filter(mv(2)), None);
+        assert_eq!(reporter.filter(mv(-2)), None);
+        assert_eq!(reporter.filter(mv(3)), Some(mv(3)));
+        assert_eq!(reporter.last_reported(), Some(mv(3)));
+        reporter.reset();
+        assert_eq!(reporter.filter(mv(3)), Some(mv(3)));
+        reporter.into_inner().destroy().done();
+    }
+
+    #[test]
+    fn test_threshold_hysteresis() {
+        let mut monitor = ThresholdMonitor::new(-100_000, 100_000, 10_000);
+        assert_eq!(monitor.update(mv(50)), None);
+        let crossing = monitor.update(mv(100)).unwrap();
+        assert_eq!((crossing.from, crossing.to), (Level::Normal, Level::High));
+        assert_eq!(monitor.update(mv(95)), None);
+        assert_eq!(monitor.update(mv(89)).unwrap().to, Level::Normal);
+        assert_eq!(monitor.update(mv(-105)).unwrap().to, Level::Low);
+        assert_eq!(monitor.update(mv(-91)), None);
+        // Directly from low to high
+        let crossing = monitor.update(mv(200)).unwrap();
+        assert_eq!((crossing.from, crossing.to), (Level::Low, Level::High));
+    }
+
+    /// Limits beyond the input range of the device apply to calibrated
+    /// readings, e.g. behind a voltage divider.
+    #[test]
+    fn test_threshold_beyond_input_range() {
+        let mut monitor = ThresholdMonitor::new(40_000_000, 48_000_000, 500_000);
+        assert_eq!(monitor.update(mv(48_000)).unwrap().to, Level::High);
+        assert_eq!(monitor.update(mv(47_600)), None);
+        assert_eq!(monitor.update(mv(47_400)).unwrap().to, Level::Normal);
+    }
+
+    #[test]
+    fn test_wait_for_crossing() {
+        let expectations = oneshot_expectations(0x68, 0, &[10, 20, 30]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let mut monitor = ThresholdMonitor::new(0, 25_000, 0);
+        let crossing = monitor
+            .wait_for_crossing(&mut adc, &Config::default())
+            .unwrap();
+        assert_eq!(crossing.to, Level::High);
+        adc.destroy().done();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_wait_for_crossing_async() {
+        use futures::executor::block_on;
+
+        let expectations = oneshot_expectations(0x68, 0, &[-16]);
+        let dev = I2cMock::new(&expectations);
+        let mut adc = crate::asynch::MCP3425::oneshot(dev.clone(), 0x68, NoopDelay);
+        let mut monitor = ThresholdMonitor::new(0, 25_000, 0);
+        let crossing =
+            block_on(monitor.wait_for_crossing_async(&mut adc, &Config::default())).unwrap();
+        assert_eq!(crossing.to, Level::Low);
+        dev.clone().done();
+    }
+}
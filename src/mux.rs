@@ -0,0 +1,192 @@
+//! Support for devices behind an I²C multiplexer.
+//!
+//! Since all MCP3425 devices share the same address, a multiplexer such as
+//! the TCA9548A is needed to put more than one of them on a bus. A
+//! [`MuxedI2c`](struct.MuxedI2c.html) wraps a bus handle and selects the
+//! multiplexer channel of its device before every transaction, so the driver
+//! can be used as if the device was connected directly:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{mux::{MuxedI2c, Tca9548a}, Config, MCP3425};
+//!
+//! # let bus = Mock::new(&[
+//! #     Transaction::write(0x70, vec![1 << 3]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::write(0x70, vec![1 << 3]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! # ]);
+//! # let i2c = bus.clone();
+//! let i2c = MuxedI2c::new(i2c, Tca9548a::new(0x70, 3));
+//! let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let voltage = adc.measure(&Config::default()).unwrap();
+//! # bus.clone().done();
+//! ```
+//!
+//! To use several devices behind the same multiplexer, give each
+//! `MuxedI2c` its own handle to a shared bus (see the `shared_bus` feature).
+
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+
+/// Selects the bus segment of a device before it is accessed.
+pub trait BusSelector {
+    /// Make the device reachable on `i2c`.
+    fn select<I: I2c>(&mut self, i2c: &mut I) -> Result<(), I::Error>;
+}
+
+/// A channel of a TCA9548A (or compatible PCA9548A) I²C multiplexer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tca9548a {
+    /// I²C address of the multiplexer.
+    address: u8,
+    /// Selected channel (0–7).
+    channel: u8,
+}
+
+impl Tca9548a {
+    /// Select `channel` (0–7) of the multiplexer at `address`.
+    ///
+    /// Panics if `channel` is greater than 7.
+    pub fn new(address: u8, channel: u8) -> Self {
+        assert!(channel < 8, "TCA9548A channel out of range: {}", channel);
+        Tca9548a { address, channel }
+    }
+
+    /// Return the selected channel.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+}
+
+impl BusSelector for Tca9548a {
+    fn select<I: I2c>(&mut self, i2c: &mut I) -> Result<(), I::Error> {
+        i2c.write(self.address, &[1 << self.channel])
+    }
+}
+
+/// An I²C bus handle that selects a bus segment before every transaction.
+#[derive(Debug)]
+pub struct MuxedI2c<I2C, S> {
+    /// The underlying bus.
+    i2c: I2C,
+    /// The selector for the bus segment of the device.
+    selector: S,
+}
+
+impl<I2C, S> MuxedI2c<I2C, S>
+where
+    I2C: I2c,
+    S: BusSelector,
+{
+    /// Wrap `i2c`, selecting the segment with `selector` before each
+    /// transaction.
+    pub fn new(i2c: I2C, selector: S) -> Self {
+        MuxedI2c { i2c, selector }
+    }
+
+    /// Destroy the wrapper and return the bus and the selector.
+    pub fn release(self) -> (I2C, S) {
+        (self.i2c, self.selector)
+    }
+}
+
+impl<I2C: ErrorType, S> ErrorType for MuxedI2c<I2C, S> {
+    type Error = I2C::Error;
+}
+
+impl<I2C, S> I2c for MuxedI2c<I2C, S>
+where
+    I2C: I2c,
+    S: BusSelector,
+{
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c)?;
+        self.i2c.read(address, read)
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c)?;
+        self.i2c.write(address, write)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c)?;
+        self.i2c.write_read(address, write, read)
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.selector.select(&mut self.i2c)?;
+        self.i2c.transaction(address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use super::*;
+    use crate::{Config, MeasureError, MCP3425};
+
+    /// Two devices with the same address on different multiplexer channels.
+    #[test]
+    fn test_select_before_each_transaction() {
+        let expectations = [
+            Transaction::write(0x70, vec![0b00000001]),
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::write(0x70, vec![0b00000001]),
+            Transaction::read(0x68, vec![0, 0, 0]),
+            Transaction::write(0x70, vec![0b10000000]),
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::write(0x70, vec![0b10000000]),
+            Transaction::read(0x68, vec![0, 0, 0]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let i2c1 = MuxedI2c::new(bus.clone(), Tca9548a::new(0x70, 0));
+        let i2c2 = MuxedI2c::new(bus.clone(), Tca9548a::new(0x70, 7));
+        let mut adc1 = MCP3425::oneshot(i2c1, 0x68, NoopDelay);
+        let mut adc2 = MCP3425::oneshot(i2c2, 0x68, NoopDelay);
+
+        adc1.measure(&Config::default()).unwrap();
+        adc2.measure(&Config::default()).unwrap();
+
+        bus.clone().done();
+    }
+
+    /// If the multiplexer does not respond, the device is not accessed.
+    #[test]
+    fn test_select_error() {
+        let expectations =
+            [Transaction::write(0x70, vec![0b00000100]).with_error(ErrorKind::Other)];
+        let bus = I2cMock::new(&expectations);
+        let i2c = MuxedI2c::new(bus.clone(), Tca9548a::new(0x70, 2));
+        let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+
+        let err = adc.measure(&Config::default()).unwrap_err();
+        assert!(
+            matches!(err, MeasureError::I2c(ErrorKind::Other)),
+            "{:?}",
+            err
+        );
+
+        bus.clone().done();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_channel() {
+        Tca9548a::new(0x70, 8);
+    }
+}
@@ -0,0 +1,134 @@
+//! Conversion of measurements to user-defined types.
+//!
+//! The driver returns a [`Voltage`](../struct.Voltage.html) by default. A
+//! type implementing [`FromAdcCode`](trait.FromAdcCode.html) can be returned
+//! directly instead, e.g. the temperature of an analog sensor or the current
+//! through a shunt, with
+//! [`measure_as`](../struct.MCP3425.html#method.measure_as) and
+//! [`read_measurement_as`](../struct.MCP3425.html#method.read_measurement_as):
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{output::FromAdcCode, Config, MCP3425};
+//!
+//! /// Temperature of an LM35 sensor (10mV/°C) in hundredths of a degree.
+//! struct Centidegrees(i32);
+//!
+//! impl FromAdcCode for Centidegrees {
+//!     fn from_adc_code(_code: i32, microvolts: i32, _config: &Config) -> Self {
+//!         Centidegrees(microvolts / 100)
+//!     }
+//! }
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0x00, 0xFA, 0]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let temperature: Centidegrees = adc.measure_as(&Config::default()).unwrap();
+//! assert_eq!(temperature.0, 2_500);
+//! # dev.done();
+//! ```
+
+use crate::{voltage_from_microvolts, Config, Voltage};
+
+/// A type that a conversion result can be converted to.
+pub trait FromAdcCode: Sized {
+    /// Convert a conversion result.
+    ///
+    /// `code` is the signed output code at the resolution of `config`, and
+    /// `microvolts` the input voltage with the calibration of `config` and
+    /// the temperature compensation of the driver applied. Saturated codes
+    /// are handled by the driver according to the
+    /// [`SaturationPolicy`](../enum.SaturationPolicy.html) before, with
+    /// `SaturationPolicy::Clamp`, `microvolts` is the full-scale voltage.
+    fn from_adc_code(code: i32, microvolts: i32, config: &Config) -> Self;
+}
+
+impl FromAdcCode for Voltage {
+    fn from_adc_code(_code: i32, microvolts: i32, _config: &Config) -> Self {
+        voltage_from_microvolts(microvolts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use super::*;
+    use crate::{MeasureError, SaturationPolicy, MCP3425};
+
+    /// Records the arguments of the conversion.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Raw {
+        code: i32,
+        microvolts: i32,
+    }
+
+    impl FromAdcCode for Raw {
+        fn from_adc_code(code: i32, microvolts: i32, _config: &Config) -> Self {
+            Raw { code, microvolts }
+        }
+    }
+
+    #[test]
+    fn test_measure_as() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0xFF, 0xF6, 0]),
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0x07, 0xFF, 0]),
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0x07, 0xFF, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let config = Config::default();
+        let raw: Raw = adc.measure_as(&config).unwrap();
+        assert_eq!(
+            raw,
+            Raw {
+                code: -10,
+                microvolts: -10_000
+            }
+        );
+        // Saturated codes are handled before the conversion
+        let err = adc.measure_as::<Raw>(&config).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooHigh), "{:?}", err);
+        let config = config.with_saturation_policy(SaturationPolicy::Clamp);
+        let raw: Raw = adc.measure_as(&config).unwrap();
+        assert_eq!(
+            raw,
+            Raw {
+                code: 2047,
+                microvolts: 2_048_000
+            }
+        );
+        adc.destroy().done();
+    }
+
+    #[test]
+    fn test_read_measurement_as() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00010000]),
+            Transaction::read(0x68, vec![0, 0, 0b00010000]),
+            Transaction::read(0x68, vec![0, 42, 0b00010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        adc.set_config(&Config::default()).unwrap();
+        let raw: Raw = adc.read_measurement_as().unwrap();
+        assert_eq!(
+            raw,
+            Raw {
+                code: 42,
+                microvolts: 42_000
+            }
+        );
+        adc.destroy().done();
+    }
+}
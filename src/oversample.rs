@@ -0,0 +1,174 @@
+//! Oversampling and decimation for extra effective resolution.
+//!
+//! An [`Oversampler`](struct.Oversampler.html) with `k` extra bits takes
+//! `4^k` one-shot samples at 12 bits (240 SPS), sums them and divides the sum
+//! by `2^k`. Since the noise of the device is larger than one 12-bit LSB, this
+//! results in a code with `12 + k` effective bits, trading speed for
+//! precision:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{oversample::Oversampler, Config, MCP3425};
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let oversampler = Oversampler::new(1);
+//! let result = oversampler.measure(&mut adc, &Config::default()).unwrap();
+//! assert_eq!(result.bits, 13);
+//! # dev.done();
+//! ```
+//!
+//! Oversampling only improves the resolution if the input is stable during
+//! all samples, and it does not improve the accuracy (offset and gain
+//! errors) of the device.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::model::Model;
+use crate::transport::Transport;
+use crate::{Config, MeasureError, OneShotMode, Resolution, LSB_12BIT_MICROVOLTS, MCP3425};
+
+/// The maximum number of extra bits, which takes 65536 samples.
+pub const MAX_EXTRA_BITS: u8 = 8;
+
+/// The result of an oversampled measurement.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Oversampled {
+    /// The decimated signed output code.
+    pub code: i32,
+    /// The effective resolution of `code` in bits.
+    pub bits: u8,
+    /// The input voltage in microvolts, taking the gain and the calibration
+    /// into account.
+    pub microvolts: i32,
+}
+
+/// Oversampling measurement with a fixed number of extra bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Oversampler {
+    /// Number of extra bits.
+    extra_bits: u8,
+}
+
+impl Oversampler {
+    /// Create an oversampler that gains `extra_bits` bits of resolution,
+    /// taking `4^extra_bits` samples per measurement.
+    ///
+    /// Panics if `extra_bits` is larger than
+    /// [`MAX_EXTRA_BITS`](constant.MAX_EXTRA_BITS.html).
+    pub const fn new(extra_bits: u8) -> Self {
+        assert!(extra_bits <= MAX_EXTRA_BITS, "too many extra bits");
+        Oversampler { extra_bits }
+    }
+
+    /// Return the number of samples per measurement.
+    pub const fn sample_count(&self) -> u32 {
+        1 << (2 * self.extra_bits)
+    }
+
+    /// Do an oversampled measurement with the gain and channel of `config`.
+    ///
+    /// The resolution of `config` is ignored, all samples are taken at 12
+    /// bits. If a sample is saturated, the
+    /// [`SaturationPolicy`](../enum.SaturationPolicy.html) of `config` is
+    /// applied to it: The default policy aborts the measurement with
+    /// [`MeasureError::VoltageTooHigh`](../enum.MeasureError.html#variant.VoltageTooHigh)
+    /// or `VoltageTooLow`, the other policies accumulate the saturated code.
+    ///
+    /// The first failing sample aborts the measurement. Transient errors are
+    /// retried per sample according to the
+    /// [`RetryPolicy`](../struct.RetryPolicy.html).
+    pub fn measure<I2C, D, DEV>(
+        &self,
+        adc: &mut MCP3425<I2C, D, OneShotMode, DEV>,
+        config: &Config,
+    ) -> Result<Oversampled, MeasureError<I2C::Error>>
+    where
+        I2C: Transport,
+        D: DelayNs,
+        DEV: Model,
+    {
+        let config = config.with_resolution(Resolution::Bits12Sps240);
+        let wait = adc.wait;
+        adc.check_channel(&config)?;
+        let mut sum = 0i64;
+        for _ in 0..self.sample_count() {
+            sum += i64::from(adc.measure_checked(&config, wait)?);
+        }
+        Ok(self.decimate(sum, &adc.compensated(&adc.calibrated(&config))))
+    }
+
+    /// Decimate the sum of all sample codes.
+    fn decimate(&self, sum: i64, config: &Config) -> Oversampled {
+        // The sum has at most 12 + 2 * MAX_EXTRA_BITS bits, the decimated
+        // code 12 + MAX_EXTRA_BITS bits, so the conversions can't fail.
+        let code = (sum >> self.extra_bits) as i32;
+        let divisor = (1i64 << self.extra_bits) * i64::from(config.gain.factor());
+        let microvolts = (i64::from(code) * i64::from(LSB_12BIT_MICROVOLTS) / divisor) as i32;
+        let microvolts = config
+            .calibration
+            .map_or(microvolts, |calibration| calibration.apply_uv(microvolts));
+        Oversampled {
+            code,
+            bits: 12 + self.extra_bits,
+            microvolts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock as I2cMock};
+
+    use super::*;
+    use crate::tests::oneshot_expectations;
+    use crate::{Gain, SaturationPolicy};
+
+    /// Four samples at 12 bits give one extra bit.
+    #[test]
+    fn test_oversample() {
+        let dev = I2cMock::new(&oneshot_expectations(0x68, 0b00000001, &[10, 11, 11, 11]));
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let config = Config::default().with_gain(Gain::Gain2);
+        let result = Oversampler::new(1).measure(&mut adc, &config).unwrap();
+        // Sum 43, halved: 21 LSB at 13 bits and gain 2, i.e. 250µV each
+        assert_eq!(
+            result,
+            Oversampled {
+                code: 21,
+                bits: 13,
+                microvolts: 5_250,
+            }
+        );
+        adc.destroy().done();
+    }
+
+    /// Saturated samples abort the measurement by default, and are
+    /// accumulated with the `Raw` policy.
+    #[test]
+    fn test_oversample_saturated() {
+        let dev = I2cMock::new(&oneshot_expectations(0x68, 0b00000001, &[1, 2047]));
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let config = Config::default().with_gain(Gain::Gain2);
+        let err = Oversampler::new(1).measure(&mut adc, &config).unwrap_err();
+        assert!(matches!(err, MeasureError::VoltageTooHigh), "{:?}", err);
+        adc.destroy().done();
+
+        let dev = I2cMock::new(&oneshot_expectations(0x68, 0b00000001, &[2047; 4]));
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let config = config.with_saturation_policy(SaturationPolicy::Raw);
+        let result = Oversampler::new(1).measure(&mut adc, &config).unwrap();
+        assert_eq!(result.code, 2047 * 2);
+        adc.destroy().done();
+    }
+}
@@ -0,0 +1,199 @@
+//! Measurements at a fixed rate.
+//!
+//! A [`Sampler`](struct.Sampler.html) does one-shot measurements at a fixed
+//! period, waiting with the delay of the driver. The time a measurement takes
+//! is read from a [`Clock`](trait.Clock.html) and subtracted from the wait,
+//! so the long-run sample rate is exact instead of drifting by the conversion
+//! time on every sample:
+//!
+//! ```no_run
+//! # fn run<I2C, D, C>(adc: mcp3425::MCP3425<I2C, D, mcp3425::OneShotMode>, clock: C)
+//! # where I2C: embedded_hal::i2c::I2c, D: embedded_hal::delay::DelayNs, C: mcp3425::sampler::Clock
+//! # {
+//! use mcp3425::{sampler::Sampler, Config};
+//!
+//! // Sample once per second
+//! let mut sampler = Sampler::new(adc, Config::default(), 1_000_000, clock);
+//! loop {
+//!     let voltage = sampler.sample();
+//!     // ...
+//! }
+//! # }
+//! ```
+
+use embedded_hal::delay::DelayNs;
+
+use crate::model::Model;
+use crate::transport::Transport;
+use crate::{Config, MeasureError, OneShotMode, Voltage, MCP3425};
+
+/// A monotonic time source.
+///
+/// Used by the [`Sampler`](struct.Sampler.html) and the async
+/// `PeriodicSampler` to measure how much time the measurement itself took,
+/// so that it can be subtracted from the time to wait until the next sample.
+pub trait Clock {
+    /// Return the current time in microseconds.
+    ///
+    /// The origin of the time scale is irrelevant, but the value must never
+    /// decrease.
+    fn now_us(&mut self) -> u64;
+}
+
+/// Perform one-shot measurements at a fixed period.
+///
+/// The sampler keeps an absolute schedule: The time spent in I²C
+/// transactions and waiting for the conversion is not added on top of the
+/// period, so the sample times do not drift over long periods.
+///
+/// If a measurement takes longer than the period, the schedule is
+/// re-anchored at the current time instead of catching up with a burst of
+/// back-to-back samples.
+#[derive(Debug)]
+pub struct Sampler<I2C, D, C, DEV> {
+    /// The one-shot driver performing the measurements.
+    adc: MCP3425<I2C, D, OneShotMode, DEV>,
+    /// The configuration used for every measurement.
+    config: Config,
+    /// The period in microseconds.
+    period_us: u32,
+    /// The time source used for drift compensation.
+    clock: C,
+    /// The time at which the next measurement should be started.
+    next_us: Option<u64>,
+}
+
+impl<I2C, D, C, DEV> Sampler<I2C, D, C, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    C: Clock,
+    DEV: Model,
+{
+    /// Create a new sampler taking a measurement every `period_us`
+    /// microseconds.
+    ///
+    /// The delay implementation of the driver is used to wait between
+    /// samples. The first sample is taken immediately.
+    pub fn new(
+        adc: MCP3425<I2C, D, OneShotMode, DEV>,
+        config: Config,
+        period_us: u32,
+        clock: C,
+    ) -> Self {
+        Sampler {
+            adc,
+            config,
+            period_us,
+            clock,
+            next_us: None,
+        }
+    }
+
+    /// Wait until the next sample is due, then do a one-shot measurement.
+    pub fn sample(&mut self) -> Result<Voltage, MeasureError<I2C::Error>> {
+        let now = self.clock.now_us();
+        let due = *self.next_us.get_or_insert(now);
+        if due > now {
+            let wait_us = (due - now).min(u64::from(self.period_us)) as u32;
+            self.adc.delay.delay_us(wait_us);
+        }
+
+        let result = self.adc.measure(&self.config);
+
+        // Schedule the next sample relative to the previous deadline, not
+        // relative to the end of this measurement.
+        let next = due + u64::from(self.period_us);
+        let now = self.clock.now_us();
+        self.next_us = Some(next.max(now));
+
+        result
+    }
+
+    /// Destroy the sampler and return the driver and the clock.
+    pub fn release(self) -> (MCP3425<I2C, D, OneShotMode, DEV>, C) {
+        (self.adc, self.clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::{CheckedDelay, Transaction as DelayTransaction},
+        i2c::Mock as I2cMock,
+    };
+
+    use super::*;
+    use crate::tests::oneshot_expectations;
+
+    /// A clock that advances by a fixed step every time it is read.
+    struct SteppingClock {
+        now: u64,
+        step: u64,
+    }
+
+    impl Clock for SteppingClock {
+        fn now_us(&mut self) -> u64 {
+            let now = self.now;
+            self.now += self.step;
+            now
+        }
+    }
+
+    /// The time a measurement takes is subtracted from the wait.
+    #[test]
+    fn test_sampler_compensates_drift() {
+        let addr = 0x68;
+        let expectations = oneshot_expectations(addr, 0, &[1; 2]);
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(4_000),
+            // 10ms have passed since the first sample started
+            DelayTransaction::delay_us(990_000),
+            DelayTransaction::delay_us(4_000),
+        ]);
+        let adc = MCP3425::oneshot(dev, addr, delay);
+        let clock = SteppingClock {
+            now: 0,
+            step: 5_000,
+        };
+        let mut sampler = Sampler::new(adc, Config::default(), 1_000_000, clock);
+        sampler.sample().unwrap();
+        sampler.sample().unwrap();
+        let (adc, _) = sampler.release();
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+
+    /// A measurement longer than the period re-anchors the schedule.
+    #[test]
+    fn test_sampler_overrun() {
+        let addr = 0x68;
+        let expectations = oneshot_expectations(addr, 0, &[1; 3]);
+        let dev = I2cMock::new(&expectations);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(4_000),
+            // No wait between the samples
+            DelayTransaction::delay_us(4_000),
+            DelayTransaction::delay_us(4_000),
+        ]);
+        let adc = MCP3425::oneshot(dev, addr, delay);
+        let clock = SteppingClock {
+            now: 0,
+            step: 20_000,
+        };
+        let mut sampler = Sampler::new(adc, Config::default(), 10_000, clock);
+        for _ in 0..3 {
+            sampler.sample().unwrap();
+        }
+        let (adc, _) = sampler.release();
+        let MCP3425 {
+            mut i2c, mut delay, ..
+        } = adc;
+        i2c.done();
+        delay.done();
+    }
+}
@@ -0,0 +1,405 @@
+//! Ordered measurement sequences with a configuration per entry.
+//!
+//! A [`ScanSequence`](struct.ScanSequence.html) lists the channels to
+//! measure, each with its own gain and resolution, e.g. a thermistor at high
+//! gain and resolution and a supply rail at low gain and high speed on the
+//! same device. Run it with
+//! [`run_scan`](../struct.MCP3425.html#method.run_scan):
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{sequence::ScanSequence, Channel, Gain, Resolution, MCP3425};
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10001011]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! let sequence = ScanSequence::new([
+//!     (Channel::Channel1, Gain::Gain8, Resolution::Bits16Sps15),
+//!     (Channel::Channel1, Gain::Gain1, Resolution::Bits12Sps240),
+//! ]);
+//! let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let [thermistor, battery] = adc.run_scan(&sequence);
+//! # dev.done();
+//! ```
+
+use embedded_hal::delay::DelayNs;
+
+use crate::model::Model;
+use crate::transport::Transport;
+use crate::{
+    Channel, Config, ContinuousMode, Error, Gain, MeasureError, OneShotMode, Resolution, Voltage,
+    MCP3425,
+};
+
+/// An ordered list of measurements, each with its own configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScanSequence<const N: usize> {
+    /// The configuration of every entry, in order.
+    configs: [Config; N],
+}
+
+impl<const N: usize> ScanSequence<N> {
+    /// Create a sequence from `(channel, gain, resolution)` entries.
+    pub fn new(entries: [(Channel, Gain, Resolution); N]) -> Self {
+        ScanSequence {
+            configs: entries
+                .map(|(channel, gain, resolution)| Config::new(resolution, gain, channel)),
+        }
+    }
+
+    /// Create a sequence from complete configurations, e.g. to select a
+    /// [`SaturationPolicy`](../enum.SaturationPolicy.html) per entry.
+    pub fn from_configs(configs: [Config; N]) -> Self {
+        ScanSequence { configs }
+    }
+
+    /// Return the configuration of every entry, in order.
+    pub fn configs(&self) -> &[Config; N] {
+        &self.configs
+    }
+}
+
+/// Iterator over one-shot measurements of every available channel.
+///
+/// Created with [`sweep`](../struct.MCP3425.html#method.sweep). Each channel
+/// is measured when the iterator is advanced, and a failing channel does not
+/// end the iteration.
+#[derive(Debug)]
+pub struct Sweep<'a, I2C, D, DEV> {
+    /// The driver.
+    adc: &'a mut MCP3425<I2C, D, OneShotMode, DEV>,
+    /// The configuration, except for the channel.
+    config: Config,
+    /// Index of the next channel.
+    next: u8,
+}
+
+impl<'a, I2C, D, DEV> Sweep<'a, I2C, D, DEV> {
+    pub(crate) fn new(adc: &'a mut MCP3425<I2C, D, OneShotMode, DEV>, config: Config) -> Self {
+        Sweep {
+            adc,
+            config,
+            next: 0,
+        }
+    }
+}
+
+impl<I2C, D, DEV> Iterator for Sweep<'_, I2C, D, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    type Item = (Channel, Result<Voltage, MeasureError<I2C::Error>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= DEV::CHANNELS {
+            return None;
+        }
+        let channel = Channel::try_from(self.next).ok()?;
+        self.next += 1;
+        let config = Config {
+            channel,
+            ..self.config
+        };
+        Some((channel, self.adc.measure(&config)))
+    }
+}
+
+/// Round-robin acquisition of several channels in continuous mode.
+///
+/// Every call to [`sample`](#method.sample) switches the device to the next
+/// channel and returns the first conversion result after the switch, so
+/// results of the previous channel are never returned. Between two samples,
+/// the scanner waits for the pacing delay.
+///
+/// ```
+/// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+/// use mcp3425::{sequence::Scanner, Channel, Config, MCP3425};
+///
+/// # let mut dev = Mock::new(&[
+/// #     Transaction::write(0x68, vec![0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b10010000]),
+/// # ]);
+/// # let i2c = dev.clone();
+/// let adc = MCP3425::continuous(i2c, 0x68, NoopDelay);
+/// let mut scanner = Scanner::new(adc, Config::default(), [Channel::Channel1], 10_000);
+/// let (channel, voltage) = scanner.sample();
+/// # dev.done();
+/// ```
+#[derive(Debug)]
+pub struct Scanner<I2C, D, DEV, const N: usize> {
+    /// The driver.
+    adc: MCP3425<I2C, D, ContinuousMode, DEV>,
+    /// The configuration of every channel, in scan order.
+    configs: [Config; N],
+    /// Index of the next channel.
+    next: usize,
+    /// Time to wait between two samples, in microseconds.
+    pacing_us: u32,
+    /// Whether a sample was taken already.
+    started: bool,
+}
+
+impl<I2C, D, DEV, const N: usize> Scanner<I2C, D, DEV, N>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    /// Create a scanner that measures `channels` in order, with the
+    /// resolution and gain of `config`, waiting `pacing_us` microseconds
+    /// between two samples.
+    ///
+    /// Panics if `channels` is empty.
+    pub fn new(
+        adc: MCP3425<I2C, D, ContinuousMode, DEV>,
+        config: Config,
+        channels: [Channel; N],
+        pacing_us: u32,
+    ) -> Self {
+        let configs = channels.map(|channel| Config { channel, ..config });
+        Scanner::from_configs(adc, configs, pacing_us)
+    }
+
+    /// Create a scanner that cycles through complete configurations, e.g.
+    /// to use a different gain per channel.
+    ///
+    /// Panics if `configs` is empty.
+    pub fn from_configs(
+        adc: MCP3425<I2C, D, ContinuousMode, DEV>,
+        configs: [Config; N],
+        pacing_us: u32,
+    ) -> Self {
+        assert!(N > 0, "scanner without channels");
+        Scanner {
+            adc,
+            configs,
+            next: 0,
+            pacing_us,
+            started: false,
+        }
+    }
+
+    /// Switch to the next channel and return its first conversion result,
+    /// together with the channel.
+    pub fn sample(&mut self) -> (Channel, Result<Voltage, Error<I2C::Error>>) {
+        let (config, result) = self.advance();
+        (config.channel, result)
+    }
+
+    /// Switch to the next configuration and return it, together with its
+    /// first conversion result.
+    fn advance(&mut self) -> (Config, Result<Voltage, Error<I2C::Error>>) {
+        if self.started {
+            self.adc.delay.delay_us(self.pacing_us);
+        }
+        self.started = true;
+
+        let config = self.configs[self.next];
+        self.next = (self.next + 1) % N;
+        (config, self.switch_and_read(&config))
+    }
+
+    /// Write `config`, wait for the first conversion and return its result.
+    fn switch_and_read(&mut self, config: &Config) -> Result<Voltage, Error<I2C::Error>> {
+        // Always write the configuration, even with a single channel, so the
+        // result never predates this call. Waiting for the first measurement
+        // already read the result, so it is not fresh anymore.
+        self.adc.set_config(config)?;
+        Ok(self.adc.read_measurement_full()?.voltage)
+    }
+
+    /// Destroy the scanner and return the driver.
+    pub fn release(self) -> MCP3425<I2C, D, ContinuousMode, DEV> {
+        self.adc
+    }
+}
+
+/// Alternation between two configurations in continuous mode.
+///
+/// A common pattern for voltage and current monitoring, e.g. on the MCP3427
+/// with the voltage divider on channel 1 at gain 1 and the shunt on
+/// channel 2 at gain 8. Every call to [`sample`](#method.sample) writes the
+/// other configuration and returns the first conversion result after the
+/// switch.
+///
+/// ```
+/// # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+/// use mcp3425::{sequence::Alternator, Config, Gain, MCP3425};
+///
+/// # let mut dev = Mock::new(&[
+/// #     Transaction::write(0x68, vec![0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b10010000]),
+/// #     Transaction::write(0x68, vec![0b00010011]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b00010011]),
+/// #     Transaction::read(0x68, vec![0, 0, 0b10010011]),
+/// # ]);
+/// # let i2c = dev.clone();
+/// let adc = MCP3425::continuous(i2c, 0x68, NoopDelay);
+/// let voltage_config = Config::default();
+/// let current_config = Config::default().with_gain(Gain::Gain8);
+/// let mut alternator = Alternator::new(adc, voltage_config, current_config);
+/// let (_, voltage) = alternator.sample();
+/// let (_, current) = alternator.sample();
+/// # dev.done();
+/// ```
+#[derive(Debug)]
+pub struct Alternator<I2C, D, DEV> {
+    /// Scanner over both configurations, without pacing.
+    scanner: Scanner<I2C, D, DEV, 2>,
+}
+
+impl<I2C, D, DEV> Alternator<I2C, D, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    DEV: Model,
+{
+    /// Create an alternator that starts with `first`.
+    pub fn new(adc: MCP3425<I2C, D, ContinuousMode, DEV>, first: Config, second: Config) -> Self {
+        Alternator {
+            scanner: Scanner::from_configs(adc, [first, second], 0),
+        }
+    }
+
+    /// Switch to the other configuration and return its first conversion
+    /// result, together with the configuration.
+    pub fn sample(&mut self) -> (Config, Result<Voltage, Error<I2C::Error>>) {
+        self.scanner.advance()
+    }
+
+    /// Destroy the alternator and return the driver.
+    pub fn release(self) -> MCP3425<I2C, D, ContinuousMode, DEV> {
+        self.scanner.release()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use super::*;
+
+    /// A failing entry does not stop the remaining entries.
+    #[test]
+    fn test_run_scan() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10001011]),
+            Transaction::read(0x68, vec![0x7F, 0xFF, 0b00001011]),
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0, 1, 0b00000000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay);
+        let sequence = ScanSequence::new([
+            (Channel::Channel1, Gain::Gain8, Resolution::Bits16Sps15),
+            (Channel::Channel1, Gain::Gain1, Resolution::Bits12Sps240),
+        ]);
+        let [first, second] = adc.run_scan(&sequence);
+        assert!(
+            matches!(first, Err(MeasureError::VoltageTooHigh)),
+            "{:?}",
+            first
+        );
+        assert_eq!(second.unwrap().channel, Channel::Channel1);
+        adc.destroy().done();
+    }
+
+    /// The configuration is rewritten before every sample.
+    #[test]
+    fn test_scanner() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00010001]),
+            Transaction::read(0x68, vec![0, 1, 0b00010001]),
+            Transaction::read(0x68, vec![0, 1, 0b10010001]),
+            Transaction::write(0x68, vec![0b00010001]),
+            Transaction::read(0x68, vec![0, 2, 0b00010001]),
+            Transaction::read(0x68, vec![0, 2, 0b10010001]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        let config = Config::default().with_gain(Gain::Gain2);
+        let mut scanner = Scanner::new(adc, config, [Channel::Channel1; 2], 1_000);
+        let (channel, voltage) = scanner.sample();
+        assert_eq!(channel, Channel::Channel1);
+        assert!(voltage.is_ok());
+        assert!(scanner.sample().1.is_ok());
+        scanner.release().destroy().done();
+    }
+
+    /// Both configurations are written in turn.
+    #[test]
+    fn test_alternator() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00010000]),
+            Transaction::read(0x68, vec![0, 1, 0b00010000]),
+            Transaction::read(0x68, vec![0, 1, 0b10010000]),
+            Transaction::write(0x68, vec![0b00010011]),
+            Transaction::read(0x68, vec![0, 2, 0b00010011]),
+            Transaction::read(0x68, vec![0, 2, 0b10010011]),
+            Transaction::write(0x68, vec![0b00010000]),
+            Transaction::read(0x68, vec![0, 3, 0b00010000]),
+            Transaction::read(0x68, vec![0, 3, 0b10010000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let adc = MCP3425::continuous(dev, 0x68, NoopDelay);
+        let second = Config::default().with_gain(Gain::Gain8);
+        let mut alternator = Alternator::new(adc, Config::default(), second);
+        assert_eq!(alternator.sample().0, Config::default());
+        let (config, result) = alternator.sample();
+        assert_eq!(config, second);
+        assert!(result.is_ok());
+        assert_eq!(alternator.sample().0, Config::default());
+        alternator.release().destroy().done();
+    }
+
+    /// Every available channel is measured once.
+    #[test]
+    fn test_sweep() {
+        use crate::model::Mcp3425;
+
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0, 1, 0]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay).with_model(Mcp3425);
+        let mut sweep = adc.sweep(&Config::default());
+        let (channel, voltage) = sweep.next().unwrap();
+        assert_eq!(channel, Channel::Channel1);
+        assert!(voltage.is_ok());
+        assert!(sweep.next().is_none());
+        adc.destroy().done();
+    }
+
+    /// The sweep stops at the channel count of the model.
+    #[test]
+    #[cfg(feature = "quad_channel")]
+    fn test_sweep_model_channels() {
+        use crate::model::Mcp3426;
+
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0x07, 0xFF, 0]),
+            Transaction::write(0x68, vec![0b10100000]),
+            Transaction::read(0x68, vec![0, 1, 0b00100000]),
+        ];
+        let dev = I2cMock::new(&expectations);
+        let mut adc = MCP3425::oneshot(dev, 0x68, NoopDelay).with_model(Mcp3426);
+        let results: Vec<_> = adc.sweep(&Config::default()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, Channel::Channel2);
+        adc.destroy().done();
+    }
+}
@@ -0,0 +1,126 @@
+//! Constructors for sharing one I²C bus between several drivers.
+//!
+//! The wrapper types from
+//! [`embedded-hal-bus`](https://docs.rs/embedded-hal-bus) allow multiple
+//! drivers (e.g. several ADCs, or an ADC and other sensors) to use the same
+//! I²C peripheral. The constructors in this module create the wrapper for you,
+//! so you don't have to spell out the device types yourself:
+//!
+//! ```no_run
+//! # extern crate linux_embedded_hal;
+//! use core::cell::RefCell;
+//! use linux_embedded_hal::{Delay, I2cdev};
+//! use mcp3425::MCP3425;
+//!
+//! let bus = RefCell::new(I2cdev::new("/dev/i2c-1").unwrap());
+//! let mut adc1 = MCP3425::oneshot_shared(&bus, 0x68, Delay);
+//! let mut adc2 = MCP3425::oneshot_shared(&bus, 0x69, Delay);
+//! ```
+//!
+//! Use [`RefCellDevice`](struct.RefCellDevice.html) if all drivers live in
+//! the same execution context, and
+//! [`CriticalSectionDevice`](struct.CriticalSectionDevice.html) if the bus is
+//! shared with interrupt handlers or between threads.
+//!
+//! Only available if the `shared_bus` Cargo feature is enabled.
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+pub use embedded_hal_bus::i2c::{CriticalSectionDevice, RefCellDevice};
+
+use crate::{ContinuousMode, OneShotMode, MCP3425};
+
+impl<'a, BUS, D> MCP3425<RefCellDevice<'a, BUS>, D, OneShotMode>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in One-Shot mode on a bus shared
+    /// through a `RefCell`.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn oneshot_shared(bus: &'a RefCell<BUS>, address: u8, delay: D) -> Self {
+        MCP3425::oneshot(RefCellDevice::new(bus), address, delay)
+    }
+}
+
+impl<'a, BUS, D> MCP3425<RefCellDevice<'a, BUS>, D, ContinuousMode>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in Continuous Measurement mode on a bus
+    /// shared through a `RefCell`.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn continuous_shared(bus: &'a RefCell<BUS>, address: u8, delay: D) -> Self {
+        MCP3425::continuous(RefCellDevice::new(bus), address, delay)
+    }
+}
+
+impl<'a, BUS, D> MCP3425<CriticalSectionDevice<'a, BUS>, D, OneShotMode>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in One-Shot mode on a bus shared
+    /// through a critical section mutex.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn oneshot_critical_section(bus: &'a Mutex<RefCell<BUS>>, address: u8, delay: D) -> Self {
+        MCP3425::oneshot(CriticalSectionDevice::new(bus), address, delay)
+    }
+}
+
+impl<'a, BUS, D> MCP3425<CriticalSectionDevice<'a, BUS>, D, ContinuousMode>
+where
+    BUS: I2c,
+    D: DelayNs,
+{
+    /// Initialize the MCP3425 driver in Continuous Measurement mode on a bus
+    /// shared through a critical section mutex.
+    ///
+    /// This constructor is side-effect free, so it will not write any
+    /// configuration to the device until a first measurement is triggered.
+    pub fn continuous_critical_section(
+        bus: &'a Mutex<RefCell<BUS>>,
+        address: u8,
+        delay: D,
+    ) -> Self {
+        MCP3425::continuous(CriticalSectionDevice::new(bus), address, delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use super::*;
+    use crate::Config;
+
+    /// Two drivers with different addresses can use the same bus.
+    #[test]
+    fn test_two_devices_on_shared_bus() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b10000000]),
+            Transaction::read(0x68, vec![0, 0, 0]),
+            Transaction::write(0x69, vec![0b10000000]),
+            Transaction::read(0x69, vec![0, 0, 0]),
+        ];
+        let bus = RefCell::new(I2cMock::new(&expectations));
+        let mut adc1 = MCP3425::oneshot_shared(&bus, 0x68, NoopDelay);
+        let mut adc2 = MCP3425::oneshot_shared(&bus, 0x69, NoopDelay);
+        adc1.measure(&Config::default()).unwrap();
+        adc2.measure(&Config::default()).unwrap();
+        bus.into_inner().done();
+    }
+}
@@ -0,0 +1,144 @@
+//! Putting the device into standby when the driver is dropped.
+//!
+//! In continuous mode, the device keeps converting after the driver is gone,
+//! e.g. after a task exited or panicked. Wrap the driver in a
+//! [`PowerDownOnDrop`](struct.PowerDownOnDrop.html) to write a one-shot
+//! configuration when it is dropped, which puts the device into its
+//! low-power standby state:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{Config, MCP3425};
+//!
+//! # let bus = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b00010000]),
+//! #     Transaction::read(0x68, vec![0, 0, 0b00010000]),
+//! #     Transaction::write(0x68, vec![0b00000000]),
+//! # ]);
+//! # let i2c = bus.clone();
+//! let mut adc = MCP3425::continuous(i2c, 0x68, NoopDelay).power_down_on_drop();
+//! adc.set_config(&Config::default()).unwrap();
+//! // ...
+//! drop(adc);
+//! # bus.clone().done();
+//! ```
+//!
+//! This is opt-in, since errors can't be reported from `drop`: If writing the
+//! configuration fails, the error is ignored.
+
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal::delay::DelayNs;
+
+use crate::model::Model;
+use crate::transport::Transport;
+use crate::{ContinuousConversion, MCP3425};
+
+/// A continuous mode driver that puts the device into standby when dropped.
+///
+/// Created with
+/// [`power_down_on_drop`](../struct.MCP3425.html#method.power_down_on_drop).
+/// All driver methods can be used through `Deref`.
+#[derive(Debug)]
+pub struct PowerDownOnDrop<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    /// The wrapped driver. Only `None` after `into_inner`.
+    adc: Option<MCP3425<I2C, D, M, DEV>>,
+}
+
+impl<I2C, D, M, DEV> PowerDownOnDrop<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    pub(crate) fn new(adc: MCP3425<I2C, D, M, DEV>) -> Self {
+        PowerDownOnDrop { adc: Some(adc) }
+    }
+
+    /// Return the wrapped driver without powering down the device.
+    pub fn into_inner(mut self) -> MCP3425<I2C, D, M, DEV> {
+        self.adc.take().expect("driver already taken")
+    }
+}
+
+impl<I2C, D, M, DEV> Deref for PowerDownOnDrop<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    type Target = MCP3425<I2C, D, M, DEV>;
+
+    fn deref(&self) -> &Self::Target {
+        self.adc.as_ref().expect("driver already taken")
+    }
+}
+
+impl<I2C, D, M, DEV> DerefMut for PowerDownOnDrop<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.adc.as_mut().expect("driver already taken")
+    }
+}
+
+impl<I2C, D, M, DEV> Drop for PowerDownOnDrop<I2C, D, M, DEV>
+where
+    I2C: Transport,
+    D: DelayNs,
+    M: ContinuousConversion,
+    DEV: Model,
+{
+    fn drop(&mut self) {
+        if let Some(adc) = self.adc.as_mut() {
+            // Errors can't be reported from here
+            let _ = adc.power_down();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction},
+    };
+
+    use crate::{Config, MCP3425};
+
+    /// Dropping the wrapper writes a one-shot configuration.
+    #[test]
+    fn test_power_down_on_drop() {
+        let expectations = [
+            Transaction::write(0x68, vec![0b00011000]),
+            Transaction::read(0x68, vec![0, 0, 0b00011000]),
+            Transaction::write(0x68, vec![0b00001000]),
+        ];
+        let bus = I2cMock::new(&expectations);
+        let mut adc = MCP3425::continuous(bus.clone(), 0x68, NoopDelay).power_down_on_drop();
+        adc.set_config(&Config::high_resolution()).unwrap();
+        drop(adc);
+        bus.clone().done();
+    }
+
+    /// Unwrapping the driver does not power down the device.
+    #[test]
+    fn test_into_inner() {
+        let bus = I2cMock::new(&[]);
+        let adc = MCP3425::continuous(bus.clone(), 0x68, NoopDelay).power_down_on_drop();
+        drop(adc.into_inner());
+        bus.clone().done();
+    }
+}
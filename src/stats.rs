@@ -0,0 +1,409 @@
+//! Per-channel statistics across scan iterations.
+//!
+//! A [`ChannelStats`](struct.ChannelStats.html) accumulates the minimum,
+//! maximum and mean voltage of every channel without allocating, e.g. to
+//! report rail statistics from monitoring firmware:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c::{Mock, Transaction}};
+//! use mcp3425::{sequence::ScanSequence, stats::ChannelStats, Channel, Gain, Resolution, MCP3425};
+//!
+//! # let mut dev = Mock::new(&[
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 5, 0]),
+//! #     Transaction::write(0x68, vec![0b10000000]),
+//! #     Transaction::read(0x68, vec![0, 7, 0]),
+//! # ]);
+//! # let i2c = dev.clone();
+//! let mut adc = MCP3425::oneshot(i2c, 0x68, NoopDelay);
+//! let sequence = ScanSequence::new([(Channel::Channel1, Gain::Gain1, Resolution::Bits12Sps240)]);
+//! let mut stats = ChannelStats::<1>::new();
+//! for _ in 0..2 {
+//!     for reading in adc.run_scan(&sequence).iter().flatten() {
+//!         stats.record(reading);
+//!     }
+//! }
+//! let summary = stats.summary(Channel::Channel1).unwrap();
+//! assert_eq!(summary.count, 2);
+//! # dev.done();
+//! ```
+//!
+//! For the statistics of consecutive samples of a single input, including the
+//! standard deviation, see
+//! [`measure_stats`](../struct.MCP3425.html#method.measure_stats).
+
+use crate::{voltage_from_microvolts, voltage_to_microvolts, Channel, ChannelReading, Voltage};
+
+/// Minimum, maximum and mean voltage of a channel.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Summary {
+    /// The lowest recorded voltage.
+    pub min: Voltage,
+    /// The highest recorded voltage.
+    pub max: Voltage,
+    /// The mean of all recorded voltages, rounded towards zero to whole
+    /// microvolts.
+    pub mean: Voltage,
+    /// The number of recorded voltages.
+    pub count: u32,
+}
+
+/// Running values of a single channel, in microvolts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Accumulator {
+    min_uv: i32,
+    max_uv: i32,
+    sum_uv: i64,
+    count: u32,
+}
+
+impl Accumulator {
+    const EMPTY: Self = Accumulator {
+        min_uv: i32::MAX,
+        max_uv: i32::MIN,
+        sum_uv: 0,
+        count: 0,
+    };
+}
+
+/// Minimum, maximum and mean voltage of the first `N` channels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChannelStats<const N: usize> {
+    /// The values of every channel, by channel index.
+    channels: [Accumulator; N],
+}
+
+impl<const N: usize> ChannelStats<N> {
+    /// Create a tracker without recorded values.
+    pub const fn new() -> Self {
+        ChannelStats {
+            channels: [Accumulator::EMPTY; N],
+        }
+    }
+
+    /// Record the voltage of a reading.
+    ///
+    /// Panics if the channel index of the reading is not below `N`.
+    pub fn record(&mut self, reading: &ChannelReading) {
+        let microvolts = voltage_to_microvolts(&reading.voltage);
+        let channel = &mut self.channels[usize::from(reading.channel.index())];
+        channel.min_uv = channel.min_uv.min(microvolts);
+        channel.max_uv = channel.max_uv.max(microvolts);
+        channel.sum_uv += i64::from(microvolts);
+        channel.count = channel.count.saturating_add(1);
+    }
+
+    /// Return the statistics of `channel`, or `None` if no voltage was
+    /// recorded for it or its index is not below `N`.
+    pub fn summary(&self, channel: Channel) -> Option<Summary> {
+        let channel = self.channels.get(usize::from(channel.index()))?;
+        if channel.count == 0 {
+            return None;
+        }
+        // The mean is within the range of the samples, so it fits
+        let mean_uv = channel.sum_uv / i64::from(channel.count);
+        Some(Summary {
+            min: voltage_from_microvolts(channel.min_uv),
+            max: voltage_from_microvolts(channel.max_uv),
+            mean: voltage_from_microvolts(mean_uv as i32),
+            count: channel.count,
+        })
+    }
+
+    /// Discard all recorded values.
+    pub fn reset(&mut self) {
+        self.channels = [Accumulator::EMPTY; N];
+    }
+}
+
+impl<const N: usize> Default for ChannelStats<N> {
+    fn default() -> Self {
+        ChannelStats::new()
+    }
+}
+
+/// Statistics of a window of consecutive samples.
+///
+/// Returned by [`measure_stats`](../struct.MCP3425.html#method.measure_stats).
+/// All values are calculated with integer math.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stats {
+    /// The lowest sample.
+    pub min: Voltage,
+    /// The highest sample.
+    pub max: Voltage,
+    /// The mean of all samples, rounded towards zero to whole microvolts.
+    pub mean: Voltage,
+    /// The population standard deviation in microvolts.
+    pub std_dev_uv: u32,
+    /// The number of samples at the limits of the input range.
+    pub saturated_count: u16,
+    /// The number of samples.
+    pub count: u16,
+}
+
+/// Running sums of samples: Microvolts for [`Stats`](struct.Stats.html),
+/// output codes for [`NoiseReport`](struct.NoiseReport.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct StatsAccumulator {
+    min: i32,
+    max: i32,
+    sum: i64,
+    sum_sq: u128,
+    saturated_count: u16,
+    count: u16,
+}
+
+impl StatsAccumulator {
+    pub(crate) const fn new() -> Self {
+        StatsAccumulator {
+            min: i32::MAX,
+            max: i32::MIN,
+            sum: 0,
+            sum_sq: 0,
+            saturated_count: 0,
+            count: 0,
+        }
+    }
+
+    /// Add a sample.
+    pub(crate) fn push(&mut self, sample: i32, saturated: bool) {
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+        self.sum += i64::from(sample);
+        // At most 65535 samples of up to 2³¹µV (calibrated samples are not
+        // limited to the input range), so the sum of squares fits in 2⁷⁸
+        self.sum_sq += u128::from(sample.unsigned_abs()).pow(2);
+        self.saturated_count += u16::from(saturated);
+        self.count += 1;
+    }
+
+    /// Return the variance multiplied by `scale`.
+    ///
+    /// Var(X) = (n * sum(X²) - sum(X)²) / n², without rounding the mean
+    /// first, which would distort small variances of large values.
+    fn scaled_variance(&self, scale: u32) -> u64 {
+        let n = u128::from(self.count);
+        let spread = (n * self.sum_sq).saturating_sub(u128::from(self.sum.unsigned_abs()).pow(2));
+        u64::try_from(spread * u128::from(scale) / (n * n)).unwrap_or(u64::MAX)
+    }
+
+    /// Return the statistics of microvolt samples, or `None` if no sample
+    /// was added.
+    pub(crate) fn finish(&self) -> Option<Stats> {
+        if self.count == 0 {
+            return None;
+        }
+        let mean_uv = self.sum / i64::from(self.count);
+        Some(Stats {
+            min: voltage_from_microvolts(self.min),
+            max: voltage_from_microvolts(self.max),
+            mean: voltage_from_microvolts(mean_uv as i32),
+            std_dev_uv: isqrt(self.scaled_variance(1)) as u32,
+            saturated_count: self.saturated_count,
+            count: self.count,
+        })
+    }
+
+    /// Return the noise report of output code samples at `res_bits` bits, or
+    /// `None` if no sample was added.
+    pub(crate) fn noise_report(&self, res_bits: u8) -> Option<NoiseReport> {
+        if self.count == 0 {
+            return None;
+        }
+        let peak_to_peak = self.max.abs_diff(self.min);
+        // Standard deviation with 8 fractional bits
+        let std_dev = isqrt(self.scaled_variance(1 << 16));
+        let bits = i64::from(res_bits) << 8;
+        let effective_bits = bits - (log2_fixed(std_dev) - (8 << 8)).max(0);
+        let noise_free_bits = bits - log2_fixed(u64::from(peak_to_peak).max(1));
+        Some(NoiseReport {
+            peak_to_peak_codes: peak_to_peak,
+            std_dev_codes: std_dev as f32 / 256.0,
+            effective_bits: effective_bits as f32 / 256.0,
+            noise_free_bits: noise_free_bits as f32 / 256.0,
+            count: self.count,
+        })
+    }
+}
+
+/// Noise of a presumed-stable input, in output codes.
+///
+/// Returned by [`measure_noise`](../struct.MCP3425.html#method.measure_noise).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseReport {
+    /// The difference between the highest and lowest code.
+    pub peak_to_peak_codes: u32,
+    /// The population standard deviation of the codes.
+    pub std_dev_codes: f32,
+    /// The effective number of bits (ENOB), i.e. the resolution minus the
+    /// bits covered by the standard deviation. A standard deviation below
+    /// one code is counted as one code.
+    pub effective_bits: f32,
+    /// The noise-free resolution, i.e. the resolution minus the bits covered
+    /// by the peak-to-peak noise.
+    pub noise_free_bits: f32,
+    /// The number of samples.
+    pub count: u16,
+}
+
+/// Return the base 2 logarithm of `value` with 8 fractional bits, rounded
+/// down. `value` must not be 0.
+fn log2_fixed(value: u64) -> i64 {
+    let integer = 63 - value.leading_zeros();
+    // Normalize to [1, 2) with 32 fractional bits
+    let mut x = if integer > 32 {
+        value >> (integer - 32)
+    } else {
+        value << (32 - integer)
+    };
+    let mut result = i64::from(integer) << 8;
+    for bit in (0..8).rev() {
+        x = ((u128::from(x) * u128::from(x)) >> 32) as u64;
+        if x >= 2 << 32 {
+            x >>= 1;
+            result |= 1 << bit;
+        }
+    }
+    result
+}
+
+/// Return the integer square root of `value`, rounded down.
+fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method, starting above the root
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(millivolts: i32) -> Voltage {
+        voltage_from_microvolts(millivolts * 1000)
+    }
+
+    fn reading(channel: Channel, millivolts: i32) -> ChannelReading {
+        ChannelReading {
+            channel,
+            voltage: mv(millivolts),
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut stats = ChannelStats::<1>::new();
+        assert_eq!(stats.summary(Channel::Channel1), None);
+        for millivolts in [10, -4, 7] {
+            stats.record(&reading(Channel::Channel1, millivolts));
+        }
+        let summary = stats.summary(Channel::Channel1).unwrap();
+        assert_eq!(summary.min, mv(-4));
+        assert_eq!(summary.max, mv(10));
+        assert_eq!(summary.mean, voltage_from_microvolts(4_333));
+        assert_eq!(summary.count, 3);
+
+        stats.reset();
+        assert_eq!(stats.summary(Channel::Channel1), None);
+    }
+
+    /// Channels are tracked separately.
+    #[test]
+    #[cfg(any(feature = "dual_channel", feature = "quad_channel"))]
+    fn test_channels_are_separate() {
+        let mut stats = ChannelStats::<2>::new();
+        stats.record(&reading(Channel::Channel1, 1));
+        stats.record(&reading(Channel::Channel2, 100));
+        assert_eq!(stats.summary(Channel::Channel1).unwrap().max, mv(1));
+        assert_eq!(stats.summary(Channel::Channel2).unwrap().min, mv(100));
+    }
+
+    #[test]
+    fn test_log2_fixed() {
+        assert_eq!(log2_fixed(1), 0);
+        assert_eq!(log2_fixed(2), 256);
+        assert_eq!(log2_fixed(1 << 40), 40 << 8);
+        // log2(3) = 1.58496
+        assert_eq!(log2_fixed(3), 405);
+        assert_eq!(log2_fixed(u64::MAX), (64 << 8) - 1);
+    }
+
+    #[test]
+    fn test_noise_report() {
+        let mut acc = StatsAccumulator::new();
+        for code in [1000, 1002, 1000, 998] {
+            acc.push(code, false);
+        }
+        let report = acc.noise_report(16).unwrap();
+        assert_eq!(report.peak_to_peak_codes, 4);
+        // Standard deviation sqrt(2)
+        assert_eq!(report.std_dev_codes, 362.0 / 256.0);
+        assert_eq!(report.effective_bits, 16.0 - 127.0 / 256.0);
+        assert_eq!(report.noise_free_bits, 14.0);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for value in [0u64, 1, 2, 3, 4, 15, 16, 17, 1_000_000, u64::MAX] {
+            let root = isqrt(value);
+            assert!(root * root <= value);
+            assert!((root + 1)
+                .checked_mul(root + 1)
+                .map_or(true, |sq| sq > value));
+        }
+    }
+
+    #[test]
+    fn test_stats_accumulator() {
+        let mut acc = StatsAccumulator::new();
+        assert_eq!(acc.finish(), None);
+        for microvolts in [2_000, 4_000, 4_000, 4_000, 5_000, 5_000, 7_000, 9_000] {
+            acc.push(microvolts, false);
+        }
+        acc.push(5_000, true);
+        let stats = acc.finish().unwrap();
+        assert_eq!(stats.min, mv(2));
+        assert_eq!(stats.max, mv(9));
+        assert_eq!(stats.mean, mv(5));
+        assert_eq!(stats.std_dev_uv, 1_885);
+        assert_eq!(stats.saturated_count, 1);
+        assert_eq!(stats.count, 9);
+    }
+
+    /// Small variations of large voltages are not lost to rounding.
+    #[test]
+    fn test_stats_accumulator_offset() {
+        let mut acc = StatsAccumulator::new();
+        for microvolts in [2_000_000, 2_000_001, 2_000_001] {
+            acc.push(microvolts, false);
+        }
+        assert_eq!(acc.finish().unwrap().std_dev_uv, 0);
+
+        let mut acc = StatsAccumulator::new();
+        for microvolts in [2_000_000, 2_000_002] {
+            acc.push(microvolts, false);
+        }
+        assert_eq!(acc.finish().unwrap().std_dev_uv, 1);
+    }
+
+    /// Calibrated samples beyond the input range don't overflow the sums.
+    #[test]
+    fn test_stats_accumulator_extremes() {
+        let mut acc = StatsAccumulator::new();
+        for _ in 0..u16::MAX / 2 {
+            acc.push(i32::MIN, false);
+            acc.push(i32::MAX, false);
+        }
+        let stats = acc.finish().unwrap();
+        assert_eq!(stats.count, u16::MAX - 1);
+        assert_eq!(stats.std_dev_uv, 2_147_483_647);
+    }
+}
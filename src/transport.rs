@@ -0,0 +1,134 @@
+//! Abstraction of the raw register access.
+//!
+//! The driver does not talk to the bus directly, but through the
+//! [`Transport`](trait.Transport.html) trait. It is implemented for every
+//! [`embedded_hal::i2c::I2c`] bus, so usually there is nothing to do.
+//!
+//! For devices that are not connected to a local I²C bus, e.g. behind an
+//! MCP2221 USB bridge or an I²C-over-UART tunnel, implement `Transport` for
+//! the bridge and pass it to the driver like an I²C bus:
+//!
+//! ```
+//! use mcp3425::{transport::Transport, Config, MCP3425};
+//! # use embedded_hal_mock::eh1::delay::NoopDelay;
+//!
+//! struct Bridge;
+//!
+//! impl Transport for Bridge {
+//!     type Error = ();
+//!
+//!     fn write_command(&mut self, address: u8, command: u8) -> Result<(), ()> {
+//!         // Send the command byte through the bridge
+//! #       let _ = (address, command);
+//!         Ok(())
+//!     }
+//!
+//!     fn read_data(&mut self, address: u8, buf: &mut [u8]) -> Result<(), ()> {
+//!         // Read the output code and config register through the bridge
+//! #       let _ = address;
+//! #       buf.fill(0);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut adc = MCP3425::oneshot(Bridge, 0x68, NoopDelay);
+//! let voltage = adc.measure(&Config::default()).unwrap();
+//! ```
+
+use core::fmt::Debug;
+
+use embedded_hal::i2c::{Error as I2cError, ErrorKind, I2c};
+
+/// Raw access to the registers of an MCP342x device.
+pub trait Transport {
+    /// Error type of the transport.
+    type Error: Debug;
+
+    /// Write the configuration register (or, at the general call address, a
+    /// general call command).
+    fn write_command(&mut self, address: u8, command: u8) -> Result<(), Self::Error>;
+
+    /// Read the output code and configuration register into `buf`.
+    fn read_data(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Return whether `error` means that no device responded at the address.
+    ///
+    /// This is used by [`ping`](../struct.MCP3425.html#method.ping). The
+    /// default implementation never reports a device as absent.
+    fn is_absent(_error: &Self::Error) -> bool {
+        false
+    }
+}
+
+impl<T: I2c> Transport for T {
+    type Error = T::Error;
+
+    fn write_command(&mut self, address: u8, command: u8) -> Result<(), Self::Error> {
+        self.write(address, &[command])
+    }
+
+    fn read_data(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.read(address, buf)
+    }
+
+    fn is_absent(error: &Self::Error) -> bool {
+        matches!(error.kind(), ErrorKind::NoAcknowledge(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+
+    use super::*;
+    use crate::{Config, Error, MCP3425};
+
+    /// A transport that records the last command and returns fixed data.
+    struct FakeTransport {
+        command: Option<(u8, u8)>,
+        data: [u8; 3],
+    }
+
+    impl Transport for FakeTransport {
+        type Error = &'static str;
+
+        fn write_command(&mut self, address: u8, command: u8) -> Result<(), Self::Error> {
+            self.command = Some((address, command));
+            Ok(())
+        }
+
+        fn read_data(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+            if address != 0x68 {
+                return Err("absent");
+            }
+            let len = buf.len().min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_custom_transport() {
+        let transport = FakeTransport {
+            command: None,
+            data: [0x01, 0x00, 0x00],
+        };
+        let mut adc = MCP3425::oneshot(transport, 0x68, NoopDelay);
+        adc.measure(&Config::default()).unwrap();
+
+        let transport = adc.destroy();
+        assert_eq!(transport.command, Some((0x68, 0b10000000)));
+    }
+
+    /// Errors of custom transports are not classified as absent devices.
+    #[test]
+    fn test_custom_transport_ping() {
+        let transport = FakeTransport {
+            command: None,
+            data: [0; 3],
+        };
+        let mut adc = MCP3425::oneshot(transport, 0x69, NoopDelay);
+        let err = adc.ping().unwrap_err();
+        assert!(matches!(err, Error::I2c("absent")), "{:?}", err);
+    }
+}